@@ -2,10 +2,11 @@ use std::path::Path;
 use std::env;
 use std::fs;
 
-use vmlib::{LIBRARY_ENV_VARIABLE, ADDRESS_SIZE, INSTRUCTION_SIZE, INTERRUPT_SIZE, ERROR_CODE_SIZE, ErrorCodes, Interrupts};
+use vmlib::{LIBRARY_ENV_VARIABLE, ADDRESS_SIZE, INSTRUCTION_SIZE, INTERRUPT_SIZE, ERROR_CODE_SIZE, SHIFT_COUNT_SIZE, FLOAT4_SIZE, FLOAT8_SIZE, SHORTEST_FLOAT_PRECISION, ErrorCodes, Interrupts};
+use vmlib::files::{OPEN_READ, OPEN_WRITE, OPEN_APPEND, OPEN_CREATE, OPEN_TRUNCATE, OPEN_EXCLUSIVE, SEEK_START, SEEK_CURRENT, SEEK_END, FILE_KIND_NONE, FILE_KIND_FILE, FILE_KIND_DIRECTORY, FILE_KIND_OTHER, SPAWN_PIPE_STDIN, SPAWN_PIPE_STDOUT};
 
 
-const ARCH_LIB_NAME: &'static str = "archlib.asm";
+const ARCH_LIB_NAME: &str = "archlib.asm";
 
 
 fn exit(message: &str) -> ! {
@@ -36,6 +37,10 @@ fn main() {
     %= INSTRUCTION_SIZE {INSTRUCTION_SIZE}
     %= INTERRUPT_CODE_SIZE {INTERRUPT_SIZE}
     %= ERROR_CODE_SIZE {ERROR_CODE_SIZE}
+    %= SHIFT_COUNT_SIZE {SHIFT_COUNT_SIZE}
+    %= FLOAT4_SIZE {FLOAT4_SIZE}
+    %= FLOAT8_SIZE {FLOAT8_SIZE}
+    %= SHORTEST_FLOAT_PRECISION {SHORTEST_FLOAT_PRECISION}
 
     ; Built-in error codes
 
@@ -43,6 +48,21 @@ fn main() {
     %= GENERIC_ERROR_CODE {GENERIC_ERROR_CODE}
     %= NO_ERROR_CODE {NO_ERROR_CODE}
     %= EOF_ERROR_CODE {EOF_ERROR_CODE}
+    %= DIVISION_BY_ZERO_ERROR_CODE {DIVISION_BY_ZERO_ERROR_CODE}
+    %= OVERFLOW_ERROR_CODE {OVERFLOW_ERROR_CODE}
+    %= OUT_OF_MEMORY_ERROR_CODE {OUT_OF_MEMORY_ERROR_CODE}
+    %= INVALID_POINTER_ERROR_CODE {INVALID_POINTER_ERROR_CODE}
+    %= DOUBLE_FREE_ERROR_CODE {DOUBLE_FREE_ERROR_CODE}
+    %= FILE_NOT_FOUND_ERROR_CODE {FILE_NOT_FOUND_ERROR_CODE}
+    %= PERMISSION_DENIED_ERROR_CODE {PERMISSION_DENIED_ERROR_CODE}
+    %= FILE_EXISTS_ERROR_CODE {FILE_EXISTS_ERROR_CODE}
+    %= INVALID_DESCRIPTOR_ERROR_CODE {INVALID_DESCRIPTOR_ERROR_CODE}
+    %= INVALID_ARGUMENT_ERROR_CODE {INVALID_ARGUMENT_ERROR_CODE}
+    %= IO_ERROR_CODE {IO_ERROR_CODE}
+    %= VARIABLE_NOT_FOUND_ERROR_CODE {VARIABLE_NOT_FOUND_ERROR_CODE}
+    %= CONNECTION_REFUSED_ERROR_CODE {CONNECTION_REFUSED_ERROR_CODE}
+    %= CONNECTION_RESET_ERROR_CODE {CONNECTION_RESET_ERROR_CODE}
+    %= ADDRESS_IN_USE_ERROR_CODE {ADDRESS_IN_USE_ERROR_CODE}
 
     ; Interrupt codes
 
@@ -57,6 +77,88 @@ fn main() {
     %= PRINT_STATIC_STRING_INTR {PRINT_STATIC_STRING_INTR}
     %= READ_BYTES_INTR {READ_BYTES_INTR}
     %= READ_ALL_INTR {READ_ALL_INTR}
+    %= FLUSH_INTR {FLUSH_INTR}
+    %= OPEN_INTR {OPEN_INTR}
+    %= READ_INTR {READ_INTR}
+    %= WRITE_INTR {WRITE_INTR}
+    %= SEEK_INTR {SEEK_INTR}
+    %= CLOSE_INTR {CLOSE_INTR}
+    %= STAT_INTR {STAT_INTR}
+    %= UNLINK_INTR {UNLINK_INTR}
+    %= OPEN_DIR_INTR {OPEN_DIR_INTR}
+    %= READ_DIR_INTR {READ_DIR_INTR}
+    %= ARG_COUNT_INTR {ARG_COUNT_INTR}
+    %= ARG_INTR {ARG_INTR}
+    %= GET_ENV_INTR {GET_ENV_INTR}
+    %= MONOTONIC_TIME_INTR {MONOTONIC_TIME_INTR}
+    %= UNIX_TIME_INTR {UNIX_TIME_INTR}
+    %= SLEEP_INTR {SLEEP_INTR}
+    %= RANDOM_INTR {RANDOM_INTR}
+    %= SPAWN_INTR {SPAWN_INTR}
+    %= WAIT_INTR {WAIT_INTR}
+    %= LISTEN_INTR {LISTEN_INTR}
+    %= ACCEPT_INTR {ACCEPT_INTR}
+    %= CONNECT_INTR {CONNECT_INTR}
+    %= BIND_INTR {BIND_INTR}
+    %= SEND_INTR {SEND_INTR}
+    %= SEND_TO_INTR {SEND_TO_INTR}
+    %= RECV_INTR {RECV_INTR}
+    %= RECV_FROM_INTR {RECV_FROM_INTR}
+    %= LOCAL_PORT_INTR {LOCAL_PORT_INTR}
+    %= PRINT_INT1_INTR {PRINT_INT1_INTR}
+    %= PRINT_INT2_INTR {PRINT_INT2_INTR}
+    %= PRINT_INT4_INTR {PRINT_INT4_INTR}
+    %= PRINT_INT8_INTR {PRINT_INT8_INTR}
+    %= PRINT_HEX1_INTR {PRINT_HEX1_INTR}
+    %= PRINT_HEX2_INTR {PRINT_HEX2_INTR}
+    %= PRINT_HEX4_INTR {PRINT_HEX4_INTR}
+    %= PRINT_HEX8_INTR {PRINT_HEX8_INTR}
+    %= PRINT_BINARY1_INTR {PRINT_BINARY1_INTR}
+    %= PRINT_BINARY2_INTR {PRINT_BINARY2_INTR}
+    %= PRINT_BINARY4_INTR {PRINT_BINARY4_INTR}
+    %= PRINT_BINARY8_INTR {PRINT_BINARY8_INTR}
+    %= PRINT_FLOAT4_INTR {PRINT_FLOAT4_INTR}
+    %= PRINT_FLOAT8_INTR {PRINT_FLOAT8_INTR}
+    %= PRINT_CODE_POINT_INTR {PRINT_CODE_POINT_INTR}
+    %= PRINT_FORMAT_INTR {PRINT_FORMAT_INTR}
+    %= FORMAT_UINT1_INTR {FORMAT_UINT1_INTR}
+    %= FORMAT_UINT2_INTR {FORMAT_UINT2_INTR}
+    %= FORMAT_UINT4_INTR {FORMAT_UINT4_INTR}
+    %= FORMAT_UINT8_INTR {FORMAT_UINT8_INTR}
+    %= FORMAT_INT1_INTR {FORMAT_INT1_INTR}
+    %= FORMAT_INT2_INTR {FORMAT_INT2_INTR}
+    %= FORMAT_INT4_INTR {FORMAT_INT4_INTR}
+    %= FORMAT_INT8_INTR {FORMAT_INT8_INTR}
+    %= FORMAT_FLOAT4_INTR {FORMAT_FLOAT4_INTR}
+    %= FORMAT_FLOAT8_INTR {FORMAT_FLOAT8_INTR}
+    %= PARSE_UINT1_INTR {PARSE_UINT1_INTR}
+    %= PARSE_UINT2_INTR {PARSE_UINT2_INTR}
+    %= PARSE_UINT4_INTR {PARSE_UINT4_INTR}
+    %= PARSE_UINT8_INTR {PARSE_UINT8_INTR}
+    %= PARSE_INT1_INTR {PARSE_INT1_INTR}
+    %= PARSE_INT2_INTR {PARSE_INT2_INTR}
+    %= PARSE_INT4_INTR {PARSE_INT4_INTR}
+    %= PARSE_INT8_INTR {PARSE_INT8_INTR}
+    %= PARSE_FLOAT4_INTR {PARSE_FLOAT4_INTR}
+    %= PARSE_FLOAT8_INTR {PARSE_FLOAT8_INTR}
+
+    ; File system and process interrupt constants
+
+    %= OPEN_READ {OPEN_READ}
+    %= OPEN_WRITE {OPEN_WRITE}
+    %= OPEN_APPEND {OPEN_APPEND}
+    %= OPEN_CREATE {OPEN_CREATE}
+    %= OPEN_TRUNCATE {OPEN_TRUNCATE}
+    %= OPEN_EXCLUSIVE {OPEN_EXCLUSIVE}
+    %= SEEK_START {SEEK_START}
+    %= SEEK_CURRENT {SEEK_CURRENT}
+    %= SEEK_END {SEEK_END}
+    %= FILE_KIND_NONE {FILE_KIND_NONE}
+    %= FILE_KIND_FILE {FILE_KIND_FILE}
+    %= FILE_KIND_DIRECTORY {FILE_KIND_DIRECTORY}
+    %= FILE_KIND_OTHER {FILE_KIND_OTHER}
+    %= SPAWN_PIPE_STDIN {SPAWN_PIPE_STDIN}
+    %= SPAWN_PIPE_STDOUT {SPAWN_PIPE_STDOUT}
 
     ",
         GENERATED_AT = chrono::Utc::now().to_rfc2822(),
@@ -64,6 +166,21 @@ fn main() {
         GENERIC_ERROR_CODE = ErrorCodes::GenericError as i32,
         NO_ERROR_CODE = ErrorCodes::NoError as i32,
         EOF_ERROR_CODE = ErrorCodes::EOF as i32,
+        DIVISION_BY_ZERO_ERROR_CODE = ErrorCodes::DivisionByZero as i32,
+        OVERFLOW_ERROR_CODE = ErrorCodes::Overflow as i32,
+        OUT_OF_MEMORY_ERROR_CODE = ErrorCodes::OutOfMemory as i32,
+        INVALID_POINTER_ERROR_CODE = ErrorCodes::InvalidPointer as i32,
+        DOUBLE_FREE_ERROR_CODE = ErrorCodes::DoubleFree as i32,
+        FILE_NOT_FOUND_ERROR_CODE = ErrorCodes::FileNotFound as i32,
+        PERMISSION_DENIED_ERROR_CODE = ErrorCodes::PermissionDenied as i32,
+        FILE_EXISTS_ERROR_CODE = ErrorCodes::FileExists as i32,
+        INVALID_DESCRIPTOR_ERROR_CODE = ErrorCodes::InvalidDescriptor as i32,
+        INVALID_ARGUMENT_ERROR_CODE = ErrorCodes::InvalidArgument as i32,
+        IO_ERROR_CODE = ErrorCodes::IOError as i32,
+        VARIABLE_NOT_FOUND_ERROR_CODE = ErrorCodes::VariableNotFound as i32,
+        CONNECTION_REFUSED_ERROR_CODE = ErrorCodes::ConnectionRefused as i32,
+        CONNECTION_RESET_ERROR_CODE = ErrorCodes::ConnectionReset as i32,
+        ADDRESS_IN_USE_ERROR_CODE = ErrorCodes::AddressInUse as i32,
         PRINT1_INTR = Interrupts::Print1,
         PRINT2_INTR = Interrupts::Print2,
         PRINT4_INTR = Interrupts::Print4,
@@ -75,6 +192,70 @@ fn main() {
         PRINT_STATIC_STRING_INTR = Interrupts::PrintStaticString,
         READ_BYTES_INTR = Interrupts::ReadBytes,
         READ_ALL_INTR = Interrupts::ReadAll,
+        FLUSH_INTR = Interrupts::Flush,
+        OPEN_INTR = Interrupts::Open,
+        READ_INTR = Interrupts::Read,
+        WRITE_INTR = Interrupts::Write,
+        SEEK_INTR = Interrupts::Seek,
+        CLOSE_INTR = Interrupts::Close,
+        STAT_INTR = Interrupts::Stat,
+        UNLINK_INTR = Interrupts::Unlink,
+        OPEN_DIR_INTR = Interrupts::OpenDir,
+        READ_DIR_INTR = Interrupts::ReadDir,
+        ARG_COUNT_INTR = Interrupts::ArgCount,
+        ARG_INTR = Interrupts::Arg,
+        GET_ENV_INTR = Interrupts::GetEnv,
+        MONOTONIC_TIME_INTR = Interrupts::MonotonicTime,
+        UNIX_TIME_INTR = Interrupts::UnixTime,
+        SLEEP_INTR = Interrupts::Sleep,
+        RANDOM_INTR = Interrupts::Random,
+        SPAWN_INTR = Interrupts::Spawn,
+        WAIT_INTR = Interrupts::Wait,
+        LISTEN_INTR = Interrupts::Listen,
+        ACCEPT_INTR = Interrupts::Accept,
+        CONNECT_INTR = Interrupts::Connect,
+        BIND_INTR = Interrupts::Bind,
+        SEND_INTR = Interrupts::Send,
+        SEND_TO_INTR = Interrupts::SendTo,
+        RECV_INTR = Interrupts::Recv,
+        RECV_FROM_INTR = Interrupts::RecvFrom,
+        LOCAL_PORT_INTR = Interrupts::LocalPort,
+        PRINT_INT1_INTR = Interrupts::PrintInt1,
+        PRINT_INT2_INTR = Interrupts::PrintInt2,
+        PRINT_INT4_INTR = Interrupts::PrintInt4,
+        PRINT_INT8_INTR = Interrupts::PrintInt8,
+        PRINT_HEX1_INTR = Interrupts::PrintHex1,
+        PRINT_HEX2_INTR = Interrupts::PrintHex2,
+        PRINT_HEX4_INTR = Interrupts::PrintHex4,
+        PRINT_HEX8_INTR = Interrupts::PrintHex8,
+        PRINT_BINARY1_INTR = Interrupts::PrintBinary1,
+        PRINT_BINARY2_INTR = Interrupts::PrintBinary2,
+        PRINT_BINARY4_INTR = Interrupts::PrintBinary4,
+        PRINT_BINARY8_INTR = Interrupts::PrintBinary8,
+        PRINT_FLOAT4_INTR = Interrupts::PrintFloat4,
+        PRINT_FLOAT8_INTR = Interrupts::PrintFloat8,
+        PRINT_CODE_POINT_INTR = Interrupts::PrintCodePoint,
+        PRINT_FORMAT_INTR = Interrupts::PrintFormat,
+        FORMAT_UINT1_INTR = Interrupts::FormatUint1,
+        FORMAT_UINT2_INTR = Interrupts::FormatUint2,
+        FORMAT_UINT4_INTR = Interrupts::FormatUint4,
+        FORMAT_UINT8_INTR = Interrupts::FormatUint8,
+        FORMAT_INT1_INTR = Interrupts::FormatInt1,
+        FORMAT_INT2_INTR = Interrupts::FormatInt2,
+        FORMAT_INT4_INTR = Interrupts::FormatInt4,
+        FORMAT_INT8_INTR = Interrupts::FormatInt8,
+        FORMAT_FLOAT4_INTR = Interrupts::FormatFloat4,
+        FORMAT_FLOAT8_INTR = Interrupts::FormatFloat8,
+        PARSE_UINT1_INTR = Interrupts::ParseUint1,
+        PARSE_UINT2_INTR = Interrupts::ParseUint2,
+        PARSE_UINT4_INTR = Interrupts::ParseUint4,
+        PARSE_UINT8_INTR = Interrupts::ParseUint8,
+        PARSE_INT1_INTR = Interrupts::ParseInt1,
+        PARSE_INT2_INTR = Interrupts::ParseInt2,
+        PARSE_INT4_INTR = Interrupts::ParseInt4,
+        PARSE_INT8_INTR = Interrupts::ParseInt8,
+        PARSE_FLOAT4_INTR = Interrupts::ParseFloat4,
+        PARSE_FLOAT8_INTR = Interrupts::ParseFloat8,
     );
 
     fs::write(&arch_lib_path, asm)
@@ -1,5 +1,4 @@
 
-include "reglib.asm"
 include "archlib.asm"
 include "cstring.asm"
 include "io.asm"
@@ -11,13 +10,9 @@ ds "hello\0"
 
 .text
 
-    ;!init_reglib
-
     vctr str
     
     call cstrlen
-
-    !ldr1
     !println8
 
     loadc4 0
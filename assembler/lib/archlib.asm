@@ -1,6 +1,6 @@
 
 
-; Generated Mon, 27 May 2024 18:09:45 +0000
+; Generated Sat, 17 Oct 2026 01:12:58 +0000
 ; This is an automatically generated library file. Do not edit this file manually.
 ; This file contains enrivonment variables for the VM architecture. 
 
@@ -9,9 +9,13 @@
     ; Architecture details
 
     %= ADDRESS_SIZE 8
-    %= INSTRUCTION_SIZE 1
+    %= INSTRUCTION_SIZE 2
     %= INTERRUPT_CODE_SIZE 1
     %= ERROR_CODE_SIZE 4
+    %= SHIFT_COUNT_SIZE 1
+    %= FLOAT4_SIZE 4
+    %= FLOAT8_SIZE 8
+    %= SHORTEST_FLOAT_PRECISION 255
 
     ; Built-in error codes
 
@@ -19,6 +23,21 @@
     %= GENERIC_ERROR_CODE -1
     %= NO_ERROR_CODE 0
     %= EOF_ERROR_CODE 1
+    %= DIVISION_BY_ZERO_ERROR_CODE 2
+    %= OVERFLOW_ERROR_CODE 3
+    %= OUT_OF_MEMORY_ERROR_CODE 4
+    %= INVALID_POINTER_ERROR_CODE 5
+    %= DOUBLE_FREE_ERROR_CODE 6
+    %= FILE_NOT_FOUND_ERROR_CODE 7
+    %= PERMISSION_DENIED_ERROR_CODE 8
+    %= FILE_EXISTS_ERROR_CODE 9
+    %= INVALID_DESCRIPTOR_ERROR_CODE 10
+    %= INVALID_ARGUMENT_ERROR_CODE 11
+    %= IO_ERROR_CODE 12
+    %= VARIABLE_NOT_FOUND_ERROR_CODE 13
+    %= CONNECTION_REFUSED_ERROR_CODE 14
+    %= CONNECTION_RESET_ERROR_CODE 15
+    %= ADDRESS_IN_USE_ERROR_CODE 16
 
     ; Interrupt codes
 
@@ -33,5 +52,87 @@
     %= PRINT_STATIC_STRING_INTR 8
     %= READ_BYTES_INTR 9
     %= READ_ALL_INTR 10
+    %= FLUSH_INTR 11
+    %= OPEN_INTR 12
+    %= READ_INTR 13
+    %= WRITE_INTR 14
+    %= SEEK_INTR 15
+    %= CLOSE_INTR 16
+    %= STAT_INTR 17
+    %= UNLINK_INTR 18
+    %= OPEN_DIR_INTR 19
+    %= READ_DIR_INTR 20
+    %= ARG_COUNT_INTR 21
+    %= ARG_INTR 22
+    %= GET_ENV_INTR 23
+    %= MONOTONIC_TIME_INTR 24
+    %= UNIX_TIME_INTR 25
+    %= SLEEP_INTR 26
+    %= RANDOM_INTR 27
+    %= SPAWN_INTR 28
+    %= WAIT_INTR 29
+    %= LISTEN_INTR 30
+    %= ACCEPT_INTR 31
+    %= CONNECT_INTR 32
+    %= BIND_INTR 33
+    %= SEND_INTR 34
+    %= SEND_TO_INTR 35
+    %= RECV_INTR 36
+    %= RECV_FROM_INTR 37
+    %= LOCAL_PORT_INTR 38
+    %= PRINT_INT1_INTR 39
+    %= PRINT_INT2_INTR 40
+    %= PRINT_INT4_INTR 41
+    %= PRINT_INT8_INTR 42
+    %= PRINT_HEX1_INTR 43
+    %= PRINT_HEX2_INTR 44
+    %= PRINT_HEX4_INTR 45
+    %= PRINT_HEX8_INTR 46
+    %= PRINT_BINARY1_INTR 47
+    %= PRINT_BINARY2_INTR 48
+    %= PRINT_BINARY4_INTR 49
+    %= PRINT_BINARY8_INTR 50
+    %= PRINT_FLOAT4_INTR 51
+    %= PRINT_FLOAT8_INTR 52
+    %= PRINT_CODE_POINT_INTR 53
+    %= PRINT_FORMAT_INTR 54
+    %= FORMAT_UINT1_INTR 55
+    %= FORMAT_UINT2_INTR 56
+    %= FORMAT_UINT4_INTR 57
+    %= FORMAT_UINT8_INTR 58
+    %= FORMAT_INT1_INTR 59
+    %= FORMAT_INT2_INTR 60
+    %= FORMAT_INT4_INTR 61
+    %= FORMAT_INT8_INTR 62
+    %= FORMAT_FLOAT4_INTR 63
+    %= FORMAT_FLOAT8_INTR 64
+    %= PARSE_UINT1_INTR 65
+    %= PARSE_UINT2_INTR 66
+    %= PARSE_UINT4_INTR 67
+    %= PARSE_UINT8_INTR 68
+    %= PARSE_INT1_INTR 69
+    %= PARSE_INT2_INTR 70
+    %= PARSE_INT4_INTR 71
+    %= PARSE_INT8_INTR 72
+    %= PARSE_FLOAT4_INTR 73
+    %= PARSE_FLOAT8_INTR 74
+
+    ; File system and process interrupt constants
+
+    %= OPEN_READ 1
+    %= OPEN_WRITE 2
+    %= OPEN_APPEND 4
+    %= OPEN_CREATE 8
+    %= OPEN_TRUNCATE 16
+    %= OPEN_EXCLUSIVE 32
+    %= SEEK_START 0
+    %= SEEK_CURRENT 1
+    %= SEEK_END 2
+    %= FILE_KIND_NONE 0
+    %= FILE_KIND_FILE 1
+    %= FILE_KIND_DIRECTORY 2
+    %= FILE_KIND_OTHER 3
+    %= SPAWN_PIPE_STDIN 1
+    %= SPAWN_PIPE_STDOUT 2
 
     
\ No newline at end of file
@@ -1,5 +1,3 @@
-
-
 include "io.asm"
 
 
@@ -7,52 +5,29 @@ include "io.asm"
 
 ; ...
 ; arg 8 bytes
-; ret 8 bytes
 ; -> 
+; ...
+; length 8 bytes
 
-; calculate the stack address of the argument
-    loadsp
-    loadc8 8
-    addi8
-
-; load the argument (char*)
-    load8
+; keep the original char* to compare later
     dup8
 
-; current char pointer
-    !r2
-    store8
-
-; set immutable original char* to compare later
-    !r3
-    store8
-
-
     @loop
-    ; load the current char
-        !ldr2
+    ; load the current char and stop at \0
+        dup8
         load1
+        jzc1 end
 
-    ; compare with \0
-        loadc1 '\0'
-        subi1 
-
-    ; update the current char register
-        !ldr2
+    ; advance the current char pointer
         loadc8 1
         addi8
-        !r2
-        store8
 
-        jnzc1 loop
+        jmpconst loop
 
+    @end
     ; calculate the string length
-    !ldr2
-    !ldr3
+    swap8
     subi8
 
-    !r1
-    store8
-
     ret
     
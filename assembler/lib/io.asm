@@ -17,3 +17,10 @@ include "archlib.asm"
 
 %endmacro
 
+
+
+%flush
+
+    intrconst !FLUSH_INTR
+
+%endmacro
@@ -20,7 +20,7 @@ pub fn load_unit_asm<'a>(caller_directory: Option<&Path>, unit_path: &'a Path, s
         .unwrap_or_else(|err| 
             errors::io_error(err, format!("Failed to canonicalize path \"{}\"", unit_path.display()).as_str()));
 
-    if module_manager.is_loaded(&unit_path) {
+    if module_manager.is_loaded(unit_path) {
         // If the module was already imported, return an empty assembly
         return Vec::new();
     }
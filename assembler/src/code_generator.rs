@@ -5,10 +5,11 @@ use crate::module_manager::ModuleManager;
 use crate::errors;
 use crate::symbol_table::StaticValue;
 use crate::{lang::AsmNode, symbol_table::SymbolTable};
-use crate::lang::{AddressLike, AsmInstruction, AsmNodeValue, Number, NumberLike, ENTRY_SECTION_NAME};
+use crate::lang::{AddressLike, AsmInstruction, AsmNodeValue, Number, NumberLike, SectionAttributes, ENTRY_SECTION_NAME};
 use crate::tokenizer::SourceToken;
 
-use vmlib::{ByteCodes, VirtualAddress, ADDRESS_SIZE, ERROR_CODE_SIZE, INSTRUCTION_SIZE, INTERRUPT_SIZE};
+use vmlib::metadata::{ProgramMetadata, Symbol};
+use vmlib::{ByteCodes, VirtualAddress, ADDRESS_SIZE, ERROR_CODE_SIZE, HOST_FUNCTION_ID_SIZE, INSTRUCTION_SIZE, INTERRUPT_SIZE, STACK_INDEX_SIZE};
 
 
 struct UnresolvedLabel<'a> {
@@ -28,22 +29,44 @@ pub fn generate<'a>(asm: &[AsmNode], symbol_table: &'a SymbolTable<'a>, module_m
     // The vector will most probably be reallocated, but this pre-allocation should avoid most minor initial reallocations.
     // In case all nodes are single-byte instructions, all reallocations are prevented.
     // In case the assembly code contains many non-code structures (sections, macros, etc.) this approach may avoid a reallocation.
-    // + 9 to include an initial jump instruction to the entry point (1-byte instruction + 8-byte address)
+    // Include an initial jump instruction to the entry point (instruction code + address)
     let mut bytecode = Vec::with_capacity(asm.len() + ADDRESS_SIZE + INSTRUCTION_SIZE);
 
     let mut label_map: HashMap<&str, VirtualAddress> = HashMap::new();
     let mut unresolved_labels: Vec<UnresolvedLabel> = Vec::new();
+    // Debug symbols in order of declaration, to be included in the program metadata
+    let mut symbols: Vec<Symbol> = Vec::new();
 
-    let mut current_section: Option<&str> = None;
+    // Content of the writable sections, which are placed in the data segment instead of the program space
+    let mut data: Vec<u8> = Vec::new();
+    let mut zeroed_data_size: usize = 0;
+    // Labels in zero-initialized sections and their offset from the start of the zero-initialized data
+    let mut zeroed_labels: Vec<(&str, usize)> = Vec::new();
+
+    let mut current_section: Option<SectionAttributes> = None;
 
     macro_rules! push_op {
         ($op:ident) => {
-            bytecode.push(ByteCodes::$op as u8)
+            bytecode.extend_from_slice(&ByteCodes::$op.to_le_bytes())
+        }
+    }
+
+    macro_rules! define_label {
+        ($name:expr, $attributes:expr) => {
+            if $attributes.zeroed {
+                // The zero-initialized data is located after the initialized data, whose size is not known yet
+                zeroed_labels.push(($name, zeroed_data_size));
+            } else if $attributes.writable {
+                label_map.insert($name, VirtualAddress(data.len()));
+            } else {
+                label_map.insert($name, VirtualAddress(bytecode.len()));
+                symbols.push(Symbol { name: $name.to_string(), address: VirtualAddress(bytecode.len()) });
+            }
         }
     }
 
     push_op!(JumpConst);
-    bytecode.extend_from_slice([0u8; 8].as_ref()); // A placeholder for the entry point address, will be filled later
+    bytecode.extend_from_slice([0u8; ADDRESS_SIZE].as_ref()); // A placeholder for the entry point address, will be filled later
 
     for node in asm {
 
@@ -51,30 +74,53 @@ pub fn generate<'a>(asm: &[AsmNode], symbol_table: &'a SymbolTable<'a>, module_m
 
             AsmNodeValue::Label(name) => {
                 
-                if current_section.is_none() {
-                    errors::outside_section(&node.source, module_manager, "Labels must be located inside an assembly section");
-                }
+                let attributes = current_section.unwrap_or_else(
+                    || errors::outside_section(&node.source, module_manager, "Labels must be located inside an assembly section")
+                );
                 
-                label_map.insert(name, VirtualAddress(bytecode.len()));
+                define_label!(name, attributes);
             },
             
-            AsmNodeValue::Section(section) => {
+            AsmNodeValue::Section(section, attributes) => {
                 // Sections are secretly labels
-                label_map.insert(section, VirtualAddress(bytecode.len()));
-                current_section = Some(section);
+                define_label!(section, attributes);
+                current_section = Some(*attributes);
             },
 
             AsmNodeValue::Instruction(instruction) => {
 
-                if current_section.is_none() {
-                    errors::outside_section(&node.source, module_manager, "Instructions must be located inside an assembly section");
+                let attributes = current_section.unwrap_or_else(
+                    || errors::outside_section(&node.source, module_manager, "Instructions must be located inside an assembly section")
+                );
+
+                if attributes.writable && !matches!(instruction, AsmInstruction::DefineNumber { .. } | AsmInstruction::DefineBytes { .. } | AsmInstruction::DefineString { .. }) {
+                    errors::parsing_error(&node.source, module_manager, "Writable sections can only contain data, not instructions.");
+                }
+
+                // Position of the next byte in the current segment
+                let current_position = if attributes.writable { data.len() } else { bytecode.len() };
+
+                macro_rules! emit_data {
+                    ($bytes:expr) => {{
+                        let bytes = $bytes;
+                        if attributes.zeroed {
+                            if bytes.iter().any(|&byte| byte != 0) {
+                                errors::invalid_argument(&node.source, module_manager, "Zero-initialized sections can only contain zeros.");
+                            }
+                            zeroed_data_size += bytes.len();
+                        } else if attributes.writable {
+                            data.extend(bytes);
+                        } else {
+                            bytecode.extend(bytes);
+                        }
+                    }}
                 }
 
 
                 macro_rules! one_arg_address_instruction {
                     ($name:ident, $addr:ident) => {{
 
-                        bytecode.push(ByteCodes::$name as u8);
+                        bytecode.extend_from_slice(&ByteCodes::$name.to_le_bytes());
 
                         let operand: VirtualAddress = match &$addr.0 {
 
@@ -104,11 +150,11 @@ pub fn generate<'a>(asm: &[AsmNode], symbol_table: &'a SymbolTable<'a>, module_m
                 macro_rules! one_arg_number_instruction {
                     ($name:ident, $value:ident, $size:expr) => {{
 
-                        bytecode.push(ByteCodes::$name as u8);
+                        bytecode.extend_from_slice(&ByteCodes::$name.to_le_bytes());
 
                         let (bytes, minimum_size) = match &$value.0 {
 
-                            NumberLike::Number(n, size) => (n.as_le_bytes(), *size as usize),
+                            NumberLike::Number(n, size) => (n.as_sized_le_bytes($size), *size as usize),
 
                             NumberLike::Symbol(id) => (
                                 get_symbol_or_placeholder!(*id, &$value.1, |value, symbol| 
@@ -174,6 +220,62 @@ pub fn generate<'a>(asm: &[AsmNode], symbol_table: &'a SymbolTable<'a>, module_m
                     AsmInstruction::ModInt2 => push_op!(ModInt2),
                     AsmInstruction::ModInt4 => push_op!(ModInt4),
                     AsmInstruction::ModInt8 => push_op!(ModInt8),
+                    AsmInstruction::DivUint1 => push_op!(DivUint1),
+                    AsmInstruction::DivUint2 => push_op!(DivUint2),
+                    AsmInstruction::DivUint4 => push_op!(DivUint4),
+                    AsmInstruction::DivUint8 => push_op!(DivUint8),
+                    AsmInstruction::ModUint1 => push_op!(ModUint1),
+                    AsmInstruction::ModUint2 => push_op!(ModUint2),
+                    AsmInstruction::ModUint4 => push_op!(ModUint4),
+                    AsmInstruction::ModUint8 => push_op!(ModUint8),
+                    AsmInstruction::CheckedAddInt1 => push_op!(CheckedAddInt1),
+                    AsmInstruction::CheckedAddInt2 => push_op!(CheckedAddInt2),
+                    AsmInstruction::CheckedAddInt4 => push_op!(CheckedAddInt4),
+                    AsmInstruction::CheckedAddInt8 => push_op!(CheckedAddInt8),
+                    AsmInstruction::CheckedAddUint1 => push_op!(CheckedAddUint1),
+                    AsmInstruction::CheckedAddUint2 => push_op!(CheckedAddUint2),
+                    AsmInstruction::CheckedAddUint4 => push_op!(CheckedAddUint4),
+                    AsmInstruction::CheckedAddUint8 => push_op!(CheckedAddUint8),
+                    AsmInstruction::CheckedSubInt1 => push_op!(CheckedSubInt1),
+                    AsmInstruction::CheckedSubInt2 => push_op!(CheckedSubInt2),
+                    AsmInstruction::CheckedSubInt4 => push_op!(CheckedSubInt4),
+                    AsmInstruction::CheckedSubInt8 => push_op!(CheckedSubInt8),
+                    AsmInstruction::CheckedSubUint1 => push_op!(CheckedSubUint1),
+                    AsmInstruction::CheckedSubUint2 => push_op!(CheckedSubUint2),
+                    AsmInstruction::CheckedSubUint4 => push_op!(CheckedSubUint4),
+                    AsmInstruction::CheckedSubUint8 => push_op!(CheckedSubUint8),
+                    AsmInstruction::CheckedMulInt1 => push_op!(CheckedMulInt1),
+                    AsmInstruction::CheckedMulInt2 => push_op!(CheckedMulInt2),
+                    AsmInstruction::CheckedMulInt4 => push_op!(CheckedMulInt4),
+                    AsmInstruction::CheckedMulInt8 => push_op!(CheckedMulInt8),
+                    AsmInstruction::CheckedMulUint1 => push_op!(CheckedMulUint1),
+                    AsmInstruction::CheckedMulUint2 => push_op!(CheckedMulUint2),
+                    AsmInstruction::CheckedMulUint4 => push_op!(CheckedMulUint4),
+                    AsmInstruction::CheckedMulUint8 => push_op!(CheckedMulUint8),
+                    AsmInstruction::SaturatingAddInt1 => push_op!(SaturatingAddInt1),
+                    AsmInstruction::SaturatingAddInt2 => push_op!(SaturatingAddInt2),
+                    AsmInstruction::SaturatingAddInt4 => push_op!(SaturatingAddInt4),
+                    AsmInstruction::SaturatingAddInt8 => push_op!(SaturatingAddInt8),
+                    AsmInstruction::SaturatingAddUint1 => push_op!(SaturatingAddUint1),
+                    AsmInstruction::SaturatingAddUint2 => push_op!(SaturatingAddUint2),
+                    AsmInstruction::SaturatingAddUint4 => push_op!(SaturatingAddUint4),
+                    AsmInstruction::SaturatingAddUint8 => push_op!(SaturatingAddUint8),
+                    AsmInstruction::SaturatingSubInt1 => push_op!(SaturatingSubInt1),
+                    AsmInstruction::SaturatingSubInt2 => push_op!(SaturatingSubInt2),
+                    AsmInstruction::SaturatingSubInt4 => push_op!(SaturatingSubInt4),
+                    AsmInstruction::SaturatingSubInt8 => push_op!(SaturatingSubInt8),
+                    AsmInstruction::SaturatingSubUint1 => push_op!(SaturatingSubUint1),
+                    AsmInstruction::SaturatingSubUint2 => push_op!(SaturatingSubUint2),
+                    AsmInstruction::SaturatingSubUint4 => push_op!(SaturatingSubUint4),
+                    AsmInstruction::SaturatingSubUint8 => push_op!(SaturatingSubUint8),
+                    AsmInstruction::SaturatingMulInt1 => push_op!(SaturatingMulInt1),
+                    AsmInstruction::SaturatingMulInt2 => push_op!(SaturatingMulInt2),
+                    AsmInstruction::SaturatingMulInt4 => push_op!(SaturatingMulInt4),
+                    AsmInstruction::SaturatingMulInt8 => push_op!(SaturatingMulInt8),
+                    AsmInstruction::SaturatingMulUint1 => push_op!(SaturatingMulUint1),
+                    AsmInstruction::SaturatingMulUint2 => push_op!(SaturatingMulUint2),
+                    AsmInstruction::SaturatingMulUint4 => push_op!(SaturatingMulUint4),
+                    AsmInstruction::SaturatingMulUint8 => push_op!(SaturatingMulUint8),
                     AsmInstruction::AddFloat4 => push_op!(AddFloat4),
                     AsmInstruction::AddFloat8 => push_op!(AddFloat8),
                     AsmInstruction::SubFloat4 => push_op!(SubFloat4),
@@ -184,6 +286,177 @@ pub fn generate<'a>(asm: &[AsmNode], symbol_table: &'a SymbolTable<'a>, module_m
                     AsmInstruction::DivFloat8 => push_op!(DivFloat8),
                     AsmInstruction::ModFloat4 => push_op!(ModFloat4),
                     AsmInstruction::ModFloat8 => push_op!(ModFloat8),
+                    AsmInstruction::NegFloat4 => push_op!(NegFloat4),
+                    AsmInstruction::NegFloat8 => push_op!(NegFloat8),
+                    AsmInstruction::AbsFloat4 => push_op!(AbsFloat4),
+                    AsmInstruction::AbsFloat8 => push_op!(AbsFloat8),
+                    AsmInstruction::SqrtFloat4 => push_op!(SqrtFloat4),
+                    AsmInstruction::SqrtFloat8 => push_op!(SqrtFloat8),
+                    AsmInstruction::FloorFloat4 => push_op!(FloorFloat4),
+                    AsmInstruction::FloorFloat8 => push_op!(FloorFloat8),
+                    AsmInstruction::CeilFloat4 => push_op!(CeilFloat4),
+                    AsmInstruction::CeilFloat8 => push_op!(CeilFloat8),
+                    AsmInstruction::MinFloat4 => push_op!(MinFloat4),
+                    AsmInstruction::MinFloat8 => push_op!(MinFloat8),
+                    AsmInstruction::MaxFloat4 => push_op!(MaxFloat4),
+                    AsmInstruction::MaxFloat8 => push_op!(MaxFloat8),
+                    AsmInstruction::SinFloat4 => push_op!(SinFloat4),
+                    AsmInstruction::SinFloat8 => push_op!(SinFloat8),
+                    AsmInstruction::CosFloat4 => push_op!(CosFloat4),
+                    AsmInstruction::CosFloat8 => push_op!(CosFloat8),
+                    AsmInstruction::ExpFloat4 => push_op!(ExpFloat4),
+                    AsmInstruction::ExpFloat8 => push_op!(ExpFloat8),
+                    AsmInstruction::LogFloat4 => push_op!(LogFloat4),
+                    AsmInstruction::LogFloat8 => push_op!(LogFloat8),
+                    AsmInstruction::PowFloat4 => push_op!(PowFloat4),
+                    AsmInstruction::PowFloat8 => push_op!(PowFloat8),
+                    AsmInstruction::BitAnd1 => push_op!(BitAnd1),
+                    AsmInstruction::BitAnd2 => push_op!(BitAnd2),
+                    AsmInstruction::BitAnd4 => push_op!(BitAnd4),
+                    AsmInstruction::BitAnd8 => push_op!(BitAnd8),
+                    AsmInstruction::BitOr1 => push_op!(BitOr1),
+                    AsmInstruction::BitOr2 => push_op!(BitOr2),
+                    AsmInstruction::BitOr4 => push_op!(BitOr4),
+                    AsmInstruction::BitOr8 => push_op!(BitOr8),
+                    AsmInstruction::BitXor1 => push_op!(BitXor1),
+                    AsmInstruction::BitXor2 => push_op!(BitXor2),
+                    AsmInstruction::BitXor4 => push_op!(BitXor4),
+                    AsmInstruction::BitXor8 => push_op!(BitXor8),
+                    AsmInstruction::BitNot1 => push_op!(BitNot1),
+                    AsmInstruction::BitNot2 => push_op!(BitNot2),
+                    AsmInstruction::BitNot4 => push_op!(BitNot4),
+                    AsmInstruction::BitNot8 => push_op!(BitNot8),
+                    AsmInstruction::ShiftLeft1 => push_op!(ShiftLeft1),
+                    AsmInstruction::ShiftLeft2 => push_op!(ShiftLeft2),
+                    AsmInstruction::ShiftLeft4 => push_op!(ShiftLeft4),
+                    AsmInstruction::ShiftLeft8 => push_op!(ShiftLeft8),
+                    AsmInstruction::ShiftRight1 => push_op!(ShiftRight1),
+                    AsmInstruction::ShiftRight2 => push_op!(ShiftRight2),
+                    AsmInstruction::ShiftRight4 => push_op!(ShiftRight4),
+                    AsmInstruction::ShiftRight8 => push_op!(ShiftRight8),
+                    AsmInstruction::ShiftRightArith1 => push_op!(ShiftRightArith1),
+                    AsmInstruction::ShiftRightArith2 => push_op!(ShiftRightArith2),
+                    AsmInstruction::ShiftRightArith4 => push_op!(ShiftRightArith4),
+                    AsmInstruction::ShiftRightArith8 => push_op!(ShiftRightArith8),
+                    AsmInstruction::RotateLeft1 => push_op!(RotateLeft1),
+                    AsmInstruction::RotateLeft2 => push_op!(RotateLeft2),
+                    AsmInstruction::RotateLeft4 => push_op!(RotateLeft4),
+                    AsmInstruction::RotateLeft8 => push_op!(RotateLeft8),
+                    AsmInstruction::RotateRight1 => push_op!(RotateRight1),
+                    AsmInstruction::RotateRight2 => push_op!(RotateRight2),
+                    AsmInstruction::RotateRight4 => push_op!(RotateRight4),
+                    AsmInstruction::RotateRight8 => push_op!(RotateRight8),
+                    AsmInstruction::ByteSwap2 => push_op!(ByteSwap2),
+                    AsmInstruction::ByteSwap4 => push_op!(ByteSwap4),
+                    AsmInstruction::ByteSwap8 => push_op!(ByteSwap8),
+                    AsmInstruction::EqualInt1 => push_op!(EqualInt1),
+                    AsmInstruction::EqualInt2 => push_op!(EqualInt2),
+                    AsmInstruction::EqualInt4 => push_op!(EqualInt4),
+                    AsmInstruction::EqualInt8 => push_op!(EqualInt8),
+                    AsmInstruction::NotEqualInt1 => push_op!(NotEqualInt1),
+                    AsmInstruction::NotEqualInt2 => push_op!(NotEqualInt2),
+                    AsmInstruction::NotEqualInt4 => push_op!(NotEqualInt4),
+                    AsmInstruction::NotEqualInt8 => push_op!(NotEqualInt8),
+                    AsmInstruction::LessInt1 => push_op!(LessInt1),
+                    AsmInstruction::LessInt2 => push_op!(LessInt2),
+                    AsmInstruction::LessInt4 => push_op!(LessInt4),
+                    AsmInstruction::LessInt8 => push_op!(LessInt8),
+                    AsmInstruction::LessEqualInt1 => push_op!(LessEqualInt1),
+                    AsmInstruction::LessEqualInt2 => push_op!(LessEqualInt2),
+                    AsmInstruction::LessEqualInt4 => push_op!(LessEqualInt4),
+                    AsmInstruction::LessEqualInt8 => push_op!(LessEqualInt8),
+                    AsmInstruction::GreaterInt1 => push_op!(GreaterInt1),
+                    AsmInstruction::GreaterInt2 => push_op!(GreaterInt2),
+                    AsmInstruction::GreaterInt4 => push_op!(GreaterInt4),
+                    AsmInstruction::GreaterInt8 => push_op!(GreaterInt8),
+                    AsmInstruction::GreaterEqualInt1 => push_op!(GreaterEqualInt1),
+                    AsmInstruction::GreaterEqualInt2 => push_op!(GreaterEqualInt2),
+                    AsmInstruction::GreaterEqualInt4 => push_op!(GreaterEqualInt4),
+                    AsmInstruction::GreaterEqualInt8 => push_op!(GreaterEqualInt8),
+                    AsmInstruction::LessUint1 => push_op!(LessUint1),
+                    AsmInstruction::LessUint2 => push_op!(LessUint2),
+                    AsmInstruction::LessUint4 => push_op!(LessUint4),
+                    AsmInstruction::LessUint8 => push_op!(LessUint8),
+                    AsmInstruction::LessEqualUint1 => push_op!(LessEqualUint1),
+                    AsmInstruction::LessEqualUint2 => push_op!(LessEqualUint2),
+                    AsmInstruction::LessEqualUint4 => push_op!(LessEqualUint4),
+                    AsmInstruction::LessEqualUint8 => push_op!(LessEqualUint8),
+                    AsmInstruction::GreaterUint1 => push_op!(GreaterUint1),
+                    AsmInstruction::GreaterUint2 => push_op!(GreaterUint2),
+                    AsmInstruction::GreaterUint4 => push_op!(GreaterUint4),
+                    AsmInstruction::GreaterUint8 => push_op!(GreaterUint8),
+                    AsmInstruction::GreaterEqualUint1 => push_op!(GreaterEqualUint1),
+                    AsmInstruction::GreaterEqualUint2 => push_op!(GreaterEqualUint2),
+                    AsmInstruction::GreaterEqualUint4 => push_op!(GreaterEqualUint4),
+                    AsmInstruction::GreaterEqualUint8 => push_op!(GreaterEqualUint8),
+                    AsmInstruction::EqualFloat4 => push_op!(EqualFloat4),
+                    AsmInstruction::EqualFloat8 => push_op!(EqualFloat8),
+                    AsmInstruction::NotEqualFloat4 => push_op!(NotEqualFloat4),
+                    AsmInstruction::NotEqualFloat8 => push_op!(NotEqualFloat8),
+                    AsmInstruction::LessFloat4 => push_op!(LessFloat4),
+                    AsmInstruction::LessFloat8 => push_op!(LessFloat8),
+                    AsmInstruction::LessEqualFloat4 => push_op!(LessEqualFloat4),
+                    AsmInstruction::LessEqualFloat8 => push_op!(LessEqualFloat8),
+                    AsmInstruction::GreaterFloat4 => push_op!(GreaterFloat4),
+                    AsmInstruction::GreaterFloat8 => push_op!(GreaterFloat8),
+                    AsmInstruction::GreaterEqualFloat4 => push_op!(GreaterEqualFloat4),
+                    AsmInstruction::GreaterEqualFloat8 => push_op!(GreaterEqualFloat8),
+                    AsmInstruction::Select1 => push_op!(Select1),
+                    AsmInstruction::Select2 => push_op!(Select2),
+                    AsmInstruction::Select4 => push_op!(Select4),
+                    AsmInstruction::Select8 => push_op!(Select8),
+                    AsmInstruction::SignExtend1To2 => push_op!(SignExtend1To2),
+                    AsmInstruction::SignExtend1To4 => push_op!(SignExtend1To4),
+                    AsmInstruction::SignExtend1To8 => push_op!(SignExtend1To8),
+                    AsmInstruction::SignExtend2To4 => push_op!(SignExtend2To4),
+                    AsmInstruction::SignExtend2To8 => push_op!(SignExtend2To8),
+                    AsmInstruction::SignExtend4To8 => push_op!(SignExtend4To8),
+                    AsmInstruction::ZeroExtend1To2 => push_op!(ZeroExtend1To2),
+                    AsmInstruction::ZeroExtend1To4 => push_op!(ZeroExtend1To4),
+                    AsmInstruction::ZeroExtend1To8 => push_op!(ZeroExtend1To8),
+                    AsmInstruction::ZeroExtend2To4 => push_op!(ZeroExtend2To4),
+                    AsmInstruction::ZeroExtend2To8 => push_op!(ZeroExtend2To8),
+                    AsmInstruction::ZeroExtend4To8 => push_op!(ZeroExtend4To8),
+                    AsmInstruction::Truncate2To1 => push_op!(Truncate2To1),
+                    AsmInstruction::Truncate4To1 => push_op!(Truncate4To1),
+                    AsmInstruction::Truncate8To1 => push_op!(Truncate8To1),
+                    AsmInstruction::Truncate4To2 => push_op!(Truncate4To2),
+                    AsmInstruction::Truncate8To2 => push_op!(Truncate8To2),
+                    AsmInstruction::Truncate8To4 => push_op!(Truncate8To4),
+                    AsmInstruction::Int1ToFloat4 => push_op!(Int1ToFloat4),
+                    AsmInstruction::Int1ToFloat8 => push_op!(Int1ToFloat8),
+                    AsmInstruction::Int2ToFloat4 => push_op!(Int2ToFloat4),
+                    AsmInstruction::Int2ToFloat8 => push_op!(Int2ToFloat8),
+                    AsmInstruction::Int4ToFloat4 => push_op!(Int4ToFloat4),
+                    AsmInstruction::Int4ToFloat8 => push_op!(Int4ToFloat8),
+                    AsmInstruction::Int8ToFloat4 => push_op!(Int8ToFloat4),
+                    AsmInstruction::Int8ToFloat8 => push_op!(Int8ToFloat8),
+                    AsmInstruction::Uint1ToFloat4 => push_op!(Uint1ToFloat4),
+                    AsmInstruction::Uint1ToFloat8 => push_op!(Uint1ToFloat8),
+                    AsmInstruction::Uint2ToFloat4 => push_op!(Uint2ToFloat4),
+                    AsmInstruction::Uint2ToFloat8 => push_op!(Uint2ToFloat8),
+                    AsmInstruction::Uint4ToFloat4 => push_op!(Uint4ToFloat4),
+                    AsmInstruction::Uint4ToFloat8 => push_op!(Uint4ToFloat8),
+                    AsmInstruction::Uint8ToFloat4 => push_op!(Uint8ToFloat4),
+                    AsmInstruction::Uint8ToFloat8 => push_op!(Uint8ToFloat8),
+                    AsmInstruction::Float4ToInt1 => push_op!(Float4ToInt1),
+                    AsmInstruction::Float8ToInt1 => push_op!(Float8ToInt1),
+                    AsmInstruction::Float4ToInt2 => push_op!(Float4ToInt2),
+                    AsmInstruction::Float8ToInt2 => push_op!(Float8ToInt2),
+                    AsmInstruction::Float4ToInt4 => push_op!(Float4ToInt4),
+                    AsmInstruction::Float8ToInt4 => push_op!(Float8ToInt4),
+                    AsmInstruction::Float4ToInt8 => push_op!(Float4ToInt8),
+                    AsmInstruction::Float8ToInt8 => push_op!(Float8ToInt8),
+                    AsmInstruction::Float4ToUint1 => push_op!(Float4ToUint1),
+                    AsmInstruction::Float8ToUint1 => push_op!(Float8ToUint1),
+                    AsmInstruction::Float4ToUint2 => push_op!(Float4ToUint2),
+                    AsmInstruction::Float8ToUint2 => push_op!(Float8ToUint2),
+                    AsmInstruction::Float4ToUint4 => push_op!(Float4ToUint4),
+                    AsmInstruction::Float8ToUint4 => push_op!(Float8ToUint4),
+                    AsmInstruction::Float4ToUint8 => push_op!(Float4ToUint8),
+                    AsmInstruction::Float8ToUint8 => push_op!(Float8ToUint8),
+                    AsmInstruction::Float4ToFloat8 => push_op!(Float4ToFloat8),
+                    AsmInstruction::Float8ToFloat4 => push_op!(Float8ToFloat4),
                     AsmInstruction::LoadStatic1 { addr } => one_arg_address_instruction!(LoadStatic1, addr),
                     AsmInstruction::LoadStatic2 { addr } => one_arg_address_instruction!(LoadStatic2, addr),
                     AsmInstruction::LoadStatic4 { addr } => one_arg_address_instruction!(LoadStatic4, addr),
@@ -276,6 +549,16 @@ pub fn generate<'a>(asm: &[AsmNode], symbol_table: &'a SymbolTable<'a>, module_m
                     AsmInstruction::PopConst { value } => one_arg_number_instruction!(PopConst, value, ADDRESS_SIZE),
                     AsmInstruction::VirtualConstToReal { addr } => one_arg_address_instruction!(VirtualConstToReal, addr),
                     AsmInstruction::VirtualToReal => push_op!(VirtualToReal),
+                    AsmInstruction::LoadData1 { addr } => one_arg_address_instruction!(LoadData1, addr),
+                    AsmInstruction::LoadData2 { addr } => one_arg_address_instruction!(LoadData2, addr),
+                    AsmInstruction::LoadData4 { addr } => one_arg_address_instruction!(LoadData4, addr),
+                    AsmInstruction::LoadData8 { addr } => one_arg_address_instruction!(LoadData8, addr),
+                    AsmInstruction::StoreData1 { addr } => one_arg_address_instruction!(StoreData1, addr),
+                    AsmInstruction::StoreData2 { addr } => one_arg_address_instruction!(StoreData2, addr),
+                    AsmInstruction::StoreData4 { addr } => one_arg_address_instruction!(StoreData4, addr),
+                    AsmInstruction::StoreData8 { addr } => one_arg_address_instruction!(StoreData8, addr),
+                    AsmInstruction::VirtualDataConstToReal { addr } => one_arg_address_instruction!(VirtualDataConstToReal, addr),
+                    AsmInstruction::VirtualDataToReal => push_op!(VirtualDataToReal),
                     AsmInstruction::Store1 => push_op!(Store1),
                     AsmInstruction::Store2 => push_op!(Store2),
                     AsmInstruction::Store4 => push_op!(Store4),
@@ -291,11 +574,34 @@ pub fn generate<'a>(asm: &[AsmNode], symbol_table: &'a SymbolTable<'a>, module_m
                     AsmInstruction::Duplicate4 => push_op!(Duplicate4),
                     AsmInstruction::Duplicate8 => push_op!(Duplicate8),
                     AsmInstruction::DuplicateBytes => push_op!(DuplicateBytes),
+                    AsmInstruction::Swap1 => push_op!(Swap1),
+                    AsmInstruction::Swap2 => push_op!(Swap2),
+                    AsmInstruction::Swap4 => push_op!(Swap4),
+                    AsmInstruction::Swap8 => push_op!(Swap8),
+                    AsmInstruction::Over1 => push_op!(Over1),
+                    AsmInstruction::Over2 => push_op!(Over2),
+                    AsmInstruction::Over4 => push_op!(Over4),
+                    AsmInstruction::Over8 => push_op!(Over8),
+                    AsmInstruction::Rotate1 => push_op!(Rotate1),
+                    AsmInstruction::Rotate2 => push_op!(Rotate2),
+                    AsmInstruction::Rotate4 => push_op!(Rotate4),
+                    AsmInstruction::Rotate8 => push_op!(Rotate8),
+                    AsmInstruction::Pick1 { value: index } => one_arg_number_instruction!(Pick1, index, STACK_INDEX_SIZE),
+                    AsmInstruction::Pick2 { value: index } => one_arg_number_instruction!(Pick2, index, STACK_INDEX_SIZE),
+                    AsmInstruction::Pick4 { value: index } => one_arg_number_instruction!(Pick4, index, STACK_INDEX_SIZE),
+                    AsmInstruction::Pick8 { value: index } => one_arg_number_instruction!(Pick8, index, STACK_INDEX_SIZE),
+                    AsmInstruction::Roll1 { value: index } => one_arg_number_instruction!(Roll1, index, STACK_INDEX_SIZE),
+                    AsmInstruction::Roll2 { value: index } => one_arg_number_instruction!(Roll2, index, STACK_INDEX_SIZE),
+                    AsmInstruction::Roll4 { value: index } => one_arg_number_instruction!(Roll4, index, STACK_INDEX_SIZE),
+                    AsmInstruction::Roll8 { value: index } => one_arg_number_instruction!(Roll8, index, STACK_INDEX_SIZE),
                     AsmInstruction::Malloc => push_op!(Malloc),
                     AsmInstruction::Realloc => push_op!(Realloc),
                     AsmInstruction::Free => push_op!(Free),
+                    AsmInstruction::Calloc => push_op!(Calloc),
+                    AsmInstruction::MallocAligned => push_op!(MallocAligned),
                     AsmInstruction::Intr => push_op!(Intr),
                     AsmInstruction::IntrConst { value: code } => one_arg_number_instruction!(IntrConst, code, INTERRUPT_SIZE),
+                    AsmInstruction::HostCall { value: id } => one_arg_number_instruction!(HostCall, id, HOST_FUNCTION_ID_SIZE),
                     AsmInstruction::ReadError => push_op!(ReadError),
                     AsmInstruction::SetError => push_op!(SetError),
                     AsmInstruction::SetErrorConst { value } => one_arg_number_instruction!(SetErrorConst, value, ERROR_CODE_SIZE),
@@ -332,14 +638,15 @@ pub fn generate<'a>(asm: &[AsmNode], symbol_table: &'a SymbolTable<'a>, module_m
                             } else {
                                 errors::invalid_argument(&size.1, module_manager, "Expected an unsigned integer as number size.");
                             },
-                            NumberLike::CurrentPosition => bytecode.len(),
+                            NumberLike::CurrentPosition => current_position,
                             NumberLike::Symbol(_)
                                 => errors::invalid_argument(&size.1, module_manager, "Cannot use a symbol as number size in this context. Only literals are allowed."),
                         };
 
                         let mut number_value = match &value.0 {
-                            NumberLike::Number(n, s) => n.as_le_bytes()[..*s as usize].to_vec(),
-                            NumberLike::CurrentPosition => bytecode.len().to_le_bytes().to_vec(),
+                            // Encode at the requested size when possible to preserve the sign of integers and the precision of floats
+                            NumberLike::Number(n, s) => n.as_sized_le_bytes((*s as usize).max(number_size.min(8))),
+                            NumberLike::CurrentPosition => current_position.to_le_bytes().to_vec(),
                             NumberLike::Symbol(_)
                                 => errors::invalid_argument(&value.1, module_manager, "Expected a numeric literal.")
                         };
@@ -353,7 +660,7 @@ pub fn generate<'a>(asm: &[AsmNode], symbol_table: &'a SymbolTable<'a>, module_m
                             number_value.extend(vec![0; number_size - number_value.len()]);
                         }
                     
-                        bytecode.extend(number_value);
+                        emit_data!(number_value);
                     },
 
                     AsmInstruction::DefineBytes { bytes } => {
@@ -372,7 +679,7 @@ pub fn generate<'a>(asm: &[AsmNode], symbol_table: &'a SymbolTable<'a>, module_m
                                     (*size, num as u8)
                                 },
                                 
-                                NumberLike::CurrentPosition => (ADDRESS_SIZE as u8, bytecode.len() as u8),
+                                NumberLike::CurrentPosition => (ADDRESS_SIZE as u8, current_position as u8),
 
                                 NumberLike::Symbol(_) 
                                     => errors::invalid_argument(&byte.1, module_manager, "Expected a byte literal")
@@ -385,22 +692,39 @@ pub fn generate<'a>(asm: &[AsmNode], symbol_table: &'a SymbolTable<'a>, module_m
                             value_bytes.push(num);
                         }
 
-                        bytecode.extend(value_bytes);
+                        emit_data!(value_bytes);
                     },
 
                     AsmInstruction::DefineString { static_id } => {
 
                         let static_data = symbol_table.get_static(*static_id);
-                        let string = match static_data {
-                            StaticValue::StringLiteral(string) => string,
-                            
-                        };
+                        let StaticValue::StringLiteral(string) = static_data;
 
-                        bytecode.extend(string.as_bytes());
+                        emit_data!(string.as_bytes());
                     },
 
                     AsmInstruction::Call { addr } => one_arg_address_instruction!(Call, addr),
-                    AsmInstruction::Return => push_op!(Jump), // Return is an alias for a jump with the argument being the return address
+                    AsmInstruction::CallIndirect => push_op!(CallIndirect),
+                    AsmInstruction::TailCall { addr } => one_arg_address_instruction!(TailCall, addr),
+                    AsmInstruction::Return => push_op!(Return),
+                    AsmInstruction::Enter { value: size } => one_arg_number_instruction!(Enter, size, ADDRESS_SIZE),
+                    AsmInstruction::Leave => push_op!(Leave),
+                    AsmInstruction::LoadLocal1 { value: offset } => one_arg_number_instruction!(LoadLocal1, offset, ADDRESS_SIZE),
+                    AsmInstruction::LoadLocal2 { value: offset } => one_arg_number_instruction!(LoadLocal2, offset, ADDRESS_SIZE),
+                    AsmInstruction::LoadLocal4 { value: offset } => one_arg_number_instruction!(LoadLocal4, offset, ADDRESS_SIZE),
+                    AsmInstruction::LoadLocal8 { value: offset } => one_arg_number_instruction!(LoadLocal8, offset, ADDRESS_SIZE),
+                    AsmInstruction::StoreLocal1 { value: offset } => one_arg_number_instruction!(StoreLocal1, offset, ADDRESS_SIZE),
+                    AsmInstruction::StoreLocal2 { value: offset } => one_arg_number_instruction!(StoreLocal2, offset, ADDRESS_SIZE),
+                    AsmInstruction::StoreLocal4 { value: offset } => one_arg_number_instruction!(StoreLocal4, offset, ADDRESS_SIZE),
+                    AsmInstruction::StoreLocal8 { value: offset } => one_arg_number_instruction!(StoreLocal8, offset, ADDRESS_SIZE),
+                    AsmInstruction::LoadArg1 { value: offset } => one_arg_number_instruction!(LoadArg1, offset, ADDRESS_SIZE),
+                    AsmInstruction::LoadArg2 { value: offset } => one_arg_number_instruction!(LoadArg2, offset, ADDRESS_SIZE),
+                    AsmInstruction::LoadArg4 { value: offset } => one_arg_number_instruction!(LoadArg4, offset, ADDRESS_SIZE),
+                    AsmInstruction::LoadArg8 { value: offset } => one_arg_number_instruction!(LoadArg8, offset, ADDRESS_SIZE),
+                    AsmInstruction::StoreArg1 { value: offset } => one_arg_number_instruction!(StoreArg1, offset, ADDRESS_SIZE),
+                    AsmInstruction::StoreArg2 { value: offset } => one_arg_number_instruction!(StoreArg2, offset, ADDRESS_SIZE),
+                    AsmInstruction::StoreArg4 { value: offset } => one_arg_number_instruction!(StoreArg4, offset, ADDRESS_SIZE),
+                    AsmInstruction::StoreArg8 { value: offset } => one_arg_number_instruction!(StoreArg8, offset, ADDRESS_SIZE),
                     AsmInstruction::Nop => push_op!(Nop),
                 }
             }
@@ -408,6 +732,11 @@ pub fn generate<'a>(asm: &[AsmNode], symbol_table: &'a SymbolTable<'a>, module_m
 
     }
 
+    // The zero-initialized data is located after the initialized data in the data segment
+    for (name, offset) in zeroed_labels {
+        label_map.insert(name, VirtualAddress(data.len() + offset));
+    }
+
     // Fill in the unresolved symbols
     for label in unresolved_labels {
         
@@ -421,9 +750,24 @@ pub fn generate<'a>(asm: &[AsmNode], symbol_table: &'a SymbolTable<'a>, module_m
 
     // Fill in the entry point address
     if let Some(entry) = label_map.get(ENTRY_SECTION_NAME) {
-        bytecode[1..(1 + ADDRESS_SIZE)].copy_from_slice(&entry.0.to_le_bytes());
+        bytecode[INSTRUCTION_SIZE..(INSTRUCTION_SIZE + ADDRESS_SIZE)].copy_from_slice(&entry.0.to_le_bytes());
     }
 
+    // Only labels in the program space can be called by the host
+    let exports = symbol_table.exports().iter().map(|(id, source)| {
+        let name = symbol_table.get_symbol(*id).name;
+        symbols.iter()
+            .find(|symbol| symbol.name == name)
+            .cloned()
+            .unwrap_or_else(|| if label_map.contains_key(name) {
+                errors::invalid_argument(source, module_manager, "Only labels in the program space can be exported.")
+            } else {
+                errors::undefined_symbol(source, module_manager)
+            })
+    }).collect();
+
+    ProgramMetadata { symbols, data, zeroed_data_size, host_functions: symbol_table.host_functions(), exports }.append_to(&mut bytecode);
+
     bytecode.shrink_to_fit();
     bytecode
 }
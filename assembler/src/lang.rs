@@ -60,6 +60,17 @@ impl Number {
     }
 
 
+    /// Encode the number as a value of the given size.
+    /// Floats are encoded as single-precision if the size is 4 bytes, and as double-precision otherwise.
+    pub fn as_sized_le_bytes(&self, size: usize) -> Vec<u8> {
+        match self {
+            Number::Float(value) if size == 4 => (*value as f32).to_le_bytes().to_vec(),
+            Number::Float(_) => self.as_le_bytes(),
+            _ => self.as_le_bytes()[..size].to_vec()
+        }
+    }
+
+
     pub fn as_uint(&self) -> Option<u64> {
         match self {
             Number::Uint(value) => Some(*value),
@@ -123,6 +134,65 @@ pub enum AsmInstruction<'a> {
     ModInt4,
     ModInt8,
 
+    DivUint1,
+    DivUint2,
+    DivUint4,
+    DivUint8,
+    ModUint1,
+    ModUint2,
+    ModUint4,
+    ModUint8,
+
+    CheckedAddInt1,
+    CheckedAddInt2,
+    CheckedAddInt4,
+    CheckedAddInt8,
+    CheckedAddUint1,
+    CheckedAddUint2,
+    CheckedAddUint4,
+    CheckedAddUint8,
+    CheckedSubInt1,
+    CheckedSubInt2,
+    CheckedSubInt4,
+    CheckedSubInt8,
+    CheckedSubUint1,
+    CheckedSubUint2,
+    CheckedSubUint4,
+    CheckedSubUint8,
+    CheckedMulInt1,
+    CheckedMulInt2,
+    CheckedMulInt4,
+    CheckedMulInt8,
+    CheckedMulUint1,
+    CheckedMulUint2,
+    CheckedMulUint4,
+    CheckedMulUint8,
+
+    SaturatingAddInt1,
+    SaturatingAddInt2,
+    SaturatingAddInt4,
+    SaturatingAddInt8,
+    SaturatingAddUint1,
+    SaturatingAddUint2,
+    SaturatingAddUint4,
+    SaturatingAddUint8,
+    SaturatingSubInt1,
+    SaturatingSubInt2,
+    SaturatingSubInt4,
+    SaturatingSubInt8,
+    SaturatingSubUint1,
+    SaturatingSubUint2,
+    SaturatingSubUint4,
+    SaturatingSubUint8,
+    SaturatingMulInt1,
+    SaturatingMulInt2,
+    SaturatingMulInt4,
+    SaturatingMulInt8,
+    SaturatingMulUint1,
+    SaturatingMulUint2,
+    SaturatingMulUint4,
+    SaturatingMulUint8,
+
     AddFloat4,
     AddFloat8,
     SubFloat4,
@@ -134,6 +204,192 @@ pub enum AsmInstruction<'a> {
     ModFloat4,
     ModFloat8,
 
+    NegFloat4,
+    NegFloat8,
+    AbsFloat4,
+    AbsFloat8,
+    SqrtFloat4,
+    SqrtFloat8,
+    FloorFloat4,
+    FloorFloat8,
+    CeilFloat4,
+    CeilFloat8,
+    MinFloat4,
+    MinFloat8,
+    MaxFloat4,
+    MaxFloat8,
+    SinFloat4,
+    SinFloat8,
+    CosFloat4,
+    CosFloat8,
+    ExpFloat4,
+    ExpFloat8,
+    LogFloat4,
+    LogFloat8,
+    PowFloat4,
+    PowFloat8,
+
+    BitAnd1,
+    BitAnd2,
+    BitAnd4,
+    BitAnd8,
+    BitOr1,
+    BitOr2,
+    BitOr4,
+    BitOr8,
+    BitXor1,
+    BitXor2,
+    BitXor4,
+    BitXor8,
+    BitNot1,
+    BitNot2,
+    BitNot4,
+    BitNot8,
+
+    ShiftLeft1,
+    ShiftLeft2,
+    ShiftLeft4,
+    ShiftLeft8,
+    ShiftRight1,
+    ShiftRight2,
+    ShiftRight4,
+    ShiftRight8,
+    ShiftRightArith1,
+    ShiftRightArith2,
+    ShiftRightArith4,
+    ShiftRightArith8,
+
+    RotateLeft1,
+    RotateLeft2,
+    RotateLeft4,
+    RotateLeft8,
+    RotateRight1,
+    RotateRight2,
+    RotateRight4,
+    RotateRight8,
+
+    ByteSwap2,
+    ByteSwap4,
+    ByteSwap8,
+
+    EqualInt1,
+    EqualInt2,
+    EqualInt4,
+    EqualInt8,
+    NotEqualInt1,
+    NotEqualInt2,
+    NotEqualInt4,
+    NotEqualInt8,
+    LessInt1,
+    LessInt2,
+    LessInt4,
+    LessInt8,
+    LessEqualInt1,
+    LessEqualInt2,
+    LessEqualInt4,
+    LessEqualInt8,
+    GreaterInt1,
+    GreaterInt2,
+    GreaterInt4,
+    GreaterInt8,
+    GreaterEqualInt1,
+    GreaterEqualInt2,
+    GreaterEqualInt4,
+    GreaterEqualInt8,
+
+    LessUint1,
+    LessUint2,
+    LessUint4,
+    LessUint8,
+    LessEqualUint1,
+    LessEqualUint2,
+    LessEqualUint4,
+    LessEqualUint8,
+    GreaterUint1,
+    GreaterUint2,
+    GreaterUint4,
+    GreaterUint8,
+    GreaterEqualUint1,
+    GreaterEqualUint2,
+    GreaterEqualUint4,
+    GreaterEqualUint8,
+
+    EqualFloat4,
+    EqualFloat8,
+    NotEqualFloat4,
+    NotEqualFloat8,
+    LessFloat4,
+    LessFloat8,
+    LessEqualFloat4,
+    LessEqualFloat8,
+    GreaterFloat4,
+    GreaterFloat8,
+    GreaterEqualFloat4,
+    GreaterEqualFloat8,
+
+    Select1,
+    Select2,
+    Select4,
+    Select8,
+
+    SignExtend1To2,
+    SignExtend1To4,
+    SignExtend1To8,
+    SignExtend2To4,
+    SignExtend2To8,
+    SignExtend4To8,
+
+    ZeroExtend1To2,
+    ZeroExtend1To4,
+    ZeroExtend1To8,
+    ZeroExtend2To4,
+    ZeroExtend2To8,
+    ZeroExtend4To8,
+
+    Truncate2To1,
+    Truncate4To1,
+    Truncate8To1,
+    Truncate4To2,
+    Truncate8To2,
+    Truncate8To4,
+
+    Int1ToFloat4,
+    Int1ToFloat8,
+    Int2ToFloat4,
+    Int2ToFloat8,
+    Int4ToFloat4,
+    Int4ToFloat8,
+    Int8ToFloat4,
+    Int8ToFloat8,
+    Uint1ToFloat4,
+    Uint1ToFloat8,
+    Uint2ToFloat4,
+    Uint2ToFloat8,
+    Uint4ToFloat4,
+    Uint4ToFloat8,
+    Uint8ToFloat4,
+    Uint8ToFloat8,
+
+    Float4ToInt1,
+    Float8ToInt1,
+    Float4ToInt2,
+    Float8ToInt2,
+    Float4ToInt4,
+    Float8ToInt4,
+    Float4ToInt8,
+    Float8ToInt8,
+    Float4ToUint1,
+    Float8ToUint1,
+    Float4ToUint2,
+    Float8ToUint2,
+    Float4ToUint4,
+    Float8ToUint4,
+    Float4ToUint8,
+    Float8ToUint8,
+
+    Float4ToFloat8,
+    Float8ToFloat4,
+
     LoadStatic1 { addr: AddressOperand<'a> },
     LoadStatic2 { addr: AddressOperand<'a> },
     LoadStatic4 { addr: AddressOperand<'a> },
@@ -163,6 +419,17 @@ pub enum AsmInstruction<'a> {
     VirtualConstToReal { addr: AddressOperand<'a> },
     VirtualToReal,
 
+    LoadData1 { addr: AddressOperand<'a> },
+    LoadData2 { addr: AddressOperand<'a> },
+    LoadData4 { addr: AddressOperand<'a> },
+    LoadData8 { addr: AddressOperand<'a> },
+    StoreData1 { addr: AddressOperand<'a> },
+    StoreData2 { addr: AddressOperand<'a> },
+    StoreData4 { addr: AddressOperand<'a> },
+    StoreData8 { addr: AddressOperand<'a> },
+    VirtualDataConstToReal { addr: AddressOperand<'a> },
+    VirtualDataToReal,
+
     Store1,
     Store2,
     Store4,
@@ -181,13 +448,38 @@ pub enum AsmInstruction<'a> {
     Duplicate8,
     DuplicateBytes,
 
+    Swap1,
+    Swap2,
+    Swap4,
+    Swap8,
+    Over1,
+    Over2,
+    Over4,
+    Over8,
+    Rotate1,
+    Rotate2,
+    Rotate4,
+    Rotate8,
+    Pick1 { value: NumberOperand<'a> },
+    Pick2 { value: NumberOperand<'a> },
+    Pick4 { value: NumberOperand<'a> },
+    Pick8 { value: NumberOperand<'a> },
+    Roll1 { value: NumberOperand<'a> },
+    Roll2 { value: NumberOperand<'a> },
+    Roll4 { value: NumberOperand<'a> },
+    Roll8 { value: NumberOperand<'a> },
+
     Malloc,
     Realloc,
     Free,
+    Calloc,
+    MallocAligned,
 
     Intr,
     IntrConst { value: NumberOperand<'a> },
 
+    HostCall { value: NumberOperand<'a> },
+
     ReadError,
     SetErrorConst { value: NumberOperand<'a> },
     SetError,
@@ -222,8 +514,29 @@ pub enum AsmInstruction<'a> {
     DefineString { static_id: StaticID },
 
     Call { addr: AddressOperand<'a> },
+    CallIndirect,
+    TailCall { addr: AddressOperand<'a> },
     Return,
 
+    Enter { value: NumberOperand<'a> },
+    Leave,
+    LoadLocal1 { value: NumberOperand<'a> },
+    LoadLocal2 { value: NumberOperand<'a> },
+    LoadLocal4 { value: NumberOperand<'a> },
+    LoadLocal8 { value: NumberOperand<'a> },
+    StoreLocal1 { value: NumberOperand<'a> },
+    StoreLocal2 { value: NumberOperand<'a> },
+    StoreLocal4 { value: NumberOperand<'a> },
+    StoreLocal8 { value: NumberOperand<'a> },
+    LoadArg1 { value: NumberOperand<'a> },
+    LoadArg2 { value: NumberOperand<'a> },
+    LoadArg4 { value: NumberOperand<'a> },
+    LoadArg8 { value: NumberOperand<'a> },
+    StoreArg1 { value: NumberOperand<'a> },
+    StoreArg2 { value: NumberOperand<'a> },
+    StoreArg4 { value: NumberOperand<'a> },
+    StoreArg8 { value: NumberOperand<'a> },
+
     Nop
 
 }
@@ -262,7 +575,8 @@ declare_pseudo_instructions! {
     DefineBytes db,
     DefineString ds,
     IncludeAsm include,
-    Return ret
+    DeclareHostFunction hostfn,
+    Export export
 
 }
 
@@ -285,11 +599,21 @@ pub struct AsmOperand<'a> {
 }
 
 
+/// Attributes that control where a section is placed at runtime.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SectionAttributes {
+    /// The section is placed in the writable data segment instead of the read-only program space.
+    pub writable: bool,
+    /// The section is writable and zero-initialized. Its content is not stored in the byte code.
+    pub zeroed: bool,
+}
+
+
 #[derive(Debug)]
 pub enum AsmNodeValue<'a> {
     Instruction(AsmInstruction<'a>),
     Label(&'a str),
-    Section(&'a str),
+    Section(&'a str, SectionAttributes),
 }
 
 
@@ -1,7 +1,4 @@
-#![feature(variant_count)]
 #![feature(cell_leak)]
-#![feature(io_error_more)]
-#![feature(os_str_display)]
 
 mod cli_parser;
 mod files;
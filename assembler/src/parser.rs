@@ -8,7 +8,7 @@ use crate::assembler;
 use crate::module_manager::ModuleManager;
 use crate::tokenizer::{Token, TokenLines, TokenList, TokenValue};
 use crate::symbol_table::{SymbolID, SymbolTable};
-use crate::lang::{AddressLike, AsmInstruction, AsmNode, AsmNodeValue, AsmOperand, AsmValue, Number, NumberLike, PseudoInstructions};
+use crate::lang::{AddressLike, AsmInstruction, AsmNode, AsmNodeValue, AsmOperand, AsmValue, Number, NumberLike, PseudoInstructions, SectionAttributes};
 use crate::errors;
 
 
@@ -119,7 +119,7 @@ pub struct MacroDef<'a> {
 pub type MacroMap<'a> = HashMap<SymbolID, MacroDef<'a>>;
 
 
-fn parse_line<'a>(main_operator: Token<'a>, operands: Box<[AsmOperand<'a>]>, nodes: &mut Vec<AsmNode<'a>>, macros: &mut MacroMap<'a>, token_lines: &mut TokenLines<'a>, module_manager: &'a ModuleManager<'a>, symbol_table: &'a SymbolTable<'a>) {
+fn parse_line<'a>(main_operator: Token<'a>, operands: &[AsmOperand<'a>], nodes: &mut Vec<AsmNode<'a>>, macros: &mut MacroMap<'a>, token_lines: &mut TokenLines<'a>, module_manager: &'a ModuleManager<'a>, symbol_table: &'a SymbolTable<'a>) {
 
     macro_rules! check_arg_count {
 
@@ -187,7 +187,7 @@ fn parse_line<'a>(main_operator: Token<'a>, operands: Box<[AsmOperand<'a>]>, nod
 
         // Once the macro is expanded, parse it normally
         for (main_operator, operands) in expanded_macro {
-            parse_line(main_operator, operands, nodes, macros, token_lines, module_manager, symbol_table);
+            parse_line(main_operator, &operands, nodes, macros, token_lines, module_manager, symbol_table);
         }
 
         // The macro has been expanded and parsed, there's nothing more to do
@@ -374,20 +374,41 @@ fn parse_line<'a>(main_operator: Token<'a>, operands: Box<[AsmOperand<'a>]>, nod
                 body: body.into_boxed_slice()
             }).is_some() {
                 // Disallow redefining a macro.
-                errors::symbol_redeclaration(&main_operator.source, module_manager, &symbol_table.get_symbol(macro_id).borrow());
+                errors::symbol_redeclaration(&main_operator.source, module_manager, symbol_table.get_symbol(macro_id).borrow());
             }
         },
 
         TokenValue::Dot => {
-            check_arg_count!(1);
 
-            let op = &operands[0];
+            let op = operands.first().unwrap_or_else(
+                || errors::parsing_error(&main_operator.source, module_manager, "Expected a section name after `.`.")
+            );
             let symbol_id = if let AsmValue::Symbol(id) = op.value {
                 id
             } else {
                 errors::parsing_error(&op.source, module_manager, "Expected a symbol as section name.");
             };
 
+            // Optional section attributes after the section name
+            let mut attributes = SectionAttributes::default();
+            for attribute in &operands[1..] {
+
+                let name = if let AsmValue::Symbol(id) = attribute.value {
+                    symbol_table.get_symbol(id).name
+                } else {
+                    errors::parsing_error(&attribute.source, module_manager, "Expected a section attribute name.");
+                };
+
+                match name {
+                    "writable" => attributes.writable = true,
+                    "zeroed" => {
+                        attributes.writable = true;
+                        attributes.zeroed = true;
+                    },
+                    _ => errors::parsing_error(&attribute.source, module_manager, format!("Unknown section attribute `{name}`. Expected `writable` or `zeroed`.").as_str())
+                }
+            }
+
             { // Scope for symbol borrow (cannot borrow again later while `symbol` is still borrowed)
                 let symbol = symbol_table.get_symbol(symbol_id);
 
@@ -396,7 +417,7 @@ fn parse_line<'a>(main_operator: Token<'a>, operands: Box<[AsmOperand<'a>]>, nod
                 }
                 
                 nodes.push(AsmNode {
-                    value: AsmNodeValue::Section(symbol.source.string),
+                    value: AsmNodeValue::Section(symbol.source.string, attributes),
                     source: main_operator.source.clone()
                 });
             }
@@ -427,6 +448,62 @@ fn parse_line<'a>(main_operator: Token<'a>, operands: Box<[AsmOperand<'a>]>, nod
             ByteCodes::ModInt2 => no_args_instruction!(ModInt2),
             ByteCodes::ModInt4 => no_args_instruction!(ModInt4),
             ByteCodes::ModInt8 => no_args_instruction!(ModInt8),
+            ByteCodes::DivUint1 => no_args_instruction!(DivUint1),
+            ByteCodes::DivUint2 => no_args_instruction!(DivUint2),
+            ByteCodes::DivUint4 => no_args_instruction!(DivUint4),
+            ByteCodes::DivUint8 => no_args_instruction!(DivUint8),
+            ByteCodes::ModUint1 => no_args_instruction!(ModUint1),
+            ByteCodes::ModUint2 => no_args_instruction!(ModUint2),
+            ByteCodes::ModUint4 => no_args_instruction!(ModUint4),
+            ByteCodes::ModUint8 => no_args_instruction!(ModUint8),
+            ByteCodes::CheckedAddInt1 => no_args_instruction!(CheckedAddInt1),
+            ByteCodes::CheckedAddInt2 => no_args_instruction!(CheckedAddInt2),
+            ByteCodes::CheckedAddInt4 => no_args_instruction!(CheckedAddInt4),
+            ByteCodes::CheckedAddInt8 => no_args_instruction!(CheckedAddInt8),
+            ByteCodes::CheckedAddUint1 => no_args_instruction!(CheckedAddUint1),
+            ByteCodes::CheckedAddUint2 => no_args_instruction!(CheckedAddUint2),
+            ByteCodes::CheckedAddUint4 => no_args_instruction!(CheckedAddUint4),
+            ByteCodes::CheckedAddUint8 => no_args_instruction!(CheckedAddUint8),
+            ByteCodes::CheckedSubInt1 => no_args_instruction!(CheckedSubInt1),
+            ByteCodes::CheckedSubInt2 => no_args_instruction!(CheckedSubInt2),
+            ByteCodes::CheckedSubInt4 => no_args_instruction!(CheckedSubInt4),
+            ByteCodes::CheckedSubInt8 => no_args_instruction!(CheckedSubInt8),
+            ByteCodes::CheckedSubUint1 => no_args_instruction!(CheckedSubUint1),
+            ByteCodes::CheckedSubUint2 => no_args_instruction!(CheckedSubUint2),
+            ByteCodes::CheckedSubUint4 => no_args_instruction!(CheckedSubUint4),
+            ByteCodes::CheckedSubUint8 => no_args_instruction!(CheckedSubUint8),
+            ByteCodes::CheckedMulInt1 => no_args_instruction!(CheckedMulInt1),
+            ByteCodes::CheckedMulInt2 => no_args_instruction!(CheckedMulInt2),
+            ByteCodes::CheckedMulInt4 => no_args_instruction!(CheckedMulInt4),
+            ByteCodes::CheckedMulInt8 => no_args_instruction!(CheckedMulInt8),
+            ByteCodes::CheckedMulUint1 => no_args_instruction!(CheckedMulUint1),
+            ByteCodes::CheckedMulUint2 => no_args_instruction!(CheckedMulUint2),
+            ByteCodes::CheckedMulUint4 => no_args_instruction!(CheckedMulUint4),
+            ByteCodes::CheckedMulUint8 => no_args_instruction!(CheckedMulUint8),
+            ByteCodes::SaturatingAddInt1 => no_args_instruction!(SaturatingAddInt1),
+            ByteCodes::SaturatingAddInt2 => no_args_instruction!(SaturatingAddInt2),
+            ByteCodes::SaturatingAddInt4 => no_args_instruction!(SaturatingAddInt4),
+            ByteCodes::SaturatingAddInt8 => no_args_instruction!(SaturatingAddInt8),
+            ByteCodes::SaturatingAddUint1 => no_args_instruction!(SaturatingAddUint1),
+            ByteCodes::SaturatingAddUint2 => no_args_instruction!(SaturatingAddUint2),
+            ByteCodes::SaturatingAddUint4 => no_args_instruction!(SaturatingAddUint4),
+            ByteCodes::SaturatingAddUint8 => no_args_instruction!(SaturatingAddUint8),
+            ByteCodes::SaturatingSubInt1 => no_args_instruction!(SaturatingSubInt1),
+            ByteCodes::SaturatingSubInt2 => no_args_instruction!(SaturatingSubInt2),
+            ByteCodes::SaturatingSubInt4 => no_args_instruction!(SaturatingSubInt4),
+            ByteCodes::SaturatingSubInt8 => no_args_instruction!(SaturatingSubInt8),
+            ByteCodes::SaturatingSubUint1 => no_args_instruction!(SaturatingSubUint1),
+            ByteCodes::SaturatingSubUint2 => no_args_instruction!(SaturatingSubUint2),
+            ByteCodes::SaturatingSubUint4 => no_args_instruction!(SaturatingSubUint4),
+            ByteCodes::SaturatingSubUint8 => no_args_instruction!(SaturatingSubUint8),
+            ByteCodes::SaturatingMulInt1 => no_args_instruction!(SaturatingMulInt1),
+            ByteCodes::SaturatingMulInt2 => no_args_instruction!(SaturatingMulInt2),
+            ByteCodes::SaturatingMulInt4 => no_args_instruction!(SaturatingMulInt4),
+            ByteCodes::SaturatingMulInt8 => no_args_instruction!(SaturatingMulInt8),
+            ByteCodes::SaturatingMulUint1 => no_args_instruction!(SaturatingMulUint1),
+            ByteCodes::SaturatingMulUint2 => no_args_instruction!(SaturatingMulUint2),
+            ByteCodes::SaturatingMulUint4 => no_args_instruction!(SaturatingMulUint4),
+            ByteCodes::SaturatingMulUint8 => no_args_instruction!(SaturatingMulUint8),
             ByteCodes::AddFloat4 => no_args_instruction!(AddFloat4),
             ByteCodes::AddFloat8 => no_args_instruction!(AddFloat8),
             ByteCodes::SubFloat4 => no_args_instruction!(SubFloat4),
@@ -437,6 +514,177 @@ fn parse_line<'a>(main_operator: Token<'a>, operands: Box<[AsmOperand<'a>]>, nod
             ByteCodes::DivFloat8 => no_args_instruction!(DivFloat8),
             ByteCodes::ModFloat4 => no_args_instruction!(ModFloat4),
             ByteCodes::ModFloat8 => no_args_instruction!(ModFloat8),
+            ByteCodes::NegFloat4 => no_args_instruction!(NegFloat4),
+            ByteCodes::NegFloat8 => no_args_instruction!(NegFloat8),
+            ByteCodes::AbsFloat4 => no_args_instruction!(AbsFloat4),
+            ByteCodes::AbsFloat8 => no_args_instruction!(AbsFloat8),
+            ByteCodes::SqrtFloat4 => no_args_instruction!(SqrtFloat4),
+            ByteCodes::SqrtFloat8 => no_args_instruction!(SqrtFloat8),
+            ByteCodes::FloorFloat4 => no_args_instruction!(FloorFloat4),
+            ByteCodes::FloorFloat8 => no_args_instruction!(FloorFloat8),
+            ByteCodes::CeilFloat4 => no_args_instruction!(CeilFloat4),
+            ByteCodes::CeilFloat8 => no_args_instruction!(CeilFloat8),
+            ByteCodes::MinFloat4 => no_args_instruction!(MinFloat4),
+            ByteCodes::MinFloat8 => no_args_instruction!(MinFloat8),
+            ByteCodes::MaxFloat4 => no_args_instruction!(MaxFloat4),
+            ByteCodes::MaxFloat8 => no_args_instruction!(MaxFloat8),
+            ByteCodes::SinFloat4 => no_args_instruction!(SinFloat4),
+            ByteCodes::SinFloat8 => no_args_instruction!(SinFloat8),
+            ByteCodes::CosFloat4 => no_args_instruction!(CosFloat4),
+            ByteCodes::CosFloat8 => no_args_instruction!(CosFloat8),
+            ByteCodes::ExpFloat4 => no_args_instruction!(ExpFloat4),
+            ByteCodes::ExpFloat8 => no_args_instruction!(ExpFloat8),
+            ByteCodes::LogFloat4 => no_args_instruction!(LogFloat4),
+            ByteCodes::LogFloat8 => no_args_instruction!(LogFloat8),
+            ByteCodes::PowFloat4 => no_args_instruction!(PowFloat4),
+            ByteCodes::PowFloat8 => no_args_instruction!(PowFloat8),
+            ByteCodes::BitAnd1 => no_args_instruction!(BitAnd1),
+            ByteCodes::BitAnd2 => no_args_instruction!(BitAnd2),
+            ByteCodes::BitAnd4 => no_args_instruction!(BitAnd4),
+            ByteCodes::BitAnd8 => no_args_instruction!(BitAnd8),
+            ByteCodes::BitOr1 => no_args_instruction!(BitOr1),
+            ByteCodes::BitOr2 => no_args_instruction!(BitOr2),
+            ByteCodes::BitOr4 => no_args_instruction!(BitOr4),
+            ByteCodes::BitOr8 => no_args_instruction!(BitOr8),
+            ByteCodes::BitXor1 => no_args_instruction!(BitXor1),
+            ByteCodes::BitXor2 => no_args_instruction!(BitXor2),
+            ByteCodes::BitXor4 => no_args_instruction!(BitXor4),
+            ByteCodes::BitXor8 => no_args_instruction!(BitXor8),
+            ByteCodes::BitNot1 => no_args_instruction!(BitNot1),
+            ByteCodes::BitNot2 => no_args_instruction!(BitNot2),
+            ByteCodes::BitNot4 => no_args_instruction!(BitNot4),
+            ByteCodes::BitNot8 => no_args_instruction!(BitNot8),
+            ByteCodes::ShiftLeft1 => no_args_instruction!(ShiftLeft1),
+            ByteCodes::ShiftLeft2 => no_args_instruction!(ShiftLeft2),
+            ByteCodes::ShiftLeft4 => no_args_instruction!(ShiftLeft4),
+            ByteCodes::ShiftLeft8 => no_args_instruction!(ShiftLeft8),
+            ByteCodes::ShiftRight1 => no_args_instruction!(ShiftRight1),
+            ByteCodes::ShiftRight2 => no_args_instruction!(ShiftRight2),
+            ByteCodes::ShiftRight4 => no_args_instruction!(ShiftRight4),
+            ByteCodes::ShiftRight8 => no_args_instruction!(ShiftRight8),
+            ByteCodes::ShiftRightArith1 => no_args_instruction!(ShiftRightArith1),
+            ByteCodes::ShiftRightArith2 => no_args_instruction!(ShiftRightArith2),
+            ByteCodes::ShiftRightArith4 => no_args_instruction!(ShiftRightArith4),
+            ByteCodes::ShiftRightArith8 => no_args_instruction!(ShiftRightArith8),
+            ByteCodes::RotateLeft1 => no_args_instruction!(RotateLeft1),
+            ByteCodes::RotateLeft2 => no_args_instruction!(RotateLeft2),
+            ByteCodes::RotateLeft4 => no_args_instruction!(RotateLeft4),
+            ByteCodes::RotateLeft8 => no_args_instruction!(RotateLeft8),
+            ByteCodes::RotateRight1 => no_args_instruction!(RotateRight1),
+            ByteCodes::RotateRight2 => no_args_instruction!(RotateRight2),
+            ByteCodes::RotateRight4 => no_args_instruction!(RotateRight4),
+            ByteCodes::RotateRight8 => no_args_instruction!(RotateRight8),
+            ByteCodes::ByteSwap2 => no_args_instruction!(ByteSwap2),
+            ByteCodes::ByteSwap4 => no_args_instruction!(ByteSwap4),
+            ByteCodes::ByteSwap8 => no_args_instruction!(ByteSwap8),
+            ByteCodes::EqualInt1 => no_args_instruction!(EqualInt1),
+            ByteCodes::EqualInt2 => no_args_instruction!(EqualInt2),
+            ByteCodes::EqualInt4 => no_args_instruction!(EqualInt4),
+            ByteCodes::EqualInt8 => no_args_instruction!(EqualInt8),
+            ByteCodes::NotEqualInt1 => no_args_instruction!(NotEqualInt1),
+            ByteCodes::NotEqualInt2 => no_args_instruction!(NotEqualInt2),
+            ByteCodes::NotEqualInt4 => no_args_instruction!(NotEqualInt4),
+            ByteCodes::NotEqualInt8 => no_args_instruction!(NotEqualInt8),
+            ByteCodes::LessInt1 => no_args_instruction!(LessInt1),
+            ByteCodes::LessInt2 => no_args_instruction!(LessInt2),
+            ByteCodes::LessInt4 => no_args_instruction!(LessInt4),
+            ByteCodes::LessInt8 => no_args_instruction!(LessInt8),
+            ByteCodes::LessEqualInt1 => no_args_instruction!(LessEqualInt1),
+            ByteCodes::LessEqualInt2 => no_args_instruction!(LessEqualInt2),
+            ByteCodes::LessEqualInt4 => no_args_instruction!(LessEqualInt4),
+            ByteCodes::LessEqualInt8 => no_args_instruction!(LessEqualInt8),
+            ByteCodes::GreaterInt1 => no_args_instruction!(GreaterInt1),
+            ByteCodes::GreaterInt2 => no_args_instruction!(GreaterInt2),
+            ByteCodes::GreaterInt4 => no_args_instruction!(GreaterInt4),
+            ByteCodes::GreaterInt8 => no_args_instruction!(GreaterInt8),
+            ByteCodes::GreaterEqualInt1 => no_args_instruction!(GreaterEqualInt1),
+            ByteCodes::GreaterEqualInt2 => no_args_instruction!(GreaterEqualInt2),
+            ByteCodes::GreaterEqualInt4 => no_args_instruction!(GreaterEqualInt4),
+            ByteCodes::GreaterEqualInt8 => no_args_instruction!(GreaterEqualInt8),
+            ByteCodes::LessUint1 => no_args_instruction!(LessUint1),
+            ByteCodes::LessUint2 => no_args_instruction!(LessUint2),
+            ByteCodes::LessUint4 => no_args_instruction!(LessUint4),
+            ByteCodes::LessUint8 => no_args_instruction!(LessUint8),
+            ByteCodes::LessEqualUint1 => no_args_instruction!(LessEqualUint1),
+            ByteCodes::LessEqualUint2 => no_args_instruction!(LessEqualUint2),
+            ByteCodes::LessEqualUint4 => no_args_instruction!(LessEqualUint4),
+            ByteCodes::LessEqualUint8 => no_args_instruction!(LessEqualUint8),
+            ByteCodes::GreaterUint1 => no_args_instruction!(GreaterUint1),
+            ByteCodes::GreaterUint2 => no_args_instruction!(GreaterUint2),
+            ByteCodes::GreaterUint4 => no_args_instruction!(GreaterUint4),
+            ByteCodes::GreaterUint8 => no_args_instruction!(GreaterUint8),
+            ByteCodes::GreaterEqualUint1 => no_args_instruction!(GreaterEqualUint1),
+            ByteCodes::GreaterEqualUint2 => no_args_instruction!(GreaterEqualUint2),
+            ByteCodes::GreaterEqualUint4 => no_args_instruction!(GreaterEqualUint4),
+            ByteCodes::GreaterEqualUint8 => no_args_instruction!(GreaterEqualUint8),
+            ByteCodes::EqualFloat4 => no_args_instruction!(EqualFloat4),
+            ByteCodes::EqualFloat8 => no_args_instruction!(EqualFloat8),
+            ByteCodes::NotEqualFloat4 => no_args_instruction!(NotEqualFloat4),
+            ByteCodes::NotEqualFloat8 => no_args_instruction!(NotEqualFloat8),
+            ByteCodes::LessFloat4 => no_args_instruction!(LessFloat4),
+            ByteCodes::LessFloat8 => no_args_instruction!(LessFloat8),
+            ByteCodes::LessEqualFloat4 => no_args_instruction!(LessEqualFloat4),
+            ByteCodes::LessEqualFloat8 => no_args_instruction!(LessEqualFloat8),
+            ByteCodes::GreaterFloat4 => no_args_instruction!(GreaterFloat4),
+            ByteCodes::GreaterFloat8 => no_args_instruction!(GreaterFloat8),
+            ByteCodes::GreaterEqualFloat4 => no_args_instruction!(GreaterEqualFloat4),
+            ByteCodes::GreaterEqualFloat8 => no_args_instruction!(GreaterEqualFloat8),
+            ByteCodes::Select1 => no_args_instruction!(Select1),
+            ByteCodes::Select2 => no_args_instruction!(Select2),
+            ByteCodes::Select4 => no_args_instruction!(Select4),
+            ByteCodes::Select8 => no_args_instruction!(Select8),
+            ByteCodes::SignExtend1To2 => no_args_instruction!(SignExtend1To2),
+            ByteCodes::SignExtend1To4 => no_args_instruction!(SignExtend1To4),
+            ByteCodes::SignExtend1To8 => no_args_instruction!(SignExtend1To8),
+            ByteCodes::SignExtend2To4 => no_args_instruction!(SignExtend2To4),
+            ByteCodes::SignExtend2To8 => no_args_instruction!(SignExtend2To8),
+            ByteCodes::SignExtend4To8 => no_args_instruction!(SignExtend4To8),
+            ByteCodes::ZeroExtend1To2 => no_args_instruction!(ZeroExtend1To2),
+            ByteCodes::ZeroExtend1To4 => no_args_instruction!(ZeroExtend1To4),
+            ByteCodes::ZeroExtend1To8 => no_args_instruction!(ZeroExtend1To8),
+            ByteCodes::ZeroExtend2To4 => no_args_instruction!(ZeroExtend2To4),
+            ByteCodes::ZeroExtend2To8 => no_args_instruction!(ZeroExtend2To8),
+            ByteCodes::ZeroExtend4To8 => no_args_instruction!(ZeroExtend4To8),
+            ByteCodes::Truncate2To1 => no_args_instruction!(Truncate2To1),
+            ByteCodes::Truncate4To1 => no_args_instruction!(Truncate4To1),
+            ByteCodes::Truncate8To1 => no_args_instruction!(Truncate8To1),
+            ByteCodes::Truncate4To2 => no_args_instruction!(Truncate4To2),
+            ByteCodes::Truncate8To2 => no_args_instruction!(Truncate8To2),
+            ByteCodes::Truncate8To4 => no_args_instruction!(Truncate8To4),
+            ByteCodes::Int1ToFloat4 => no_args_instruction!(Int1ToFloat4),
+            ByteCodes::Int1ToFloat8 => no_args_instruction!(Int1ToFloat8),
+            ByteCodes::Int2ToFloat4 => no_args_instruction!(Int2ToFloat4),
+            ByteCodes::Int2ToFloat8 => no_args_instruction!(Int2ToFloat8),
+            ByteCodes::Int4ToFloat4 => no_args_instruction!(Int4ToFloat4),
+            ByteCodes::Int4ToFloat8 => no_args_instruction!(Int4ToFloat8),
+            ByteCodes::Int8ToFloat4 => no_args_instruction!(Int8ToFloat4),
+            ByteCodes::Int8ToFloat8 => no_args_instruction!(Int8ToFloat8),
+            ByteCodes::Uint1ToFloat4 => no_args_instruction!(Uint1ToFloat4),
+            ByteCodes::Uint1ToFloat8 => no_args_instruction!(Uint1ToFloat8),
+            ByteCodes::Uint2ToFloat4 => no_args_instruction!(Uint2ToFloat4),
+            ByteCodes::Uint2ToFloat8 => no_args_instruction!(Uint2ToFloat8),
+            ByteCodes::Uint4ToFloat4 => no_args_instruction!(Uint4ToFloat4),
+            ByteCodes::Uint4ToFloat8 => no_args_instruction!(Uint4ToFloat8),
+            ByteCodes::Uint8ToFloat4 => no_args_instruction!(Uint8ToFloat4),
+            ByteCodes::Uint8ToFloat8 => no_args_instruction!(Uint8ToFloat8),
+            ByteCodes::Float4ToInt1 => no_args_instruction!(Float4ToInt1),
+            ByteCodes::Float8ToInt1 => no_args_instruction!(Float8ToInt1),
+            ByteCodes::Float4ToInt2 => no_args_instruction!(Float4ToInt2),
+            ByteCodes::Float8ToInt2 => no_args_instruction!(Float8ToInt2),
+            ByteCodes::Float4ToInt4 => no_args_instruction!(Float4ToInt4),
+            ByteCodes::Float8ToInt4 => no_args_instruction!(Float8ToInt4),
+            ByteCodes::Float4ToInt8 => no_args_instruction!(Float4ToInt8),
+            ByteCodes::Float8ToInt8 => no_args_instruction!(Float8ToInt8),
+            ByteCodes::Float4ToUint1 => no_args_instruction!(Float4ToUint1),
+            ByteCodes::Float8ToUint1 => no_args_instruction!(Float8ToUint1),
+            ByteCodes::Float4ToUint2 => no_args_instruction!(Float4ToUint2),
+            ByteCodes::Float8ToUint2 => no_args_instruction!(Float8ToUint2),
+            ByteCodes::Float4ToUint4 => no_args_instruction!(Float4ToUint4),
+            ByteCodes::Float8ToUint4 => no_args_instruction!(Float8ToUint4),
+            ByteCodes::Float4ToUint8 => no_args_instruction!(Float4ToUint8),
+            ByteCodes::Float8ToUint8 => no_args_instruction!(Float8ToUint8),
+            ByteCodes::Float4ToFloat8 => no_args_instruction!(Float4ToFloat8),
+            ByteCodes::Float8ToFloat4 => no_args_instruction!(Float8ToFloat4),
             ByteCodes::LoadStatic1 => one_arg_address_instruction!(LoadStatic1),
             ByteCodes::LoadStatic2 => one_arg_address_instruction!(LoadStatic2),
             ByteCodes::LoadStatic4 => one_arg_address_instruction!(LoadStatic4),
@@ -486,6 +734,16 @@ fn parse_line<'a>(main_operator: Token<'a>, operands: Box<[AsmOperand<'a>]>, nod
             ByteCodes::PopBytes => no_args_instruction!(PopBytes),
             ByteCodes::VirtualConstToReal => one_arg_address_instruction!(VirtualConstToReal),
             ByteCodes::VirtualToReal => no_args_instruction!(VirtualToReal),
+            ByteCodes::LoadData1 => one_arg_address_instruction!(LoadData1),
+            ByteCodes::LoadData2 => one_arg_address_instruction!(LoadData2),
+            ByteCodes::LoadData4 => one_arg_address_instruction!(LoadData4),
+            ByteCodes::LoadData8 => one_arg_address_instruction!(LoadData8),
+            ByteCodes::StoreData1 => one_arg_address_instruction!(StoreData1),
+            ByteCodes::StoreData2 => one_arg_address_instruction!(StoreData2),
+            ByteCodes::StoreData4 => one_arg_address_instruction!(StoreData4),
+            ByteCodes::StoreData8 => one_arg_address_instruction!(StoreData8),
+            ByteCodes::VirtualDataConstToReal => one_arg_address_instruction!(VirtualDataConstToReal),
+            ByteCodes::VirtualDataToReal => no_args_instruction!(VirtualDataToReal),
             ByteCodes::Store1 => no_args_instruction!(Store1),
             ByteCodes::Store2 => no_args_instruction!(Store2),
             ByteCodes::Store4 => no_args_instruction!(Store4),
@@ -501,11 +759,63 @@ fn parse_line<'a>(main_operator: Token<'a>, operands: Box<[AsmOperand<'a>]>, nod
             ByteCodes::Duplicate4 => no_args_instruction!(Duplicate4),
             ByteCodes::Duplicate8 => no_args_instruction!(Duplicate8),
             ByteCodes::DuplicateBytes => no_args_instruction!(DuplicateBytes),
+            ByteCodes::Swap1 => no_args_instruction!(Swap1),
+            ByteCodes::Swap2 => no_args_instruction!(Swap2),
+            ByteCodes::Swap4 => no_args_instruction!(Swap4),
+            ByteCodes::Swap8 => no_args_instruction!(Swap8),
+            ByteCodes::Over1 => no_args_instruction!(Over1),
+            ByteCodes::Over2 => no_args_instruction!(Over2),
+            ByteCodes::Over4 => no_args_instruction!(Over4),
+            ByteCodes::Over8 => no_args_instruction!(Over8),
+            ByteCodes::Rotate1 => no_args_instruction!(Rotate1),
+            ByteCodes::Rotate2 => no_args_instruction!(Rotate2),
+            ByteCodes::Rotate4 => no_args_instruction!(Rotate4),
+            ByteCodes::Rotate8 => no_args_instruction!(Rotate8),
+            ByteCodes::Pick1 => one_arg_numeric_instruction!(Pick1),
+            ByteCodes::Pick2 => one_arg_numeric_instruction!(Pick2),
+            ByteCodes::Pick4 => one_arg_numeric_instruction!(Pick4),
+            ByteCodes::Pick8 => one_arg_numeric_instruction!(Pick8),
+            ByteCodes::Roll1 => one_arg_numeric_instruction!(Roll1),
+            ByteCodes::Roll2 => one_arg_numeric_instruction!(Roll2),
+            ByteCodes::Roll4 => one_arg_numeric_instruction!(Roll4),
+            ByteCodes::Roll8 => one_arg_numeric_instruction!(Roll8),
             ByteCodes::Malloc => no_args_instruction!(Malloc),
             ByteCodes::Realloc => no_args_instruction!(Realloc),
             ByteCodes::Free => no_args_instruction!(Free),
+            ByteCodes::Calloc => no_args_instruction!(Calloc),
+            ByteCodes::MallocAligned => no_args_instruction!(MallocAligned),
             ByteCodes::Intr => no_args_instruction!(Intr),
             ByteCodes::IntrConst => one_arg_numeric_instruction!(IntrConst),
+
+            ByteCodes::HostCall => {
+                check_arg_count!(1);
+
+                let op = &operands[0];
+
+                // Host function names are resolved right away, since they must be declared with `hostfn` before being called
+                let value = match &op.value {
+                    AsmValue::Const(n) => NumberLike::from_number(n),
+
+                    AsmValue::Symbol(id) => {
+                        let host_function_id = symbol_table.get_symbol(*id)
+                            .value
+                            .as_ref()
+                            .and_then(|value| value.as_uint(symbol_table))
+                            .unwrap_or_else(
+                                || errors::parsing_error(&op.source, module_manager, "Expected a host function declared with `hostfn`.")
+                            );
+                        NumberLike::from_number(&Number::Uint(host_function_id))
+                    },
+
+                    _ => errors::parsing_error(&op.source, module_manager, "Expected a host function name or number.")
+                };
+
+                nodes.push(AsmNode {
+                    value: AsmNodeValue::Instruction(AsmInstruction::HostCall { value: (value, op.source.clone()) }),
+                    source: main_operator.source.clone()
+                });
+            },
+
             ByteCodes::ReadError => no_args_instruction!(ReadError),
             ByteCodes::SetErrorConst => one_arg_numeric_instruction!(SetErrorConst),
             ByteCodes::SetError => no_args_instruction!(SetError),
@@ -533,6 +843,27 @@ fn parse_line<'a>(main_operator: Token<'a>, operands: Box<[AsmOperand<'a>]>, nod
             ByteCodes::JumpError => no_args_instruction!(JumpError),
             ByteCodes::JumpNoError => no_args_instruction!(JumpNoError),
             ByteCodes::Call => one_arg_address_instruction!(Call),
+            ByteCodes::CallIndirect => no_args_instruction!(CallIndirect),
+            ByteCodes::TailCall => one_arg_address_instruction!(TailCall),
+            ByteCodes::Return => no_args_instruction!(Return),
+            ByteCodes::Enter => one_arg_numeric_instruction!(Enter),
+            ByteCodes::Leave => no_args_instruction!(Leave),
+            ByteCodes::LoadLocal1 => one_arg_numeric_instruction!(LoadLocal1),
+            ByteCodes::LoadLocal2 => one_arg_numeric_instruction!(LoadLocal2),
+            ByteCodes::LoadLocal4 => one_arg_numeric_instruction!(LoadLocal4),
+            ByteCodes::LoadLocal8 => one_arg_numeric_instruction!(LoadLocal8),
+            ByteCodes::StoreLocal1 => one_arg_numeric_instruction!(StoreLocal1),
+            ByteCodes::StoreLocal2 => one_arg_numeric_instruction!(StoreLocal2),
+            ByteCodes::StoreLocal4 => one_arg_numeric_instruction!(StoreLocal4),
+            ByteCodes::StoreLocal8 => one_arg_numeric_instruction!(StoreLocal8),
+            ByteCodes::LoadArg1 => one_arg_numeric_instruction!(LoadArg1),
+            ByteCodes::LoadArg2 => one_arg_numeric_instruction!(LoadArg2),
+            ByteCodes::LoadArg4 => one_arg_numeric_instruction!(LoadArg4),
+            ByteCodes::LoadArg8 => one_arg_numeric_instruction!(LoadArg8),
+            ByteCodes::StoreArg1 => one_arg_numeric_instruction!(StoreArg1),
+            ByteCodes::StoreArg2 => one_arg_numeric_instruction!(StoreArg2),
+            ByteCodes::StoreArg4 => one_arg_numeric_instruction!(StoreArg4),
+            ByteCodes::StoreArg8 => one_arg_numeric_instruction!(StoreArg8),
             ByteCodes::Nop => no_args_instruction!(Nop),
         },
 
@@ -602,7 +933,48 @@ fn parse_line<'a>(main_operator: Token<'a>, operands: Box<[AsmOperand<'a>]>, nod
                 nodes.extend(include_asm);
             },
 
-            PseudoInstructions::Return => no_args_instruction!(Return),
+            PseudoInstructions::DeclareHostFunction => {
+
+                // Syntax: hostfn name
+                // The host function is given the next host function number, which the name expands to in `hostcall` instructions
+
+                check_arg_count!(1);
+
+                let op = &operands[0];
+                let symbol_id = if let AsmValue::Symbol(id) = op.value {
+                    id
+                } else {
+                    errors::parsing_error(&op.source, module_manager, "Expected a symbol as host function name.");
+                };
+
+                let name = {
+                    let symbol = symbol_table.get_symbol(symbol_id);
+
+                    if symbol.value.is_some() {
+                        errors::symbol_redeclaration(&op.source, module_manager, &symbol);
+                    }
+
+                    symbol.name
+                };
+
+                let host_function_id = symbol_table.declare_host_function(name);
+                symbol_table.define_symbol(symbol_id, Some(AsmValue::Const(Number::Uint(host_function_id as u64))), op.source.clone());
+            },
+
+            PseudoInstructions::Export => {
+
+                // Syntax: export label
+                // The label is resolved when the binary is generated, so it can be defined after the directive
+
+                check_arg_count!(1);
+
+                let op = &operands[0];
+                let AsmValue::Symbol(symbol_id) = op.value else {
+                    errors::parsing_error(&op.source, module_manager, "Expected a label name.");
+                };
+
+                symbol_table.declare_export(symbol_id, op.source.clone());
+            },
 
         },
 
@@ -639,7 +1011,7 @@ pub fn parse<'a>(mut token_lines: TokenLines<'a>, symbol_table: &'a SymbolTable<
 
         let operands = parse_operands(line, symbol_table, module_manager);
 
-        parse_line(main_operator, operands, &mut nodes, macros, &mut token_lines, module_manager, symbol_table);
+        parse_line(main_operator, &operands, &mut nodes, macros, &mut token_lines, module_manager, symbol_table);
     }
 
     nodes.shrink_to_fit();
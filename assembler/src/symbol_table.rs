@@ -29,7 +29,7 @@ pub enum StaticValue<'a> {
 
 impl StaticValue<'_> {
 
-    pub fn as_string<'a>(&'a self) -> &'a str {
+    pub fn as_string(&self) -> &str {
         match self {
             Self::StringLiteral(s) => s,
         }
@@ -43,6 +43,10 @@ pub struct SymbolTable<'a> {
     symbols: UnsafeCell<Vec<RefCell<Symbol<'a>>>>,
     symbol_ids: UnsafeCell<HashMap<&'a str, SymbolID>>,
     statics: UnsafeCell<Vec<RefCell<StaticValue<'a>>>>,
+    /// Names of the declared host functions. The index of a name is its host function number.
+    host_functions: UnsafeCell<Vec<&'a str>>,
+    /// Labels exported to the host and the source of their `export` directive.
+    exports: UnsafeCell<Vec<(SymbolID, Rc<SourceToken<'a>>)>>,
 
 }
 
@@ -53,6 +57,8 @@ impl<'a> SymbolTable<'a> {
             symbols: Default::default(),
             symbol_ids: Default::default(),
             statics: Default::default(),
+            host_functions: Default::default(),
+            exports: Default::default(),
         }
     }
 
@@ -74,6 +80,34 @@ impl<'a> SymbolTable<'a> {
     }
 
 
+    /// Declares a host function and returns its number, assigned in order of declaration.
+    pub fn declare_host_function(&self, name: &'a str) -> u32 {
+
+        let host_functions = unsafe { &mut *self.host_functions.get() };
+
+        let id = host_functions.len() as u32;
+        host_functions.push(name);
+        id
+    }
+
+
+    pub fn host_functions(&self) -> Vec<String> {
+        let host_functions = unsafe { &*self.host_functions.get() };
+        host_functions.iter().map(|name| name.to_string()).collect()
+    }
+
+
+    pub fn declare_export(&self, id: SymbolID, source: Rc<SourceToken<'a>>) {
+        let exports = unsafe { &mut *self.exports.get() };
+        exports.push((id, source));
+    }
+
+
+    pub fn exports(&self) -> &[(SymbolID, Rc<SourceToken<'a>>)] {
+        unsafe { &*self.exports.get() }
+    }
+
+
     pub fn define_symbol(&self, id: SymbolID, value: Option<AsmValue>, definition_source: Rc<SourceToken<'a>>) {
         
         let symbols = unsafe { &mut *self.symbols.get() };
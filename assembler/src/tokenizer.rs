@@ -139,6 +139,7 @@ fn lex<'a>(source: SourceCode<'a>, unit_path: &'a Path) -> impl Iterator<Item =
 pub struct Token<'a> {
     pub source: Rc<SourceToken<'a>>,
     pub value: TokenValue,
+    #[allow(dead_code)]
     priority: TokenPriority
 }
 
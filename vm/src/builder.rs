@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use crate::exec::{ExecutionMode, VM, DEFAULT_CALL_STACK_DEPTH, DEFAULT_OPSTACK_SIZE};
+use crate::fault::FaultKind;
+use crate::host::{HostContext, HostFunctions};
+use crate::limits::Limits;
+use crate::memory::MemoryModel;
+
+
+/// Configuration of a new `VM`. Every setting has a default, so only the ones that differ need to be set.
+pub struct VmBuilder {
+
+    pub(crate) opstack_size: usize,
+    pub(crate) call_stack_depth: usize,
+    pub(crate) mode: ExecutionMode,
+    pub(crate) memory_model: MemoryModel,
+    pub(crate) sanitize: bool,
+    pub(crate) limits: Limits,
+    pub(crate) buffered: bool,
+    /// Console input. Defaults to the standard input of the host process.
+    pub(crate) input: Option<Box<dyn Read>>,
+    /// Console output. Defaults to the standard output of the host process.
+    pub(crate) output: Option<Box<dyn Write>>,
+    pub(crate) host_functions: HostFunctions,
+    /// Arguments read by the program with the argument interrupts.
+    pub(crate) args: Vec<String>,
+    /// Environment variables read by the program with the environment interrupt. Empty by default.
+    pub(crate) env: HashMap<String, String>,
+    /// Seed of the random interrupt. A different seed is picked for every VM if it's not set.
+    pub(crate) seed: Option<u64>,
+    pub(crate) allow_spawn: bool,
+
+}
+
+impl Default for VmBuilder {
+    fn default() -> Self {
+        Self {
+            opstack_size: DEFAULT_OPSTACK_SIZE,
+            call_stack_depth: DEFAULT_CALL_STACK_DEPTH,
+            mode: ExecutionMode::default(),
+            memory_model: MemoryModel::default(),
+            sanitize: false,
+            limits: Limits::default(),
+            buffered: true,
+            input: None,
+            output: None,
+            host_functions: HostFunctions::default(),
+            args: Vec::new(),
+            env: HashMap::new(),
+            seed: None,
+            allow_spawn: true,
+        }
+    }
+}
+
+impl VmBuilder {
+
+    /// Set the operation stack size in bytes.
+    pub fn opstack_size(mut self, size: usize) -> Self {
+        self.opstack_size = size;
+        self
+    }
+
+
+    /// Set the maximum number of nested function calls.
+    pub fn call_stack_depth(mut self, depth: usize) -> Self {
+        self.call_stack_depth = depth;
+        self
+    }
+
+
+    pub fn mode(mut self, mode: ExecutionMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+
+    pub fn memory_model(mut self, model: MemoryModel) -> Self {
+        self.memory_model = model;
+        self
+    }
+
+
+    /// Check memory accesses for heap buffer overflows, heap use after free, and reads of uninitialized stack memory.
+    pub fn sanitize(mut self, sanitize: bool) -> Self {
+        self.sanitize = sanitize;
+        self
+    }
+
+
+    /// Set the resource limits enforced on every run.
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+
+    /// Buffer the console input and output. Enabled by default.
+    /// The output is flushed by the flush interrupt and at the end of every run.
+    pub fn buffered(mut self, buffered: bool) -> Self {
+        self.buffered = buffered;
+        self
+    }
+
+
+    /// Read the console input of the program from `input`.
+    pub fn input(mut self, input: impl Read + 'static) -> Self {
+        self.input = Some(Box::new(input));
+        self
+    }
+
+
+    /// Write the console output of the program to `output`.
+    pub fn output(mut self, output: impl Write + 'static) -> Self {
+        self.output = Some(Box::new(output));
+        self
+    }
+
+
+    /// Register a host function under `name`. Programs declare it with `hostfn name` and call it with `hostcall name`.
+    pub fn host_function(mut self, name: &str, function: impl FnMut(&mut HostContext) -> Result<(), FaultKind> + 'static) -> Self {
+        self.host_functions.register_name(name.to_string(), Box::new(function));
+        self
+    }
+
+
+    /// Register a host function under the number `id`, for programs that call it with `hostcall id` without declaring it.
+    pub fn host_function_id(mut self, id: u32, function: impl FnMut(&mut HostContext) -> Result<(), FaultKind> + 'static) -> Self {
+        self.host_functions.register_id(id, Box::new(function));
+        self
+    }
+
+
+    /// Set the arguments passed to the program.
+    pub fn args(mut self, args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+
+    /// Pass an environment variable to the program.
+    pub fn env_var(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.insert(name.into(), value.into());
+        self
+    }
+
+
+    /// Pass environment variables to the program, for example the variables of the host process from `std::env::vars`.
+    pub fn env_vars(mut self, vars: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>) -> Self {
+        self.env.extend(vars.into_iter().map(|(name, value)| (name.into(), value.into())));
+        self
+    }
+
+
+    /// Seed the random number generator of the program, so that runs are reproducible.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+
+    /// Allow the program to spawn host processes. Enabled by default.
+    /// When disabled, the spawn interrupt fails with the `PermissionDenied` error code.
+    pub fn allow_spawn(mut self, allow: bool) -> Self {
+        self.allow_spawn = allow;
+        self
+    }
+
+
+    pub fn build(self) -> VM {
+        VM::new(self)
+    }
+
+}
@@ -1,7 +1,10 @@
 use std::path::PathBuf;
+use std::time::Duration;
 
 use clap::Parser;
 
+use vm::MemoryModel;
+
 
 #[derive(Parser)]
 #[clap(author, about, version)]
@@ -15,9 +18,76 @@ pub struct CliParser {
     #[clap()]
     pub opstack_size: Option<usize>,
 
+    /// Set the maximum number of nested function calls.
+    #[clap(long)]
+    pub call_stack_depth: Option<usize>,
+
+    /// Disable runtime safety checks. Faster, but an invalid program results in undefined behavior.
+    #[clap(long)]
+    pub unchecked: bool,
+
+    /// Select the memory model. `virtual` runs the program in a sandboxed address space where every memory access is bounds-checked.
+    #[clap(long, value_enum, default_value_t = MemoryModel::Host)]
+    pub memory: MemoryModel,
+
+    /// Check memory accesses for heap buffer overflows, heap use after free, and reads of uninitialized stack memory.
+    #[clap(long)]
+    pub sanitize: bool,
+
+    /// Stop the program after executing this many instructions.
+    #[clap(long)]
+    pub max_instructions: Option<u64>,
+
+    /// Stop the program if the total size of its live heap blocks would exceed this many bytes.
+    #[clap(long)]
+    pub max_heap: Option<usize>,
+
+    /// Stop the program after running for this many seconds.
+    #[clap(long, value_parser = parse_seconds)]
+    pub timeout: Option<Duration>,
+
+    /// Stop the program if the printing interrupts would write more than this many bytes.
+    #[clap(long)]
+    pub max_output: Option<usize>,
+
+    /// Seed the random number generator of the program, so that runs are reproducible. A different seed is picked for every run by default.
+    #[clap(long)]
+    pub seed: Option<u64>,
+
+    /// Prevent the program from spawning host processes.
+    #[clap(long)]
+    pub deny_spawn: bool,
+
+    /// Report the heap blocks that were never freed when the program exits.
+    #[clap(long)]
+    pub leak_check: bool,
+
+    /// Pass the host environment variables to the program.
+    #[clap(long)]
+    pub inherit_env: bool,
+
+    /// Pass an environment variable to the program.
+    #[clap(long = "env", value_name = "NAME=VALUE", value_parser = parse_env_var)]
+    pub env_vars: Vec<(String, String)>,
+
     /// Execute in verbose mode.
     #[clap(short='v', long)]
     pub verbose: bool,
 
+    /// Arguments passed to the program, given after `--`.
+    #[clap(last = true)]
+    pub args: Vec<String>,
+
 }
 
+
+fn parse_seconds(arg: &str) -> Result<Duration, String> {
+    let seconds: f64 = arg.parse().map_err(|err| format!("{err}"))?;
+    Duration::try_from_secs_f64(seconds).map_err(|err| format!("{err}"))
+}
+
+
+fn parse_env_var(arg: &str) -> Result<(String, String), String> {
+    let (name, value) = arg.split_once('=').ok_or("expected NAME=VALUE")?;
+    Ok((name.to_string(), value.to_string()))
+}
@@ -0,0 +1,49 @@
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+
+/// In-memory console output that can be read back after the VM wrote to it.
+/// Clones share the same buffer, so one clone can be given to the VM while the other is kept by the host.
+#[derive(Debug, Clone, Default)]
+pub struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl SharedBuffer {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+
+    /// Return a copy of the bytes written so far.
+    pub fn contents(&self) -> Vec<u8> {
+        self.0.borrow().clone()
+    }
+
+
+    /// Return the bytes written so far as a string, replacing invalid UTF-8 sequences.
+    pub fn to_string_lossy(&self) -> String {
+        String::from_utf8_lossy(&self.0.borrow()).into_owned()
+    }
+
+
+    /// Remove the bytes written so far.
+    pub fn clear(&self) {
+        self.0.borrow_mut().clear();
+    }
+
+}
+
+impl Write for SharedBuffer {
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+}
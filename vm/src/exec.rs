@@ -1,24 +1,48 @@
 
-use vmlib::{Address, ByteCode, ByteCodes, ErrorCodes, Interrupts, VirtualAddress, INSTRUCTION_SIZE};
-
-use std::io::Read;
+use vmlib::metadata::ProgramMetadata;
+use vmlib::files::{SEEK_CURRENT, SEEK_END, SEEK_START};
+use vmlib::{Address, ByteCode, ByteCodes, ErrorCodes, Interrupts, Opcode, VirtualAddress, ADDRESS_SIZE, INSTRUCTION_SIZE};
+
+use crate::fault::{Backtrace, BacktraceFrame, FaultKind, VmFault};
+use crate::memory::{Memory, RegionKind};
+use crate::sanitizer::{Sanitizer, REDZONE_SIZE};
+use crate::limits::{Limit, Limits};
+use crate::builder::VmBuilder;
+use crate::host::{HostContext, HostFunctions};
+use crate::files::{self, io_error_code, Descriptors, FileStatus, SpawnedProcess};
+use crate::random::Random;
+use crate::format::{self, Conversion, Piece};
+
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::mem::{self, MaybeUninit};
 use std::slice;
 use std::io;
-use std::alloc;
+use std::fs;
 use std::ptr;
+use std::iter;
+use std::alloc::Layout;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::thread;
+use std::path::PathBuf;
+use std::collections::HashMap;
+use std::str;
 
 
-struct Stack {
+pub(crate) struct Stack {
     /// Raw pointer to the top of the stack. Modifying this pointer will directly modify the stack.
     tos: *mut u8,
     /// Owned pointer to the stack. The stack is mutated thorugh the `tos` pointer.
     _stack: Box<[u8]>,
+    /// Whether pushes and pops are bounds-checked.
+    checked: bool,
+    /// Shadow of the stack in sanitizer mode, tells whether each byte was written since it was pushed.
+    /// The stack is deliberately left uninitialized, so reading a byte that was not written is a bug in the program.
+    initialized: Option<Box<[bool]>>,
 }
 
 impl Stack {
 
-    pub fn new(size: usize) -> Self {
+    pub fn new(size: usize, checked: bool, sanitized: bool) -> Self {
         let mut stack = unsafe {
             mem::transmute::<Box<[MaybeUninit<u8>]>, Box<[u8]>>(
                 vec![MaybeUninit::uninit(); size].into_boxed_slice()
@@ -28,190 +52,384 @@ impl Stack {
             tos: unsafe {
                 stack.as_mut_ptr().add(stack.len())
             },
-            _stack: stack
+            _stack: stack,
+            checked,
+            initialized: sanitized.then(|| vec![false; size].into_boxed_slice()),
+        }
+    }
+
+
+    /// Number of bytes currently pushed onto the stack.
+    #[inline]
+    fn used_size(&self) -> usize {
+        self._stack.as_ptr_range().end as usize - self.tos as usize
+    }
+
+
+    /// Number of bytes that can still be pushed onto the stack.
+    #[inline]
+    fn free_size(&self) -> usize {
+        self.tos as usize - self._stack.as_ptr() as usize
+    }
+
+
+    #[inline]
+    fn check_pop(&self, amount: usize) -> Result<(), FaultKind> {
+        if self.checked && amount > self.used_size() {
+            Err(FaultKind::StackUnderflow)
+        } else {
+            Ok(())
+        }
+    }
+
+
+    #[inline]
+    fn check_push(&self, amount: usize) -> Result<(), FaultKind> {
+        if self.checked && amount > self.free_size() {
+            Err(FaultKind::StackOverflow)
+        } else {
+            Ok(())
         }
     }
 
 
+    /// Shadow of the `count` bytes at `ptr`, if the stack is sanitized and they are entirely inside the stack.
+    fn shadow(&mut self, ptr: *const u8, count: usize) -> Option<&mut [bool]> {
+        let offset = (ptr as usize).wrapping_sub(self._stack.as_ptr() as usize);
+        self.initialized.as_mut()?.get_mut(offset..offset.saturating_add(count))
+    }
+
+
+    /// Check that the `count` bytes at `ptr` were initialized, if they are on the stack and the stack is sanitized.
     #[inline]
-    pub unsafe fn tos(&self) -> *const u8 {
-        self.tos
+    pub fn check_initialized(&self, ptr: *const u8, count: usize) -> Result<(), FaultKind> {
+        let Some(initialized) = &self.initialized else {
+            return Ok(());
+        };
+        let offset = (ptr as usize).wrapping_sub(self._stack.as_ptr() as usize);
+        match initialized.get(offset..offset.saturating_add(count)) {
+            Some(shadow) if shadow.contains(&false) => Err(FaultKind::UninitializedStackRead),
+            _ => Ok(())
+        }
     }
 
 
-    // pub unsafe fn tos_mut(&mut self) -> *mut u8 {
-    //     self.tos
-    // }
+    /// Mark the `count` bytes at `ptr` as initialized or not, if they are on the stack and the stack is sanitized.
+    #[inline]
+    pub fn set_initialized(&mut self, ptr: *const u8, count: usize, initialized: bool) {
+        if let Some(shadow) = self.shadow(ptr, count) {
+            shadow.fill(initialized);
+        }
+    }
 
 
-    pub fn peek_1(&self) -> u8 {
+    pub fn peek_1(&self) -> Result<u8, FaultKind> {
+        self.check_pop(mem::size_of::<u8>())?;
+        self.check_initialized(self.tos, mem::size_of::<u8>())?;
         unsafe {
-            self.tos.read_unaligned()
+            Ok(self.tos.read_unaligned())
         }
     }
 
 
-    pub fn peek_2(&self) -> u16 {
+    pub fn peek_2(&self) -> Result<u16, FaultKind> {
+        self.check_pop(mem::size_of::<u16>())?;
+        self.check_initialized(self.tos, mem::size_of::<u16>())?;
         unsafe {
-            (self.tos as *const u16).read_unaligned()
+            Ok((self.tos as *const u16).read_unaligned())
         }
     }
 
 
-    pub fn peek_4(&self) -> u32 {
+    pub fn peek_4(&self) -> Result<u32, FaultKind> {
+        self.check_pop(mem::size_of::<u32>())?;
+        self.check_initialized(self.tos, mem::size_of::<u32>())?;
         unsafe {
-            (self.tos as *const u32).read_unaligned()
+            Ok((self.tos as *const u32).read_unaligned())
         }
     }
 
 
-    pub fn peek_8(&self) -> u64 {
+    pub fn peek_8(&self) -> Result<u64, FaultKind> {
+        self.check_pop(mem::size_of::<u64>())?;
+        self.check_initialized(self.tos, mem::size_of::<u64>())?;
         unsafe {
-            (self.tos as *const u64).read_unaligned()
+            Ok((self.tos as *const u64).read_unaligned())
         }
     }
 
 
-    // pub fn peek_bytes(&self, count: usize) -> &[u8] {
-    //     unsafe {
-    //         std::slice::from_raw_parts(self.tos.byte_sub(count), count)
-    //     }
-    // }
+    pub fn peek_bytes(&self, count: usize) -> Result<&[u8], FaultKind> {
+        self.check_pop(count)?;
+        self.check_initialized(self.tos, count)?;
+        unsafe {
+            Ok(slice::from_raw_parts(self.tos, count))
+        }
+    }
 
 
-    pub fn push_1(&mut self, byte: u8) {
+    pub fn push_1(&mut self, byte: u8) -> Result<(), FaultKind> {
+        self.push_by(mem::size_of::<u8>())?;
         unsafe {
-            self.push_by(mem::size_of::<u8>());
             self.tos.write_unaligned(byte);
         }
+        self.set_initialized(self.tos, mem::size_of::<u8>(), true);
+        Ok(())
     }
 
 
-    pub fn push_2(&mut self, value: u16) {
+    pub fn push_2(&mut self, value: u16) -> Result<(), FaultKind> {
+        self.push_by(mem::size_of::<u16>())?;
         unsafe {
-            self.push_by(mem::size_of::<u16>());
             (self.tos as *mut u16).write_unaligned(value);
         }
+        self.set_initialized(self.tos, mem::size_of::<u16>(), true);
+        Ok(())
     }
 
 
-    pub fn push_4(&mut self, value: u32) {
+    pub fn push_4(&mut self, value: u32) -> Result<(), FaultKind> {
+        self.push_by(mem::size_of::<u32>())?;
         unsafe {
-            self.push_by(mem::size_of::<u32>());
             (self.tos as *mut u32).write_unaligned(value);
         }
+        self.set_initialized(self.tos, mem::size_of::<u32>(), true);
+        Ok(())
     }
 
 
-    pub fn push_8(&mut self, value: u64) {
+    pub fn push_8(&mut self, value: u64) -> Result<(), FaultKind> {
+        self.push_by(mem::size_of::<u64>())?;
         unsafe {
-            self.push_by(mem::size_of::<u64>());
             (self.tos as *mut u64).write_unaligned(value);
         }
+        self.set_initialized(self.tos, mem::size_of::<u64>(), true);
+        Ok(())
     }
 
 
-    pub fn push_bytes(&mut self, bytes: &[u8]) {
+    pub fn push_bytes(&mut self, bytes: &[u8]) -> Result<(), FaultKind> {
+        self.push_by(bytes.len())?;
         unsafe {
-            self.push_by(bytes.len());
-            self.tos.copy_from_nonoverlapping(bytes.as_ptr(), bytes.len())
+            self.tos.copy_from_nonoverlapping(bytes.as_ptr(), bytes.len());
         }
+        self.set_initialized(self.tos, bytes.len(), true);
+        Ok(())
     }
 
 
-    pub fn push_from(&mut self, src: *const u8, count: usize) {
+    pub fn push_from(&mut self, src: *const u8, count: usize) -> Result<(), FaultKind> {
+        self.check_initialized(src, count)?;
+        self.push_by(count)?;
         unsafe {
-            self.push_by(count);
-            self.tos.copy_from_nonoverlapping(src, count);
+            // The source may be on the stack itself
+            ptr::copy(src, self.tos, count);
         }
+        self.set_initialized(self.tos, count, true);
+        Ok(())
     }
 
 
     #[inline]
     /// Pushes the stack by `amount` bytes (decrements the stack pointer)
-    pub fn push_by(&mut self, amount: usize) {
+    pub fn push_by(&mut self, amount: usize) -> Result<(), FaultKind> {
+        self.check_push(amount)?;
         unsafe {
             self.tos = self.tos.byte_sub(amount);
         }
+        self.set_initialized(self.tos, amount, false);
+        Ok(())
     }
 
 
     #[inline]
     /// Pops the stack by `amount` bytes (increments the stack pointer)
-    pub fn pop_by(&mut self, amount: usize) {
+    pub fn pop_by(&mut self, amount: usize) -> Result<(), FaultKind> {
+        self.check_pop(amount)?;
         unsafe {
             self.tos = self.tos.byte_add(amount);
         }
+        Ok(())
     }
 
 
-    pub fn pop_1(&mut self) -> u8 {
-        unsafe {
-            let value = self.tos.read_unaligned();
-            self.pop_by(mem::size_of::<u8>());
-            value
-        }
+    pub fn pop_1(&mut self) -> Result<u8, FaultKind> {
+        let value = self.peek_1()?;
+        self.pop_by(mem::size_of::<u8>())?;
+        Ok(value)
     }
 
 
-    pub fn pop_2(&mut self) -> u16 {
+    pub fn pop_2(&mut self) -> Result<u16, FaultKind> {
+        let value = self.peek_2()?;
+        self.pop_by(mem::size_of::<u16>())?;
+        Ok(value)
+    }
+
+
+    pub fn pop_4(&mut self) -> Result<u32, FaultKind> {
+        let value = self.peek_4()?;
+        self.pop_by(mem::size_of::<u32>())?;
+        Ok(value)
+    }
+
+
+    pub fn pop_8(&mut self) -> Result<u64, FaultKind> {
+        let value = self.peek_8()?;
+        self.pop_by(mem::size_of::<u64>())?;
+        Ok(value)
+    }
+
+
+    pub fn pop_bytes(&mut self, count: usize) -> Result<&[u8], FaultKind> {
+        self.check_pop(count)?;
+        self.check_initialized(self.tos, count)?;
         unsafe {
-            let value = (self.tos as *const u16).read_unaligned();
-            self.pop_by(mem::size_of::<u16>());
-            value
+            let bytes = slice::from_raw_parts::<u8>(self.tos, count);
+            self.tos = self.tos.byte_add(count);
+            Ok(bytes)
         }
     }
 
 
-    pub fn pop_4(&mut self) -> u32 {
+    /// Push a copy of the `size`-byte item at position `index` from the top of the stack (0 is the top item).
+    pub fn pick(&mut self, size: usize, index: usize) -> Result<(), FaultKind> {
+        self.check_pop((index + 1) * size)?;
+        let item = unsafe {
+            self.tos.byte_add(index * size)
+        };
+        self.push_from(item, size)
+    }
+
+
+    /// Move the `size`-byte item at position `index` from the top of the stack to the top of the stack.
+    /// The items above it are shifted down by one position.
+    pub fn roll(&mut self, size: usize, index: usize) -> Result<(), FaultKind> {
+        let region_size = (index + 1) * size;
+        self.check_pop(region_size)?;
+        self.check_initialized(self.tos, region_size)?;
+        let region = unsafe {
+            slice::from_raw_parts_mut(self.tos, region_size)
+        };
+        region.rotate_right(size);
+        Ok(())
+    }
+
+
+    /// Number of bytes currently pushed onto the stack.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.used_size()
+    }
+
+
+    /// Pop the stack until it holds `depth` bytes.
+    pub fn pop_to_depth(&mut self, depth: usize) -> Result<(), FaultKind> {
+        let used = self.used_size();
+        if self.checked && depth > used {
+            return Err(FaultKind::FrameOutOfRange);
+        }
         unsafe {
-            let value = (self.tos as *const u32).read_unaligned();
-            self.pop_by(mem::size_of::<u32>());
-            value
+            self.tos = self._stack.as_mut_ptr().add(self._stack.len() - depth);
         }
+        Ok(())
     }
 
 
-    pub fn pop_8(&mut self) -> u64 {
+    /// Return a pointer to the `size`-byte item whose first byte is `depth` bytes from the bottom of the stack.
+    fn item_at(&mut self, depth: usize, size: usize) -> Result<*mut u8, FaultKind> {
+        if self.checked && (depth > self.used_size() || size > depth) {
+            return Err(FaultKind::FrameOutOfRange);
+        }
         unsafe {
-            let value = (self.tos as *const u64).read_unaligned();
-            self.pop_by(mem::size_of::<u64>());
-            value
+            Ok(self._stack.as_mut_ptr().add(self._stack.len() - depth))
         }
     }
 
 
-    pub fn pop_bytes(&mut self, count: usize) -> &[u8] {
+    /// Push a copy of the `size`-byte item whose first byte is `depth` bytes from the bottom of the stack.
+    pub fn load_at(&mut self, depth: usize, size: usize) -> Result<(), FaultKind> {
+        let item = self.item_at(depth, size)?;
+        self.push_from(item, size)
+    }
+
+
+    /// Pop `size` bytes and write them over the item whose first byte is `depth` bytes from the bottom of the stack.
+    pub fn store_at(&mut self, depth: usize, size: usize) -> Result<(), FaultKind> {
+        self.pop_by(size)?;
+        let item = self.item_at(depth, size)?;
+        let src = unsafe {
+            self.tos.byte_sub(size)
+        };
+        self.check_initialized(src, size)?;
         unsafe {
-            let bytes = std::slice::from_raw_parts::<u8>(self.tos, count);
-            self.pop_by(count);
-            bytes
+            // The popped bytes may overlap the item if it's close to the top of the stack
+            ptr::copy(src, item, size);
         }
+        self.set_initialized(item, size, true);
+        Ok(())
     }
 
 }
 
 
-struct Program<'a> {
+struct Program {
 
-    code: ByteCode<'a>,
+    /// Program space. The code is copied out of the binary, so that the program can stay loaded after the binary is dropped.
+    code: Box<[u8]>,
     // Index of the next instruction/byte in the code.
     program_counter: VirtualAddress,
+    /// Whether instruction fetches and static data accesses are bounds-checked.
+    checked: bool,
+    /// Debug symbols and other information appended to the code by the assembler.
+    metadata: ProgramMetadata,
+    /// Writable data segment, initialized from the metadata at load time. It has its own virtual address space.
+    data: Box<[u8]>,
+    /// Addresses of the program space and of the data segment in the VM memory, set when the program is mapped.
+    code_base: Address,
+    data_base: Address,
 
 }
 
-impl<'a> Program<'a> {
+impl Program {
+
+    pub fn new(binary: ByteCode, checked: bool) -> Result<Self, FaultKind> {
 
-    pub fn new(code: ByteCode<'a>) -> Self {
+        let (code, metadata) = ProgramMetadata::split(binary)
+            .map_err(|_| FaultKind::MalformedMetadata)?;
 
         if code.len() < mem::size_of::<VirtualAddress>() {
-            panic!("Missing entry point");
+            return Err(FaultKind::MissingEntryPoint);
         }
 
-        Self {
+        // Copy the initialized data and allocate the zero-initialized data after it
+        let mut data = Vec::with_capacity(metadata.data_segment_size());
+        data.extend_from_slice(&metadata.data);
+        data.resize(metadata.data_segment_size(), 0);
+
+        Ok(Self {
             program_counter: VirtualAddress(0),
-            code,
-        }
+            code: code.into(),
+            checked,
+            metadata,
+            data: data.into_boxed_slice(),
+            code_base: 0,
+            data_base: 0,
+        })
+    }
+
+
+    /// Make the program space and the data segment accessible through the VM memory.
+    /// The program space is read-only.
+    pub fn map(&mut self, memory: &mut Memory) {
+        self.code_base = memory.map(RegionKind::Program, self.code.as_ptr() as *mut u8, self.code.len(), false);
+        self.data_base = memory.map(RegionKind::Data, self.data.as_mut_ptr(), self.data.len(), true);
+    }
+
+
+    pub fn unmap(&self, memory: &mut Memory) {
+        memory.unmap(self.code_base);
+        memory.unmap(self.data_base);
     }
 
 
@@ -220,87 +438,233 @@ impl<'a> Program<'a> {
     }
 
 
-    pub fn fetch_instruction(&mut self) -> Option<ByteCodes> {
-        let instruction = ByteCodes::from(*self.code.get(self.program_counter.0)?);
-        self.program_counter.0 += INSTRUCTION_SIZE;
-        Some(instruction)
+    /// Return the raw instruction code at the given address, if it's entirely inside the program space.
+    pub fn opcode_at(&self, address: VirtualAddress) -> Option<Opcode> {
+        self.code.get(address.0..)
+            .and_then(|code| code.get(..INSTRUCTION_SIZE))
+            .map(|bytes| Opcode::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+
+    /// Fetch the next instruction. Return `None` if the end of the program has been reached.
+    pub fn fetch_instruction(&mut self) -> Result<Option<ByteCodes>, FaultKind> {
+
+        let pc = self.program_counter.0;
+
+        // The program counter may be anywhere after a jump, so the end of the instruction is not computed to avoid overflows
+        let Some(bytes) = self.code.get(pc..).and_then(|code| code.get(..INSTRUCTION_SIZE)) else {
+            if self.checked && pc > self.code.len() {
+                return Err(FaultKind::ProgramCounterOutOfRange);
+            }
+            if self.checked && pc < self.code.len() {
+                // Only part of the instruction code is inside the program space
+                return Err(FaultKind::UnknownOpcode);
+            }
+            return Ok(None);
+        };
+
+        let code = Opcode::from_le_bytes(bytes.try_into().unwrap());
+
+        let instruction = if self.checked {
+            ByteCodes::from_code(code).ok_or(FaultKind::UnknownOpcode)?
+        } else {
+            ByteCodes::from(code)
+        };
+
+        self.program_counter.0 = pc + INSTRUCTION_SIZE;
+        Ok(Some(instruction))
+    }
+
+
+    /// Check that an operand of `size` bytes can be fetched at the program counter.
+    #[inline]
+    fn check_operand(&self, size: usize) -> Result<(), FaultKind> {
+        if self.checked && size > self.code.len().saturating_sub(self.program_counter.0) {
+            Err(FaultKind::TruncatedOperand)
+        } else {
+            Ok(())
+        }
+    }
+
+
+    /// Check that `size` bytes of static data can be read at `address`.
+    #[inline]
+    fn check_static(&self, address: VirtualAddress, size: usize) -> Result<(), FaultKind> {
+        if self.checked && address.0.checked_add(size).is_none_or(|end| end > self.code.len()) {
+            Err(FaultKind::StaticOutOfRange)
+        } else {
+            Ok(())
+        }
     }
 
 
-    pub fn fetch_1(&mut self) -> u8 {
+    pub fn fetch_1(&mut self) -> Result<u8, FaultKind> {
+        self.check_operand(mem::size_of::<u8>())?;
         let byte = self.code[self.program_counter.0];
         self.program_counter.0 += mem::size_of::<u8>();
-        byte
+        Ok(byte)
     }
 
 
-    pub fn fetch_2(&mut self) -> u16 {
+    pub fn fetch_2(&mut self) -> Result<u16, FaultKind> {
+        self.check_operand(mem::size_of::<u16>())?;
         let value = unsafe {
             ((self.code.as_ptr().add(self.program_counter.0)) as *const u16).read_unaligned()
         };
         self.program_counter.0 += mem::size_of::<u16>();
-        value
+        Ok(value)
     }
 
 
-    pub fn fetch_4(&mut self) -> u32 {
+    pub fn fetch_4(&mut self) -> Result<u32, FaultKind> {
+        self.check_operand(mem::size_of::<u32>())?;
         let value = unsafe {
             (self.code.as_ptr().add(self.program_counter.0) as *const u32).read_unaligned()
         };
         self.program_counter.0 += mem::size_of::<u32>();
-        value
+        Ok(value)
     }
 
 
-    pub fn fetch_8(&mut self) -> u64 {
+    pub fn fetch_8(&mut self) -> Result<u64, FaultKind> {
+        self.check_operand(mem::size_of::<u64>())?;
         let value = unsafe {
             (self.code.as_ptr().add(self.program_counter.0) as *const u64).read_unaligned()
         };
         self.program_counter.0 += mem::size_of::<u64>();
-        value
+        Ok(value)
+    }
+
+
+    pub fn fetch_bytes(&mut self, count: usize) -> Result<&[u8], FaultKind> {
+        self.check_operand(count)?;
+        let start = self.program_counter.0;
+        let bytes = self.code.get(start..).and_then(|code| code.get(..count)).ok_or(FaultKind::TruncatedOperand)?;
+        self.program_counter.0 = start + count;
+        Ok(bytes)
     }
 
 
-    pub fn fetch_bytes(&mut self, count: usize) -> &[u8] {
-        let bytes = &self.code[self.program_counter.0..self.program_counter.0 + count];
-        self.program_counter.0 += count;
-        bytes
+    /// Check that `size` bytes of the data segment can be accessed at `address`.
+    #[inline]
+    fn check_data(&self, address: VirtualAddress, size: usize) -> Result<(), FaultKind> {
+        if self.checked && address.0.checked_add(size).is_none_or(|end| end > self.data.len()) {
+            Err(FaultKind::DataOutOfRange)
+        } else {
+            Ok(())
+        }
     }
 
 
-    pub fn get_static1(&self, address: VirtualAddress) -> u8 {
-        self.code[address.0]
+    pub fn get_static1(&self, address: VirtualAddress) -> Result<u8, FaultKind> {
+        self.check_static(address, mem::size_of::<u8>())?;
+        Ok(self.code[address.0])
     }
 
 
-    pub fn get_static2(&self, address: VirtualAddress) -> u16 {
+    pub fn get_static2(&self, address: VirtualAddress) -> Result<u16, FaultKind> {
+        self.check_static(address, mem::size_of::<u16>())?;
         unsafe {
-            (self.code[address.0..].as_ptr() as *const u16).read_unaligned()
+            Ok((self.code[address.0..].as_ptr() as *const u16).read_unaligned())
         }
     }
 
 
-    pub fn get_static4(&self, address: VirtualAddress) -> u32 {
+    pub fn get_static4(&self, address: VirtualAddress) -> Result<u32, FaultKind> {
+        self.check_static(address, mem::size_of::<u32>())?;
         unsafe {
-            (self.code[address.0..].as_ptr() as *const u32).read_unaligned()
+            Ok((self.code[address.0..].as_ptr() as *const u32).read_unaligned())
         }
     }
 
 
-    pub fn get_static8(&self, address: VirtualAddress) -> u64 {
+    pub fn get_static8(&self, address: VirtualAddress) -> Result<u64, FaultKind> {
+        self.check_static(address, mem::size_of::<u64>())?;
         unsafe {
-            (self.code[address.0..].as_ptr() as *const u64).read_unaligned()
+            Ok((self.code[address.0..].as_ptr() as *const u64).read_unaligned())
         }
     }
 
 
-    pub fn get_static_bytes(&self, address: VirtualAddress, size: usize) -> &[u8] {
-        &self.code[address.0..address.0 + size]
+    pub fn get_static_bytes(&self, address: VirtualAddress, size: usize) -> Result<&[u8], FaultKind> {
+        self.check_static(address, size)?;
+        Ok(&self.code[address.0..address.0 + size])
     }
 
 
+    /// Convert a virtual address in the program space to an address in the VM memory.
     pub fn virtual_to_real(&self, vaddress: VirtualAddress) -> Address {
-        vaddress.0 + self.code.as_ptr() as Address
+        vaddress.0 + self.code_base
+    }
+
+
+    pub fn get_data1(&self, address: VirtualAddress) -> Result<u8, FaultKind> {
+        self.check_data(address, mem::size_of::<u8>())?;
+        Ok(self.data[address.0])
+    }
+
+
+    pub fn get_data2(&self, address: VirtualAddress) -> Result<u16, FaultKind> {
+        self.check_data(address, mem::size_of::<u16>())?;
+        unsafe {
+            Ok((self.data[address.0..].as_ptr() as *const u16).read_unaligned())
+        }
+    }
+
+
+    pub fn get_data4(&self, address: VirtualAddress) -> Result<u32, FaultKind> {
+        self.check_data(address, mem::size_of::<u32>())?;
+        unsafe {
+            Ok((self.data[address.0..].as_ptr() as *const u32).read_unaligned())
+        }
+    }
+
+
+    pub fn get_data8(&self, address: VirtualAddress) -> Result<u64, FaultKind> {
+        self.check_data(address, mem::size_of::<u64>())?;
+        unsafe {
+            Ok((self.data[address.0..].as_ptr() as *const u64).read_unaligned())
+        }
+    }
+
+
+    pub fn set_data1(&mut self, address: VirtualAddress, value: u8) -> Result<(), FaultKind> {
+        self.check_data(address, mem::size_of::<u8>())?;
+        self.data[address.0] = value;
+        Ok(())
+    }
+
+
+    pub fn set_data2(&mut self, address: VirtualAddress, value: u16) -> Result<(), FaultKind> {
+        self.check_data(address, mem::size_of::<u16>())?;
+        unsafe {
+            (self.data[address.0..].as_mut_ptr() as *mut u16).write_unaligned(value);
+        }
+        Ok(())
+    }
+
+
+    pub fn set_data4(&mut self, address: VirtualAddress, value: u32) -> Result<(), FaultKind> {
+        self.check_data(address, mem::size_of::<u32>())?;
+        unsafe {
+            (self.data[address.0..].as_mut_ptr() as *mut u32).write_unaligned(value);
+        }
+        Ok(())
+    }
+
+
+    pub fn set_data8(&mut self, address: VirtualAddress, value: u64) -> Result<(), FaultKind> {
+        self.check_data(address, mem::size_of::<u64>())?;
+        unsafe {
+            (self.data[address.0..].as_mut_ptr() as *mut u64).write_unaligned(value);
+        }
+        Ok(())
+    }
+
+
+    /// Convert a virtual address in the data segment to an address in the VM memory.
+    pub fn data_virtual_to_real(&self, vaddress: VirtualAddress) -> Address {
+        vaddress.0 + self.data_base
     }
 
 
@@ -312,720 +676,3240 @@ impl<'a> Program<'a> {
 }
 
 
+/// Selects whether the VM validates the program while executing it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExecutionMode {
+    /// Stack bounds, program counter, operands, instruction codes, interrupt codes, and error codes are checked.
+    /// Invalid programs are stopped with a `VmFault`.
+    #[default]
+    Checked,
+    /// No runtime checks are performed. Faster, but an invalid program results in undefined behavior.
+    Unchecked,
+}
+
+
+/// How a program terminated, when it was not stopped by a fault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitStatus {
+    /// The program executed `exit` with the given code.
+    Exited(ErrorCodes),
+    /// The program ran past its last instruction. Holds the VM error code at that point.
+    Finished(ErrorCodes),
+}
+
+impl ExitStatus {
+
+    /// The exit code of the program.
+    pub fn code(&self) -> ErrorCodes {
+        match self {
+            ExitStatus::Exited(code) | ExitStatus::Finished(code) => *code
+        }
+    }
+
+
+    pub fn success(&self) -> bool {
+        self.code() == ErrorCodes::NoError
+    }
+
+}
+
+
+/// An active function call.
+#[derive(Debug, Clone, Copy)]
+struct CallFrame {
+    /// Address of the call instruction.
+    call_site: VirtualAddress,
+    /// Address of the instruction to return to.
+    return_address: VirtualAddress,
+}
+
+
+/// A virtual machine that runs Stack VM byte code. Use `VM::builder` to configure a new instance.
 pub struct VM {
 
     /// Operation stack. Stores the operands and results of operations.
-    opstack: Stack,
+    pub(crate) opstack: Stack,
+    /// Return addresses of the active function calls. Separate from the operation stack so that
+    /// unbalanced pushes and pops cannot corrupt them.
+    call_stack: Vec<CallFrame>,
+    /// Maximum number of nested calls.
+    max_call_depth: usize,
+    /// Memory accessible through pointers: the operation stack, the program space, the data segment, and the heap blocks allocated by the program.
+    memory: Memory,
+    /// Address of the bottom of the operation stack in the VM memory.
+    stack_base: Address,
+    /// Shadow metadata of the heap blocks, present in sanitizer mode.
+    sanitizer: Option<Sanitizer>,
+    /// Resource limits enforced while running a program.
+    limits: Limits,
+    /// Time at which the current run must be stopped, if it has a timeout.
+    deadline: Option<Instant>,
+    /// The program loaded by the last call to `load` or `run`.
+    program: Option<Program>,
+    /// Rust functions callable by the program with `hostcall`.
+    host_functions: HostFunctions,
+    /// Number of bytes written by the printing interrupts during the current run.
+    output_bytes: usize,
+    /// Console input read by the input interrupts.
+    input: BufReader<Box<dyn Read>>,
+    /// Console output written by the printing interrupts. It's flushed by the flush interrupt and at the end of every run.
+    output: BufWriter<Box<dyn Write>>,
+    /// Files and directories opened by the loaded program.
+    descriptors: Descriptors,
+    /// Arguments read by the argument interrupts.
+    args: Vec<String>,
+    /// Environment variables read by the environment interrupt.
+    env: HashMap<String, String>,
+    /// Whether the program may spawn host processes.
+    allow_spawn: bool,
+    /// Origin of the monotonic clock interrupt.
+    clock_origin: Instant,
+    /// Generator of the random interrupt. Its state carries over between runs.
+    random: Random,
+    /// Stack depth of the current function frame set by `enter`. Locals are located above it, the saved
+    /// frame pointer and the arguments below it.
+    frame_pointer: usize,
     /// Stores the last error code.
-    error_code: ErrorCodes
+    pub(crate) error_code: ErrorCodes,
+    /// Whether runtime safety checks are enabled.
+    mode: ExecutionMode,
 
 }
 
 // 1 KB should be enough for the operation stack since it stores temporary values (operands and results) 
 // which should not be too large anyway. When processing big chunks of data, we usually use pointers to the data
 // instead of copying the whole data itself.
-const DEFAULT_OPSTACK_SIZE: usize = 1024; // 1 KB
+pub(crate) const DEFAULT_OPSTACK_SIZE: usize = 1024; // 1 KB
+
+// Deep enough for reasonably recursive programs, while still catching runaway recursion quickly.
+pub(crate) const DEFAULT_CALL_STACK_DEPTH: usize = 1024;
+
+// Number of executed instructions between two checks of the timeout.
+const TIMEOUT_CHECK_INTERVAL: u64 = 1024;
+
+// Same as the default buffer size of the standard library.
+const CONSOLE_BUFFER_SIZE: usize = 8 * 1024;
 
 impl VM {
 
-    /// Instantiate a new VM with a given stack size.
-    pub fn new(opstack_size: Option<usize>) -> Self {
+    /// Return a builder to configure a new VM.
+    pub fn builder() -> VmBuilder {
+        VmBuilder::default()
+    }
+
+
+    /// Instantiate a new VM with the configuration of the given builder.
+    pub(crate) fn new(config: VmBuilder) -> Self {
+
+        let checked = config.mode == ExecutionMode::Checked;
+        // A zero capacity disables buffering, since reads and writes larger than the buffer bypass it
+        let buffer_capacity = if config.buffered { CONSOLE_BUFFER_SIZE } else { 0 };
+        let input = config.input.unwrap_or_else(|| Box::new(io::stdin()));
+        let output = config.output.unwrap_or_else(|| Box::new(io::stdout()));
+        let mut opstack = Stack::new(config.opstack_size, checked, config.sanitize);
+        let mut memory = Memory::new(config.memory_model);
+        // The stack buffer is owned by the VM, so its region stays mapped for the lifetime of the VM
+        let stack_base = memory.map(RegionKind::Stack, opstack._stack.as_mut_ptr(), opstack._stack.len(), true);
+
         Self {
-            opstack: Stack::new(opstack_size.unwrap_or(DEFAULT_OPSTACK_SIZE)),
-            error_code: ErrorCodes::NoError
+            opstack,
+            call_stack: Vec::new(),
+            max_call_depth: config.call_stack_depth,
+            memory,
+            stack_base,
+            sanitizer: config.sanitize.then(Sanitizer::default),
+            limits: config.limits,
+            deadline: None,
+            program: None,
+            host_functions: config.host_functions,
+            output_bytes: 0,
+            input: BufReader::with_capacity(buffer_capacity, input),
+            output: BufWriter::with_capacity(buffer_capacity, output),
+            descriptors: Descriptors::default(),
+            args: config.args,
+            env: config.env,
+            allow_spawn: config.allow_spawn,
+            clock_origin: Instant::now(),
+            random: config.seed.map_or_else(Random::from_entropy, Random::new),
+            frame_pointer: 0,
+            error_code: ErrorCodes::NoError,
+            mode: config.mode
         }
     }
 
 
-    pub fn run(&mut self, code: ByteCode<'_>) -> ErrorCodes {
+    /// Load a program, replacing the previously loaded one, without running it.
+    /// The functions exported by the program can then be called with `call`.
+    pub fn load(&mut self, code: ByteCode<'_>) -> Result<(), VmFault> {
 
-        let mut program = Program::new(code);
+        let mut program = Program::new(code, self.mode == ExecutionMode::Checked)
+            .map_err(|kind| VmFault { kind, pc: VirtualAddress(0), opcode: None, backtrace: Backtrace::default() })?;
 
-        while let Some(instruction) = program.fetch_instruction() {
+        // The program regions and open files must not outlive the program, so they are released with it
+        if let Some(old_program) = self.program.take() {
+            old_program.unmap(&mut self.memory);
+        }
+        self.descriptors.clear();
+        program.map(&mut self.memory);
+        self.program = Some(program);
 
-            // This match statement will be implemented through an efficient jump table by the compiler. 
-            // There's no need to implement a jump table manually.
-            match instruction {
+        Ok(())
+    }
 
-                ByteCodes::AddInt1 => {
-                    let a = self.opstack.pop_1() as i8;
-                    let b = self.opstack.pop_1() as i8;
-                    self.opstack.push_1(a.wrapping_add(b) as u8);
-                },
-                ByteCodes::AddInt2 => {
-                    let a = self.opstack.pop_2() as i16;
-                    let b = self.opstack.pop_2() as i16;
-                    self.opstack.push_2(a.wrapping_add(b) as u16);
-                },
-                ByteCodes::AddInt4 => {
-                    let a = self.opstack.pop_4() as i32;
-                    let b = self.opstack.pop_4() as i32;
-                    self.opstack.push_4(a.wrapping_add(b) as u32);
-                },
-                ByteCodes::AddInt8 => {
-                    let a = self.opstack.pop_8() as i64;
-                    let b = self.opstack.pop_8() as i64;
-                    self.opstack.push_8(a.wrapping_add(b) as u64);
-                },
-                ByteCodes::SubInt1 => {
-                    let b = self.opstack.pop_1() as i8;
-                    let a = self.opstack.pop_1() as i8;
-                    self.opstack.push_1(a.wrapping_sub(b) as u8);
-                },
-                ByteCodes::SubInt2 => {
-                    let b = self.opstack.pop_2() as i16;
-                    let a = self.opstack.pop_2() as i16;
-                    self.opstack.push_2(a.wrapping_sub(b) as u16);
-                },
-                ByteCodes::SubInt4 => {
-                    let b = self.opstack.pop_4() as i32;
-                    let a = self.opstack.pop_4() as i32;
-                    self.opstack.push_4(a.wrapping_sub(b) as u32);
-                },
-                ByteCodes::SubInt8 => {
-                    let b = self.opstack.pop_8() as i64;
-                    let a = self.opstack.pop_8() as i64;
-                    self.opstack.push_8(a.wrapping_sub(b) as u64);
-                },
-                ByteCodes::MulInt1 => {
-                    let a = self.opstack.pop_1() as i8;
-                    let b = self.opstack.pop_1() as i8;
-                    self.opstack.push_1(a.wrapping_mul(b) as u8);
-                },
-                ByteCodes::MulInt2 => {
-                    let a = self.opstack.pop_2() as i16;
-                    let b = self.opstack.pop_2() as i16;
-                    self.opstack.push_2(a.wrapping_mul(b) as u16);
-                },
-                ByteCodes::MulInt4 => {
-                    let a = self.opstack.pop_4() as i32;
-                    let b = self.opstack.pop_4() as i32;
-                    self.opstack.push_4(a.wrapping_mul(b) as u32);
-                },
-                ByteCodes::MulInt8 => {
-                    let a = self.opstack.pop_8() as i64;
-                    let b = self.opstack.pop_8() as i64;
-                    self.opstack.push_8(a.wrapping_mul(b) as u64);
-                },
-                ByteCodes::DivInt1 => {
-                    let b = self.opstack.pop_1() as i8;
-                    let a = self.opstack.pop_1() as i8;
-                    self.opstack.push_1(a.wrapping_div(b) as u8);
-                },
-                ByteCodes::DivInt2 => {
-                    let b = self.opstack.pop_2() as i16;
-                    let a = self.opstack.pop_2() as i16;
-                    self.opstack.push_2(a.wrapping_div(b) as u16);
-                },
-                ByteCodes::DivInt4 => {
-                    let b = self.opstack.pop_4() as i32;
-                    let a = self.opstack.pop_4() as i32;
-                    self.opstack.push_4(a.wrapping_div(b) as u32);
-                },
-                ByteCodes::DivInt8 => {
-                    let b = self.opstack.pop_8() as i64;
-                    let a = self.opstack.pop_8() as i64;
-                    self.opstack.push_8(a.wrapping_div(b) as u64);
-                },
-                ByteCodes::ModInt1 => {
-                    let b = self.opstack.pop_1() as i8;
-                    let a = self.opstack.pop_1() as i8;
-                    self.opstack.push_1(a.wrapping_rem(b) as u8);
-                },
-                ByteCodes::ModInt2 => {
-                    let b = self.opstack.pop_2() as i16;
-                    let a = self.opstack.pop_2() as i16;
-                    self.opstack.push_2(a.wrapping_rem(b) as u16);
-                },
-                ByteCodes::ModInt4 => {
-                    let b = self.opstack.pop_4() as i32;
-                    let a = self.opstack.pop_4() as i32;
-                    self.opstack.push_4(a.wrapping_rem(b) as u32);
-                },
-                ByteCodes::ModInt8 => {
-                    let b = self.opstack.pop_8() as i64;
-                    let a = self.opstack.pop_8() as i64;
-                    self.opstack.push_8(a.wrapping_rem(b) as u64);
-                },
 
-                ByteCodes::AddFloat4 => {
-                    let a = self.opstack.pop_4() as f32;
-                    let b = self.opstack.pop_4() as f32;
-                    self.opstack.push_4((a + b) as u32);
-                },
-                ByteCodes::AddFloat8 => {
-                    let a = self.opstack.pop_8() as f64;
-                    let b = self.opstack.pop_8() as f64;
-                    self.opstack.push_8((a + b) as u64);
-                },
-                ByteCodes::SubFloat4 => {
-                    let b = self.opstack.pop_4() as f32;
-                    let a = self.opstack.pop_4() as f32;
-                    self.opstack.push_4((a - b) as u32);
-                },
-                ByteCodes::SubFloat8 => {
-                    let b = self.opstack.pop_8() as f64;
-                    let a = self.opstack.pop_8() as f64;
-                    self.opstack.push_8((a - b) as u64);
-                },
-                ByteCodes::MulFloat4 => {
-                    let a = self.opstack.pop_4() as f32;
-                    let b = self.opstack.pop_4() as f32;
-                    self.opstack.push_4((a * b) as u32);
-                },
-                ByteCodes::MulFloat8 => {
-                    let a = self.opstack.pop_8() as f64;
-                    let b = self.opstack.pop_8() as f64;
-                    self.opstack.push_8((a * b) as u64);
-                },
-                ByteCodes::DivFloat4 => {
-                    let b = self.opstack.pop_4() as f32;
-                    let a = self.opstack.pop_4() as f32;
-                    self.opstack.push_4((a / b) as u32);
-                },
-                ByteCodes::DivFloat8 => {
-                    let b = self.opstack.pop_8() as f64;
-                    let a = self.opstack.pop_8() as f64;
-                    self.opstack.push_8((a / b) as u64);
-                },
-                ByteCodes::ModFloat4 => {
-                    let b = self.opstack.pop_4() as f32;
-                    let a = self.opstack.pop_4() as f32;
-                    self.opstack.push_4((a % b) as u32);
-                },
-                ByteCodes::ModFloat8 => {
-                    let b = self.opstack.pop_8() as f64;
-                    let a = self.opstack.pop_8() as f64;
-                    self.opstack.push_8((a % b) as u64);
-                },
+    /// Load a program and run it until it exits, runs past its last instruction, or is stopped by a fault.
+    /// The program stays loaded afterwards, so that its exported functions can be called with `call`.
+    pub fn run(&mut self, code: ByteCode<'_>) -> Result<ExitStatus, VmFault> {
 
-                ByteCodes::Memmove1 => {
-                    let dest = self.opstack.pop_8() as *mut u8;
-                    let src = self.opstack.pop_8() as *const u8;
-                    unsafe {
-                        dest.write(*src);
-                    }
-                },
-                ByteCodes::Memmove2 => {
-                    let dest = self.opstack.pop_8() as *mut u16;
-                    let src = self.opstack.pop_8() as *const u16;
-                    unsafe {
-                        dest.write(*src)
-                    }
-                },
-                ByteCodes::Memmove4 => {
-                    let dest = self.opstack.pop_8() as *mut u32;
-                    let src = self.opstack.pop_8() as *const u32;
-                    unsafe {
-                        dest.write(*src);
-                    }
-                },
-                ByteCodes::Memmove8 => {
-                    let dest = self.opstack.pop_8() as *mut u64;
-                    let src = self.opstack.pop_8() as *const u64;
-                    unsafe {
-                        dest.write(*src);
-                    }
-                },
-                ByteCodes::MemmoveBytes => {
-                    let dest = self.opstack.pop_8() as *mut u8;
-                    let src = self.opstack.pop_8() as *const u8;
-                    let count = self.opstack.pop_8() as usize;
-                    unsafe {
-                        // Assume the memory regions don't overlap.
-                        dest.copy_from_nonoverlapping(src, count);
-                    }
-                },
+        self.load(code)?;
 
-                ByteCodes::VirtualConstToReal => {
-                    let vsrc = VirtualAddress(program.fetch_8() as Address);
-                    self.opstack.push_8(program.virtual_to_real(vsrc) as u64);
-                },
-                ByteCodes::VirtualToReal => {
-                    let vsrc = VirtualAddress(self.opstack.pop_8() as Address);
-                    self.opstack.push_8(program.virtual_to_real(vsrc) as u64);
-                }
+        self.with_program(|vm, program| {
+            vm.reset_run_state();
+            vm.run_program(program)
+        })
+    }
 
-                ByteCodes::LoadStatic1 => {
-                    let vsrc = VirtualAddress(program.fetch_8() as Address);
-                    self.opstack.push_1(program.get_static1(vsrc));
-                },
-                ByteCodes::LoadStatic2 => {
-                    let vsrc = VirtualAddress(program.fetch_8() as Address);
-                    self.opstack.push_2(program.get_static2(vsrc));
-                },
-                ByteCodes::LoadStatic4 => {
-                    let vsrc = VirtualAddress(program.fetch_8() as Address);
-                    self.opstack.push_4(program.get_static4(vsrc));
-                },
-                ByteCodes::LoadStatic8 => {
-                    let vsrc = VirtualAddress(program.fetch_8() as Address);
-                    self.opstack.push_8(program.get_static8(vsrc));
-                },
-                ByteCodes::LoadStaticBytes => {
-                    let vsrc = VirtualAddress(program.fetch_8() as Address);
-                    let count = program.fetch_8() as usize;
-                    self.opstack.push_bytes(program.get_static_bytes(vsrc, count));
-                },
 
-                ByteCodes::PopConst => {
-                    let count = program.fetch_8() as usize;
-                    self.opstack.pop_by(count);
-                },
-                ByteCodes::PopBytes => {
-                    let count = self.opstack.pop_8() as usize;
-                    self.opstack.pop_by(count);
-                },
+    /// Call the function exported by the loaded program under `name`, and return the bytes it leaves on the operation stack.
+    /// The arguments are pushed in order before the call, so the last argument is on top of the stack.
+    /// The function is run until it returns to the host. It must consume its arguments, and its results are returned in memory order,
+    /// so a single result is returned as its little-endian bytes.
+    pub fn call(&mut self, name: &str, args: &[&[u8]]) -> Result<Vec<u8>, VmFault> {
 
-                ByteCodes::Load1 => {
-                    let src = self.opstack.pop_8() as *const u8;
-                    self.opstack.push_1(unsafe { *src });
-                },
-                ByteCodes::Load2 => {
-                    let src = self.opstack.pop_8() as *const u8;
-                    self.opstack.push_2(unsafe { *(src as *const u16) });
-                },
-                ByteCodes::Load4 => {
-                    let src = self.opstack.pop_8() as *const u8;
-                    self.opstack.push_4(unsafe { *(src as *const u32) });
-                },
-                ByteCodes::Load8 => {
-                    let src = self.opstack.pop_8() as *const u8;
-                    self.opstack.push_8(unsafe { *(src as *const u64) });
-                },
-                ByteCodes::LoadBytes => {
-                    let src = self.opstack.pop_8() as *const u8;
-                    let count = self.opstack.pop_8() as usize;
-                    self.opstack.push_from(src, count);
-                },
-                
-                ByteCodes::LoadConst1 => {
-                    self.opstack.push_1(program.fetch_1());
-                },
-                ByteCodes::LoadConst2 => {
-                    self.opstack.push_2(program.fetch_2());
-                },
-                ByteCodes::LoadConst4 => {
-                    self.opstack.push_4(program.fetch_4());
-                },
-                ByteCodes::LoadConst8 => {
-                    self.opstack.push_8(program.fetch_8());
-                },
-                ByteCodes::LoadConstBytes => {
-                    let count = program.fetch_8() as usize;
-                    self.opstack.push_bytes(program.fetch_bytes(count));
-                },
+        if self.program.is_none() {
+            return Err(VmFault { kind: FaultKind::UnknownExport, pc: VirtualAddress(0), opcode: None, backtrace: Backtrace::default() });
+        }
 
-                ByteCodes::LoadProgramCounter => {
-                    self.opstack.push_8(program.program_counter().0 as u64);
-                },
-                ByteCodes::LoadStackPointer => {
-                    self.opstack.push_8(unsafe { self.opstack.tos() } as u64);
-                },
-                ByteCodes::LoadStackSize => {
-                    self.opstack.push_8(self.opstack._stack.len() as u64);
-                },
-                ByteCodes::LoadStackBottom => {
-                    self.opstack.push_8(self.opstack._stack.as_ptr() as u64);
-                },
+        self.with_program(|vm, program| {
+            vm.reset_run_state();
+            vm.call_program(program, name, args)
+        })
+    }
 
-                ByteCodes::Store1 => {
-                    let dest = self.opstack.pop_8() as *mut u8;
-                    unsafe {
-                        dest.write(self.opstack.pop_1());
-                    }
-                },
-                ByteCodes::Store2 => {
-                    let dest = self.opstack.pop_8() as *mut u16;
-                    unsafe {
-                        dest.write(self.opstack.pop_2());
-                    }
-                },
-                ByteCodes::Store4 => {
-                    let dest = self.opstack.pop_8() as *mut u32;
-                    unsafe {
-                        dest.write(self.opstack.pop_4());
-                    }
-                },
-                ByteCodes::Store8 => {
-                    let dest = self.opstack.pop_8() as *mut u64;
-                    unsafe {
-                        dest.write(self.opstack.pop_8());
-                    }
-                },
-                ByteCodes::StoreBytes => {
-                    let dest = self.opstack.pop_8() as *mut u8;
-                    let count = self.opstack.pop_8() as usize;
-                    unsafe {
-                        // Note that the stack and whatever memory is being written to must not overlap. It's the programmer's responsibility to ensure this.
-                        dest.copy_from_nonoverlapping(
-                            self.opstack.pop_bytes(count).as_ptr(), 
-                            count
-                        );
-                    }
-                },
 
-                ByteCodes::Malloc => {
-                    let size = self.opstack.pop_8() as usize;
-                    let addr = unsafe {
-                        match alloc::Layout::array::<u8>(size) {
-                            Ok(layout) => {
-                                alloc::alloc(layout)
-                            },
-                            Err(_) => {
-                                ptr::null_mut()
-                            }
-                        }
-                    };
-                    self.opstack.push_8(addr as u64);
-                },
-                ByteCodes::Realloc => {
-                    let addr = self.opstack.pop_8() as *mut u8;
-                    let new_size = self.opstack.pop_8() as usize;
-                    let new_addr = unsafe {
-                        match alloc::Layout::array::<u8>(new_size) {
-                            Ok(layout) => {
-                                alloc::realloc(addr, layout, new_size)
-                            },
-                            Err(_) => {
-                                ptr::null_mut()
-                            }
-                        }
-                    };
-                    self.opstack.push_8(new_addr as u64);
-                },
-                ByteCodes::Free => {
-                    let addr = self.opstack.pop_8() as *mut u8;
-                    unsafe {
-                        alloc::dealloc(addr, alloc::Layout::new::<u8>());
-                    }
-                },
+    /// Run `f` with the loaded program, which is taken out of the VM so that both can be borrowed mutably.
+    /// The output is flushed afterwards, whatever the outcome of the execution.
+    fn with_program<T>(&mut self, f: impl FnOnce(&mut Self, &mut Program) -> Result<T, VmFault>) -> Result<T, VmFault> {
 
-                ByteCodes::Exit => {
-                    let exit_code = self.opstack.pop_4() as i32;
-                    return ErrorCodes::from(exit_code);
-                },
+        let mut program = self.program.take().expect("A program should be loaded");
+        let result = f(self, &mut program);
+        self.program = Some(program);
 
-                ByteCodes::Intr => {
-                    let intr_code = Interrupts::from(self.opstack.pop_1());
-                    self.handle_interrupt(intr_code, &mut program);
-                },
+        // The output must be visible to the host as soon as the run ends, even if the program faulted.
+        // There is no error code to report a failure to after the run, so flush errors are ignored.
+        let _ = self.output.flush();
 
-                ByteCodes::IntrConst => {
-                    let intr_code = Interrupts::from(program.fetch_1());
-                    self.handle_interrupt(intr_code, &mut program);
-                },
+        result
+    }
 
-                ByteCodes::ReadError => {
-                    self.opstack.push_4(self.error_code as u32);
-                },
 
-                ByteCodes::SetErrorConst => {
-                    let error_code = program.fetch_4() as i32;
-                    self.error_code = ErrorCodes::from(error_code);
-                },
+    fn reset_run_state(&mut self) {
+        self.call_stack.clear();
+        self.frame_pointer = 0;
+        self.output_bytes = 0;
+    }
 
-                ByteCodes::SetError => {
-                    let error_code = self.opstack.pop_4() as i32;
-                    self.error_code = ErrorCodes::from(error_code);
-                },             
 
-                ByteCodes::Duplicate1 => {
-                    self.opstack.push_1(
-                        self.opstack.peek_1()
-                    );
-                },
+    fn call_program(&mut self, program: &mut Program, name: &str, args: &[&[u8]]) -> Result<Vec<u8>, VmFault> {
 
-                ByteCodes::Duplicate2 => {
-                    self.opstack.push_2(
-                        self.opstack.peek_2()
-                    );
-                },
+        let Some(entry) = program.metadata.export(name) else {
+            return Err(VmFault { kind: FaultKind::UnknownExport, pc: VirtualAddress(0), opcode: None, backtrace: Backtrace::default() });
+        };
 
-                ByteCodes::Duplicate4 => {
-                    self.opstack.push_4(
-                        self.opstack.peek_4()
-                    );
-                },
+        let base_depth = self.opstack.depth();
 
-                ByteCodes::Duplicate8 => {
-                    self.opstack.push_8(
-                        self.opstack.peek_8()
-                    );
-                },
+        for arg in args {
+            self.opstack.push_bytes(arg)
+                .map_err(|kind| self.fault(kind, entry, None, program))?;
+        }
 
-                ByteCodes::DuplicateBytes => {
-                    let count = self.opstack.pop_8() as usize;
-                    let bytes = unsafe { std::slice::from_raw_parts(self.opstack.tos(), count) };
-                    self.opstack.push_bytes(bytes);
-                },
+        // Returning from the function jumps past the end of the program, which ends the run
+        self.push_call_frame(entry, VirtualAddress(program.code.len()))
+            .map_err(|kind| self.fault(kind, entry, None, program))?;
+        program.jump_to(entry);
+
+        let status = self.run_program(program)?;
+        let pc = program.program_counter();
+
+        if let ExitStatus::Exited(code) = status {
+            return Err(self.fault(FaultKind::ExitDuringCall(code), pc, None, program));
+        }
+        if !self.call_stack.is_empty() {
+            return Err(self.fault(FaultKind::MissingReturn, pc, None, program));
+        }
+
+        let result_size = self.opstack.depth().checked_sub(base_depth)
+            .ok_or_else(|| self.fault(FaultKind::StackUnderflow, pc, None, program))?;
+
+        self.opstack.pop_bytes(result_size)
+            .map(|bytes| bytes.to_vec())
+            .map_err(|kind| self.fault(kind, pc, None, program))
+    }
+
+
+    fn run_program(&mut self, program: &mut Program) -> Result<ExitStatus, VmFault> {
+
+        self.deadline = self.limits.timeout.map(|timeout| Instant::now() + timeout);
+        let mut executed_instructions: u64 = 0;
+
+        loop {
+
+            let pc = program.program_counter();
+
+            if let Err(kind) = self.check_run_limits(executed_instructions) {
+                return Err(self.fault(kind, pc, program.opcode_at(pc), program));
+            }
+            executed_instructions += 1;
+
+            let instruction = match program.fetch_instruction() {
+                Ok(Some(instruction)) => instruction,
+                Ok(None) => break,
+                Err(kind) => return Err(self.fault(kind, pc, program.opcode_at(pc), program))
+            };
+
+            match self.execute(instruction, program) {
+                Ok(None) => {},
+                Ok(Some(exit_code)) => return Ok(ExitStatus::Exited(exit_code)),
+                Err(kind) => return Err(self.fault(kind, pc, Some(instruction as Opcode), program))
+            }
+        }
+
+        // The program has no more instruction to execute and an exit code was not provided.
+        // Assume the program ended successfully.
+        Ok(ExitStatus::Finished(self.error_code))
+    }
+
+
+    /// Check the instruction and time limits before executing an instruction.
+    fn check_run_limits(&self, executed_instructions: u64) -> Result<(), FaultKind> {
+
+        if self.limits.max_instructions.is_some_and(|max| executed_instructions >= max) {
+            return Err(FaultKind::LimitExceeded(Limit::Instructions));
+        }
+
+        // Reading the clock is slow compared to executing an instruction, so the deadline is only checked periodically
+        if executed_instructions.is_multiple_of(TIMEOUT_CHECK_INTERVAL) && self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return Err(FaultKind::LimitExceeded(Limit::Time));
+        }
+
+        Ok(())
+    }
+
+
+    /// Block the program for `duration`. A sleep that would outlast the timeout of the run is cut short and stops the program.
+    fn sleep(&self, duration: Duration) -> Result<(), FaultKind> {
+        match self.deadline {
+            Some(deadline) if Instant::now() + duration >= deadline => {
+                thread::sleep(deadline.saturating_duration_since(Instant::now()));
+                Err(FaultKind::LimitExceeded(Limit::Time))
+            },
+            _ => {
+                thread::sleep(duration);
+                Ok(())
+            }
+        }
+    }
+
+
+    /// Number of heap bytes taken by a block of `size` bytes, including its redzone in sanitizer mode.
+    fn heap_block_size(&self, size: usize) -> usize {
+        if self.sanitizer.is_some() {
+            size.saturating_add(REDZONE_SIZE)
+        } else {
+            size
+        }
+    }
+
+
+    /// Check that allocating `additional` more heap bytes doesn't exceed the heap limit.
+    fn check_heap_limit(&self, additional: usize) -> Result<(), FaultKind> {
+        if self.limits.max_heap_bytes.is_some_and(|max| self.memory.heap_bytes().saturating_add(additional) > max) {
+            Err(FaultKind::LimitExceeded(Limit::HeapBytes))
+        } else {
+            Ok(())
+        }
+    }
+
+
+    /// Return the heap blocks that were allocated by the program and never freed, sorted by address.
+    pub fn leaked_blocks(&self) -> Vec<(Address, Layout)> {
+        let mut blocks = self.memory.leaked_blocks();
+        if let Some(sanitizer) = &self.sanitizer {
+            // Don't report the redzones as part of the blocks
+            for (address, layout) in &mut blocks {
+                if let Some(size) = sanitizer.block_size(*address) {
+                    *layout = Layout::from_size_align(size, layout.align()).unwrap();
+                }
+            }
+        }
+        blocks
+    }
+
+
+    fn fault(&self, kind: FaultKind, pc: VirtualAddress, opcode: Option<Opcode>, program: &Program) -> VmFault {
+        VmFault {
+            kind,
+            pc,
+            opcode,
+            backtrace: self.backtrace(pc, program)
+        }
+    }
+
+
+    /// Build a symbolized backtrace starting from the given program counter.
+    fn backtrace(&self, pc: VirtualAddress, program: &Program) -> Backtrace {
+
+        let call_sites = self.call_stack.iter().rev().map(|frame| frame.call_site);
+
+        Backtrace(
+            iter::once(pc).chain(call_sites).map(|address| BacktraceFrame {
+                address,
+                symbol: program.metadata.symbolize(address).map(|(symbol, offset)| (symbol.name.clone(), offset))
+            }).collect()
+        )
+    }
+
+
+    fn push_call_frame(&mut self, call_site: VirtualAddress, return_address: VirtualAddress) -> Result<(), FaultKind> {
+        if self.call_stack.len() >= self.max_call_depth {
+            return Err(FaultKind::CallStackOverflow);
+        }
+        self.call_stack.push(CallFrame { call_site, return_address });
+        Ok(())
+    }
+
+
+    /// Push the address of a new heap block, or a null pointer and set the error code if the allocation failed.
+    fn push_allocation(&mut self, block: Result<Address, ErrorCodes>) -> Result<(), FaultKind> {
+        let addr = block.unwrap_or_else(|error_code| {
+            self.error_code = error_code;
+            0
+        });
+        self.opstack.push_8(addr as u64)
+    }
+
+
+    /// Translate the address of a `size`-byte read to a host pointer.
+    /// In sanitizer mode, the read is checked against the heap blocks and the initialized stack bytes.
+    pub(crate) fn translate_read(&self, address: u64, size: usize) -> Result<*const u8, FaultKind> {
+        if let Some(sanitizer) = &self.sanitizer {
+            sanitizer.check_access(address as Address, size)?;
+        }
+        let src = self.memory.translate(address as Address, size, false)?;
+        self.opstack.check_initialized(src, size)?;
+        Ok(src)
+    }
+
+
+    /// Translate the address of a `size`-byte write to a host pointer.
+    /// In sanitizer mode, the write is checked against the heap blocks and the written stack bytes become initialized.
+    pub(crate) fn translate_write(&mut self, address: u64, size: usize) -> Result<*mut u8, FaultKind> {
+        if let Some(sanitizer) = &self.sanitizer {
+            sanitizer.check_access(address as Address, size)?;
+        }
+        let dest = self.memory.translate(address as Address, size, true)?;
+        self.opstack.set_initialized(dest, size, true);
+        Ok(dest)
+    }
+
+
+    /// Pop a string operand of an I/O interrupt, such as a path, given as the address and the length of the string.
+    /// A string that is not valid UTF-8 is reported to the program with the `InvalidArgument` error code.
+    fn pop_string(&mut self) -> Result<Result<String, ErrorCodes>, FaultKind> {
+        let length = self.opstack.pop_8()? as usize;
+        let address = self.opstack.pop_8()?;
+        let bytes = unsafe {
+            slice::from_raw_parts(self.translate_read(address, length)?, length)
+        };
+        Ok(str::from_utf8(bytes).map(str::to_string).map_err(|_| ErrorCodes::InvalidArgument))
+    }
+
+
+    fn pop_path(&mut self) -> Result<Result<PathBuf, ErrorCodes>, FaultKind> {
+        Ok(self.pop_string()?.map(PathBuf::from))
+    }
+
+
+    /// Copy `bytes` to the program buffer at `address` of `size` bytes and return their length.
+    /// If they don't fit, nothing is copied and the `Overflow` error code is set, so that the program can retry with a larger buffer.
+    fn copy_to_buffer(&mut self, bytes: &[u8], address: u64, size: usize) -> Result<usize, FaultKind> {
+        if bytes.len() > size {
+            self.error_code = ErrorCodes::Overflow;
+        } else {
+            let dest = self.translate_write(address, bytes.len())?;
+            unsafe {
+                ptr::copy(bytes.as_ptr(), dest, bytes.len());
+            }
+        }
+        Ok(bytes.len())
+    }
+
+
+    /// Read the command line of a process to spawn, given as the address of an array of `count` strings.
+    /// Each string is stored as its address followed by its length. Strings that are not valid UTF-8 and arrays too large to address are reported with the `InvalidArgument` error code.
+    fn read_argv(&self, address: u64, count: usize) -> Result<Result<Vec<String>, ErrorCodes>, FaultKind> {
+        // The count comes from the program, so the vector only grows as the entries are read and checked
+        let mut argv = Vec::new();
+        for i in 0..count {
+            let Some(offset) = i.checked_mul(2 * ADDRESS_SIZE) else {
+                return Ok(Err(ErrorCodes::InvalidArgument));
+            };
+            let entry = self.translate_read(address.wrapping_add(offset as u64), 2 * ADDRESS_SIZE)?;
+            let (arg_addr, arg_length) = unsafe {
+                (ptr::read_unaligned(entry as *const u64), ptr::read_unaligned(entry.add(ADDRESS_SIZE) as *const u64) as usize)
+            };
+            let bytes = unsafe {
+                slice::from_raw_parts(self.translate_read(arg_addr, arg_length)?, arg_length)
+            };
+            match str::from_utf8(bytes) {
+                Ok(arg) => argv.push(arg.to_string()),
+                Err(_) => return Ok(Err(ErrorCodes::InvalidArgument))
+            }
+        }
+        Ok(Ok(argv))
+    }
+
+
+    /// Return the result of a host I/O operation, or set the error code and return `default` if it failed.
+    fn io_value<T>(&mut self, result: Result<T, ErrorCodes>, default: T) -> T {
+        result.unwrap_or_else(|error_code| {
+            self.error_code = error_code;
+            default
+        })
+    }
+
+
+    /// Allocate a heap block. In sanitizer mode, the block is followed by a redzone and recorded in the shadow metadata.
+    fn allocate(&mut self, size: usize, align: usize, zeroed: bool) -> Result<Address, ErrorCodes> {
+        let Some(sanitizer) = &mut self.sanitizer else {
+            return self.memory.allocate(size, align, zeroed);
+        };
+        let block = self.memory.allocate(size.saturating_add(REDZONE_SIZE), align, zeroed)?;
+        sanitizer.allocated(block, size);
+        Ok(block)
+    }
+
+
+    fn reallocate(&mut self, block: Address, new_size: usize) -> Result<Address, ErrorCodes> {
+        let Some(sanitizer) = &mut self.sanitizer else {
+            return self.memory.reallocate(block, new_size);
+        };
+        let new_block = self.memory.reallocate(block, new_size.saturating_add(REDZONE_SIZE))?;
+        sanitizer.freed(block);
+        sanitizer.allocated(new_block, new_size);
+        Ok(new_block)
+    }
+
+
+    fn free(&mut self, block: Address) -> Result<(), ErrorCodes> {
+        self.memory.free(block)?;
+        if let Some(sanitizer) = &mut self.sanitizer {
+            sanitizer.freed(block);
+        }
+        Ok(())
+    }
+
+
+    /// Stack depth of the first byte of the `size`-byte local variable at `offset` in the current frame.
+    fn local_depth(&self, offset: usize, size: usize) -> Result<usize, FaultKind> {
+        self.frame_pointer.checked_add(offset)
+            .and_then(|depth| depth.checked_add(size))
+            .ok_or(FaultKind::FrameOutOfRange)
+    }
+
+
+    /// Stack depth of the first byte of the argument at `offset` in the current frame.
+    /// Arguments are located below the caller's frame pointer saved by `enter`.
+    fn arg_depth(&self, offset: usize) -> Result<usize, FaultKind> {
+        offset.checked_add(ADDRESS_SIZE)
+            .and_then(|offset| self.frame_pointer.checked_sub(offset))
+            .ok_or(FaultKind::FrameOutOfRange)
+    }
+
+
+    /// Convert an error code, checking its validity in checked mode.
+    fn decode_error_code(&self, code: i32) -> Result<ErrorCodes, FaultKind> {
+        match self.mode {
+            ExecutionMode::Checked => ErrorCodes::from_i32(code).ok_or(FaultKind::InvalidErrorCode(code)),
+            ExecutionMode::Unchecked => Ok(ErrorCodes::from(code)),
+        }
+    }
+
+
+    /// Call the host function the program calls with the number `id`.
+    fn call_host_function(&mut self, id: u32, program: &Program) -> Result<(), FaultKind> {
+
+        let index = self.host_functions.resolve(id, &program.metadata.host_functions)
+            .ok_or(FaultKind::UnknownHostFunction(id))?;
+
+        let mut function = self.host_functions.take(index);
+
+        // Host functions always get bounds-checked access to the operation stack, whatever the execution mode
+        let checked = mem::replace(&mut self.opstack.checked, true);
+        let result = function(&mut HostContext { vm: self });
+        self.opstack.checked = checked;
+
+        self.host_functions.restore(index, function);
+        result
+    }
+
+
+    /// Convert an interrupt code, checking its validity in checked mode.
+    fn decode_interrupt(&self, code: u8) -> Result<Interrupts, FaultKind> {
+        match self.mode {
+            ExecutionMode::Checked => Interrupts::from_byte(code).ok_or(FaultKind::UnknownInterrupt(code)),
+            ExecutionMode::Unchecked => Ok(Interrupts::from(code)),
+        }
+    }
+
+
+    /// Execute a single instruction.
+    /// Return the exit code if the instruction terminated the program.
+    fn execute(&mut self, instruction: ByteCodes, program: &mut Program) -> Result<Option<ErrorCodes>, FaultKind> {
+
+        // This match statement will be implemented through an efficient jump table by the compiler. 
+        // There's no need to implement a jump table manually.
+        match instruction {
+
+            ByteCodes::AddInt1 => {
+                let a = self.opstack.pop_1()? as i8;
+                let b = self.opstack.pop_1()? as i8;
+                self.opstack.push_1(a.wrapping_add(b) as u8)?;
+            },
+            ByteCodes::AddInt2 => {
+                let a = self.opstack.pop_2()? as i16;
+                let b = self.opstack.pop_2()? as i16;
+                self.opstack.push_2(a.wrapping_add(b) as u16)?;
+            },
+            ByteCodes::AddInt4 => {
+                let a = self.opstack.pop_4()? as i32;
+                let b = self.opstack.pop_4()? as i32;
+                self.opstack.push_4(a.wrapping_add(b) as u32)?;
+            },
+            ByteCodes::AddInt8 => {
+                let a = self.opstack.pop_8()? as i64;
+                let b = self.opstack.pop_8()? as i64;
+                self.opstack.push_8(a.wrapping_add(b) as u64)?;
+            },
+            ByteCodes::SubInt1 => {
+                let b = self.opstack.pop_1()? as i8;
+                let a = self.opstack.pop_1()? as i8;
+                self.opstack.push_1(a.wrapping_sub(b) as u8)?;
+            },
+            ByteCodes::SubInt2 => {
+                let b = self.opstack.pop_2()? as i16;
+                let a = self.opstack.pop_2()? as i16;
+                self.opstack.push_2(a.wrapping_sub(b) as u16)?;
+            },
+            ByteCodes::SubInt4 => {
+                let b = self.opstack.pop_4()? as i32;
+                let a = self.opstack.pop_4()? as i32;
+                self.opstack.push_4(a.wrapping_sub(b) as u32)?;
+            },
+            ByteCodes::SubInt8 => {
+                let b = self.opstack.pop_8()? as i64;
+                let a = self.opstack.pop_8()? as i64;
+                self.opstack.push_8(a.wrapping_sub(b) as u64)?;
+            },
+            ByteCodes::MulInt1 => {
+                let a = self.opstack.pop_1()? as i8;
+                let b = self.opstack.pop_1()? as i8;
+                self.opstack.push_1(a.wrapping_mul(b) as u8)?;
+            },
+            ByteCodes::MulInt2 => {
+                let a = self.opstack.pop_2()? as i16;
+                let b = self.opstack.pop_2()? as i16;
+                self.opstack.push_2(a.wrapping_mul(b) as u16)?;
+            },
+            ByteCodes::MulInt4 => {
+                let a = self.opstack.pop_4()? as i32;
+                let b = self.opstack.pop_4()? as i32;
+                self.opstack.push_4(a.wrapping_mul(b) as u32)?;
+            },
+            ByteCodes::MulInt8 => {
+                let a = self.opstack.pop_8()? as i64;
+                let b = self.opstack.pop_8()? as i64;
+                self.opstack.push_8(a.wrapping_mul(b) as u64)?;
+            },
+            ByteCodes::DivInt1 => {
+                let b = self.opstack.pop_1()? as i8;
+                let a = self.opstack.pop_1()? as i8;
+                if b == 0 {
+                    self.error_code = ErrorCodes::DivisionByZero;
+                    self.opstack.push_1(0)?;
+                } else {
+                    self.opstack.push_1(a.wrapping_div(b) as u8)?;
+                }
+            },
+            ByteCodes::DivInt2 => {
+                let b = self.opstack.pop_2()? as i16;
+                let a = self.opstack.pop_2()? as i16;
+                if b == 0 {
+                    self.error_code = ErrorCodes::DivisionByZero;
+                    self.opstack.push_2(0)?;
+                } else {
+                    self.opstack.push_2(a.wrapping_div(b) as u16)?;
+                }
+            },
+            ByteCodes::DivInt4 => {
+                let b = self.opstack.pop_4()? as i32;
+                let a = self.opstack.pop_4()? as i32;
+                if b == 0 {
+                    self.error_code = ErrorCodes::DivisionByZero;
+                    self.opstack.push_4(0)?;
+                } else {
+                    self.opstack.push_4(a.wrapping_div(b) as u32)?;
+                }
+            },
+            ByteCodes::DivInt8 => {
+                let b = self.opstack.pop_8()? as i64;
+                let a = self.opstack.pop_8()? as i64;
+                if b == 0 {
+                    self.error_code = ErrorCodes::DivisionByZero;
+                    self.opstack.push_8(0)?;
+                } else {
+                    self.opstack.push_8(a.wrapping_div(b) as u64)?;
+                }
+            },
+            ByteCodes::ModInt1 => {
+                let b = self.opstack.pop_1()? as i8;
+                let a = self.opstack.pop_1()? as i8;
+                if b == 0 {
+                    self.error_code = ErrorCodes::DivisionByZero;
+                    self.opstack.push_1(0)?;
+                } else {
+                    self.opstack.push_1(a.wrapping_rem(b) as u8)?;
+                }
+            },
+            ByteCodes::ModInt2 => {
+                let b = self.opstack.pop_2()? as i16;
+                let a = self.opstack.pop_2()? as i16;
+                if b == 0 {
+                    self.error_code = ErrorCodes::DivisionByZero;
+                    self.opstack.push_2(0)?;
+                } else {
+                    self.opstack.push_2(a.wrapping_rem(b) as u16)?;
+                }
+            },
+            ByteCodes::ModInt4 => {
+                let b = self.opstack.pop_4()? as i32;
+                let a = self.opstack.pop_4()? as i32;
+                if b == 0 {
+                    self.error_code = ErrorCodes::DivisionByZero;
+                    self.opstack.push_4(0)?;
+                } else {
+                    self.opstack.push_4(a.wrapping_rem(b) as u32)?;
+                }
+            },
+            ByteCodes::ModInt8 => {
+                let b = self.opstack.pop_8()? as i64;
+                let a = self.opstack.pop_8()? as i64;
+                if b == 0 {
+                    self.error_code = ErrorCodes::DivisionByZero;
+                    self.opstack.push_8(0)?;
+                } else {
+                    self.opstack.push_8(a.wrapping_rem(b) as u64)?;
+                }
+            },
+
+            ByteCodes::DivUint1 => {
+                let b = self.opstack.pop_1()?;
+                let a = self.opstack.pop_1()?;
+                if b == 0 {
+                    self.error_code = ErrorCodes::DivisionByZero;
+                    self.opstack.push_1(0)?;
+                } else {
+                    self.opstack.push_1(a.wrapping_div(b))?;
+                }
+            },
+            ByteCodes::DivUint2 => {
+                let b = self.opstack.pop_2()?;
+                let a = self.opstack.pop_2()?;
+                if b == 0 {
+                    self.error_code = ErrorCodes::DivisionByZero;
+                    self.opstack.push_2(0)?;
+                } else {
+                    self.opstack.push_2(a.wrapping_div(b))?;
+                }
+            },
+            ByteCodes::DivUint4 => {
+                let b = self.opstack.pop_4()?;
+                let a = self.opstack.pop_4()?;
+                if b == 0 {
+                    self.error_code = ErrorCodes::DivisionByZero;
+                    self.opstack.push_4(0)?;
+                } else {
+                    self.opstack.push_4(a.wrapping_div(b))?;
+                }
+            },
+            ByteCodes::DivUint8 => {
+                let b = self.opstack.pop_8()?;
+                let a = self.opstack.pop_8()?;
+                if b == 0 {
+                    self.error_code = ErrorCodes::DivisionByZero;
+                    self.opstack.push_8(0)?;
+                } else {
+                    self.opstack.push_8(a.wrapping_div(b))?;
+                }
+            },
+            ByteCodes::ModUint1 => {
+                let b = self.opstack.pop_1()?;
+                let a = self.opstack.pop_1()?;
+                if b == 0 {
+                    self.error_code = ErrorCodes::DivisionByZero;
+                    self.opstack.push_1(0)?;
+                } else {
+                    self.opstack.push_1(a.wrapping_rem(b))?;
+                }
+            },
+            ByteCodes::ModUint2 => {
+                let b = self.opstack.pop_2()?;
+                let a = self.opstack.pop_2()?;
+                if b == 0 {
+                    self.error_code = ErrorCodes::DivisionByZero;
+                    self.opstack.push_2(0)?;
+                } else {
+                    self.opstack.push_2(a.wrapping_rem(b))?;
+                }
+            },
+            ByteCodes::ModUint4 => {
+                let b = self.opstack.pop_4()?;
+                let a = self.opstack.pop_4()?;
+                if b == 0 {
+                    self.error_code = ErrorCodes::DivisionByZero;
+                    self.opstack.push_4(0)?;
+                } else {
+                    self.opstack.push_4(a.wrapping_rem(b))?;
+                }
+            },
+            ByteCodes::ModUint8 => {
+                let b = self.opstack.pop_8()?;
+                let a = self.opstack.pop_8()?;
+                if b == 0 {
+                    self.error_code = ErrorCodes::DivisionByZero;
+                    self.opstack.push_8(0)?;
+                } else {
+                    self.opstack.push_8(a.wrapping_rem(b))?;
+                }
+            },
+
+            ByteCodes::CheckedAddInt1 => {
+                let b = self.opstack.pop_1()? as i8;
+                let a = self.opstack.pop_1()? as i8;
+                let (result, overflow) = a.overflowing_add(b);
+                if overflow {
+                    self.error_code = ErrorCodes::Overflow;
+                }
+                self.opstack.push_1(result as u8)?;
+            },
+            ByteCodes::CheckedAddInt2 => {
+                let b = self.opstack.pop_2()? as i16;
+                let a = self.opstack.pop_2()? as i16;
+                let (result, overflow) = a.overflowing_add(b);
+                if overflow {
+                    self.error_code = ErrorCodes::Overflow;
+                }
+                self.opstack.push_2(result as u16)?;
+            },
+            ByteCodes::CheckedAddInt4 => {
+                let b = self.opstack.pop_4()? as i32;
+                let a = self.opstack.pop_4()? as i32;
+                let (result, overflow) = a.overflowing_add(b);
+                if overflow {
+                    self.error_code = ErrorCodes::Overflow;
+                }
+                self.opstack.push_4(result as u32)?;
+            },
+            ByteCodes::CheckedAddInt8 => {
+                let b = self.opstack.pop_8()? as i64;
+                let a = self.opstack.pop_8()? as i64;
+                let (result, overflow) = a.overflowing_add(b);
+                if overflow {
+                    self.error_code = ErrorCodes::Overflow;
+                }
+                self.opstack.push_8(result as u64)?;
+            },
+            ByteCodes::CheckedAddUint1 => {
+                let b = self.opstack.pop_1()?;
+                let a = self.opstack.pop_1()?;
+                let (result, overflow) = a.overflowing_add(b);
+                if overflow {
+                    self.error_code = ErrorCodes::Overflow;
+                }
+                self.opstack.push_1(result)?;
+            },
+            ByteCodes::CheckedAddUint2 => {
+                let b = self.opstack.pop_2()?;
+                let a = self.opstack.pop_2()?;
+                let (result, overflow) = a.overflowing_add(b);
+                if overflow {
+                    self.error_code = ErrorCodes::Overflow;
+                }
+                self.opstack.push_2(result)?;
+            },
+            ByteCodes::CheckedAddUint4 => {
+                let b = self.opstack.pop_4()?;
+                let a = self.opstack.pop_4()?;
+                let (result, overflow) = a.overflowing_add(b);
+                if overflow {
+                    self.error_code = ErrorCodes::Overflow;
+                }
+                self.opstack.push_4(result)?;
+            },
+            ByteCodes::CheckedAddUint8 => {
+                let b = self.opstack.pop_8()?;
+                let a = self.opstack.pop_8()?;
+                let (result, overflow) = a.overflowing_add(b);
+                if overflow {
+                    self.error_code = ErrorCodes::Overflow;
+                }
+                self.opstack.push_8(result)?;
+            },
+            ByteCodes::CheckedSubInt1 => {
+                let b = self.opstack.pop_1()? as i8;
+                let a = self.opstack.pop_1()? as i8;
+                let (result, overflow) = a.overflowing_sub(b);
+                if overflow {
+                    self.error_code = ErrorCodes::Overflow;
+                }
+                self.opstack.push_1(result as u8)?;
+            },
+            ByteCodes::CheckedSubInt2 => {
+                let b = self.opstack.pop_2()? as i16;
+                let a = self.opstack.pop_2()? as i16;
+                let (result, overflow) = a.overflowing_sub(b);
+                if overflow {
+                    self.error_code = ErrorCodes::Overflow;
+                }
+                self.opstack.push_2(result as u16)?;
+            },
+            ByteCodes::CheckedSubInt4 => {
+                let b = self.opstack.pop_4()? as i32;
+                let a = self.opstack.pop_4()? as i32;
+                let (result, overflow) = a.overflowing_sub(b);
+                if overflow {
+                    self.error_code = ErrorCodes::Overflow;
+                }
+                self.opstack.push_4(result as u32)?;
+            },
+            ByteCodes::CheckedSubInt8 => {
+                let b = self.opstack.pop_8()? as i64;
+                let a = self.opstack.pop_8()? as i64;
+                let (result, overflow) = a.overflowing_sub(b);
+                if overflow {
+                    self.error_code = ErrorCodes::Overflow;
+                }
+                self.opstack.push_8(result as u64)?;
+            },
+            ByteCodes::CheckedSubUint1 => {
+                let b = self.opstack.pop_1()?;
+                let a = self.opstack.pop_1()?;
+                let (result, overflow) = a.overflowing_sub(b);
+                if overflow {
+                    self.error_code = ErrorCodes::Overflow;
+                }
+                self.opstack.push_1(result)?;
+            },
+            ByteCodes::CheckedSubUint2 => {
+                let b = self.opstack.pop_2()?;
+                let a = self.opstack.pop_2()?;
+                let (result, overflow) = a.overflowing_sub(b);
+                if overflow {
+                    self.error_code = ErrorCodes::Overflow;
+                }
+                self.opstack.push_2(result)?;
+            },
+            ByteCodes::CheckedSubUint4 => {
+                let b = self.opstack.pop_4()?;
+                let a = self.opstack.pop_4()?;
+                let (result, overflow) = a.overflowing_sub(b);
+                if overflow {
+                    self.error_code = ErrorCodes::Overflow;
+                }
+                self.opstack.push_4(result)?;
+            },
+            ByteCodes::CheckedSubUint8 => {
+                let b = self.opstack.pop_8()?;
+                let a = self.opstack.pop_8()?;
+                let (result, overflow) = a.overflowing_sub(b);
+                if overflow {
+                    self.error_code = ErrorCodes::Overflow;
+                }
+                self.opstack.push_8(result)?;
+            },
+            ByteCodes::CheckedMulInt1 => {
+                let b = self.opstack.pop_1()? as i8;
+                let a = self.opstack.pop_1()? as i8;
+                let (result, overflow) = a.overflowing_mul(b);
+                if overflow {
+                    self.error_code = ErrorCodes::Overflow;
+                }
+                self.opstack.push_1(result as u8)?;
+            },
+            ByteCodes::CheckedMulInt2 => {
+                let b = self.opstack.pop_2()? as i16;
+                let a = self.opstack.pop_2()? as i16;
+                let (result, overflow) = a.overflowing_mul(b);
+                if overflow {
+                    self.error_code = ErrorCodes::Overflow;
+                }
+                self.opstack.push_2(result as u16)?;
+            },
+            ByteCodes::CheckedMulInt4 => {
+                let b = self.opstack.pop_4()? as i32;
+                let a = self.opstack.pop_4()? as i32;
+                let (result, overflow) = a.overflowing_mul(b);
+                if overflow {
+                    self.error_code = ErrorCodes::Overflow;
+                }
+                self.opstack.push_4(result as u32)?;
+            },
+            ByteCodes::CheckedMulInt8 => {
+                let b = self.opstack.pop_8()? as i64;
+                let a = self.opstack.pop_8()? as i64;
+                let (result, overflow) = a.overflowing_mul(b);
+                if overflow {
+                    self.error_code = ErrorCodes::Overflow;
+                }
+                self.opstack.push_8(result as u64)?;
+            },
+            ByteCodes::CheckedMulUint1 => {
+                let b = self.opstack.pop_1()?;
+                let a = self.opstack.pop_1()?;
+                let (result, overflow) = a.overflowing_mul(b);
+                if overflow {
+                    self.error_code = ErrorCodes::Overflow;
+                }
+                self.opstack.push_1(result)?;
+            },
+            ByteCodes::CheckedMulUint2 => {
+                let b = self.opstack.pop_2()?;
+                let a = self.opstack.pop_2()?;
+                let (result, overflow) = a.overflowing_mul(b);
+                if overflow {
+                    self.error_code = ErrorCodes::Overflow;
+                }
+                self.opstack.push_2(result)?;
+            },
+            ByteCodes::CheckedMulUint4 => {
+                let b = self.opstack.pop_4()?;
+                let a = self.opstack.pop_4()?;
+                let (result, overflow) = a.overflowing_mul(b);
+                if overflow {
+                    self.error_code = ErrorCodes::Overflow;
+                }
+                self.opstack.push_4(result)?;
+            },
+            ByteCodes::CheckedMulUint8 => {
+                let b = self.opstack.pop_8()?;
+                let a = self.opstack.pop_8()?;
+                let (result, overflow) = a.overflowing_mul(b);
+                if overflow {
+                    self.error_code = ErrorCodes::Overflow;
+                }
+                self.opstack.push_8(result)?;
+            },
+
+            ByteCodes::SaturatingAddInt1 => {
+                let b = self.opstack.pop_1()? as i8;
+                let a = self.opstack.pop_1()? as i8;
+                self.opstack.push_1(a.saturating_add(b) as u8)?;
+            },
+            ByteCodes::SaturatingAddInt2 => {
+                let b = self.opstack.pop_2()? as i16;
+                let a = self.opstack.pop_2()? as i16;
+                self.opstack.push_2(a.saturating_add(b) as u16)?;
+            },
+            ByteCodes::SaturatingAddInt4 => {
+                let b = self.opstack.pop_4()? as i32;
+                let a = self.opstack.pop_4()? as i32;
+                self.opstack.push_4(a.saturating_add(b) as u32)?;
+            },
+            ByteCodes::SaturatingAddInt8 => {
+                let b = self.opstack.pop_8()? as i64;
+                let a = self.opstack.pop_8()? as i64;
+                self.opstack.push_8(a.saturating_add(b) as u64)?;
+            },
+            ByteCodes::SaturatingAddUint1 => {
+                let b = self.opstack.pop_1()?;
+                let a = self.opstack.pop_1()?;
+                self.opstack.push_1(a.saturating_add(b))?;
+            },
+            ByteCodes::SaturatingAddUint2 => {
+                let b = self.opstack.pop_2()?;
+                let a = self.opstack.pop_2()?;
+                self.opstack.push_2(a.saturating_add(b))?;
+            },
+            ByteCodes::SaturatingAddUint4 => {
+                let b = self.opstack.pop_4()?;
+                let a = self.opstack.pop_4()?;
+                self.opstack.push_4(a.saturating_add(b))?;
+            },
+            ByteCodes::SaturatingAddUint8 => {
+                let b = self.opstack.pop_8()?;
+                let a = self.opstack.pop_8()?;
+                self.opstack.push_8(a.saturating_add(b))?;
+            },
+            ByteCodes::SaturatingSubInt1 => {
+                let b = self.opstack.pop_1()? as i8;
+                let a = self.opstack.pop_1()? as i8;
+                self.opstack.push_1(a.saturating_sub(b) as u8)?;
+            },
+            ByteCodes::SaturatingSubInt2 => {
+                let b = self.opstack.pop_2()? as i16;
+                let a = self.opstack.pop_2()? as i16;
+                self.opstack.push_2(a.saturating_sub(b) as u16)?;
+            },
+            ByteCodes::SaturatingSubInt4 => {
+                let b = self.opstack.pop_4()? as i32;
+                let a = self.opstack.pop_4()? as i32;
+                self.opstack.push_4(a.saturating_sub(b) as u32)?;
+            },
+            ByteCodes::SaturatingSubInt8 => {
+                let b = self.opstack.pop_8()? as i64;
+                let a = self.opstack.pop_8()? as i64;
+                self.opstack.push_8(a.saturating_sub(b) as u64)?;
+            },
+            ByteCodes::SaturatingSubUint1 => {
+                let b = self.opstack.pop_1()?;
+                let a = self.opstack.pop_1()?;
+                self.opstack.push_1(a.saturating_sub(b))?;
+            },
+            ByteCodes::SaturatingSubUint2 => {
+                let b = self.opstack.pop_2()?;
+                let a = self.opstack.pop_2()?;
+                self.opstack.push_2(a.saturating_sub(b))?;
+            },
+            ByteCodes::SaturatingSubUint4 => {
+                let b = self.opstack.pop_4()?;
+                let a = self.opstack.pop_4()?;
+                self.opstack.push_4(a.saturating_sub(b))?;
+            },
+            ByteCodes::SaturatingSubUint8 => {
+                let b = self.opstack.pop_8()?;
+                let a = self.opstack.pop_8()?;
+                self.opstack.push_8(a.saturating_sub(b))?;
+            },
+            ByteCodes::SaturatingMulInt1 => {
+                let b = self.opstack.pop_1()? as i8;
+                let a = self.opstack.pop_1()? as i8;
+                self.opstack.push_1(a.saturating_mul(b) as u8)?;
+            },
+            ByteCodes::SaturatingMulInt2 => {
+                let b = self.opstack.pop_2()? as i16;
+                let a = self.opstack.pop_2()? as i16;
+                self.opstack.push_2(a.saturating_mul(b) as u16)?;
+            },
+            ByteCodes::SaturatingMulInt4 => {
+                let b = self.opstack.pop_4()? as i32;
+                let a = self.opstack.pop_4()? as i32;
+                self.opstack.push_4(a.saturating_mul(b) as u32)?;
+            },
+            ByteCodes::SaturatingMulInt8 => {
+                let b = self.opstack.pop_8()? as i64;
+                let a = self.opstack.pop_8()? as i64;
+                self.opstack.push_8(a.saturating_mul(b) as u64)?;
+            },
+            ByteCodes::SaturatingMulUint1 => {
+                let b = self.opstack.pop_1()?;
+                let a = self.opstack.pop_1()?;
+                self.opstack.push_1(a.saturating_mul(b))?;
+            },
+            ByteCodes::SaturatingMulUint2 => {
+                let b = self.opstack.pop_2()?;
+                let a = self.opstack.pop_2()?;
+                self.opstack.push_2(a.saturating_mul(b))?;
+            },
+            ByteCodes::SaturatingMulUint4 => {
+                let b = self.opstack.pop_4()?;
+                let a = self.opstack.pop_4()?;
+                self.opstack.push_4(a.saturating_mul(b))?;
+            },
+            ByteCodes::SaturatingMulUint8 => {
+                let b = self.opstack.pop_8()?;
+                let a = self.opstack.pop_8()?;
+                self.opstack.push_8(a.saturating_mul(b))?;
+            },
+
+            ByteCodes::AddFloat4 => {
+                let a = f32::from_bits(self.opstack.pop_4()?);
+                let b = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_4((a + b).to_bits())?;
+            },
+            ByteCodes::AddFloat8 => {
+                let a = f64::from_bits(self.opstack.pop_8()?);
+                let b = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_8((a + b).to_bits())?;
+            },
+            ByteCodes::SubFloat4 => {
+                let b = f32::from_bits(self.opstack.pop_4()?);
+                let a = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_4((a - b).to_bits())?;
+            },
+            ByteCodes::SubFloat8 => {
+                let b = f64::from_bits(self.opstack.pop_8()?);
+                let a = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_8((a - b).to_bits())?;
+            },
+            ByteCodes::MulFloat4 => {
+                let a = f32::from_bits(self.opstack.pop_4()?);
+                let b = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_4((a * b).to_bits())?;
+            },
+            ByteCodes::MulFloat8 => {
+                let a = f64::from_bits(self.opstack.pop_8()?);
+                let b = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_8((a * b).to_bits())?;
+            },
+            ByteCodes::DivFloat4 => {
+                let b = f32::from_bits(self.opstack.pop_4()?);
+                let a = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_4((a / b).to_bits())?;
+            },
+            ByteCodes::DivFloat8 => {
+                let b = f64::from_bits(self.opstack.pop_8()?);
+                let a = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_8((a / b).to_bits())?;
+            },
+            ByteCodes::ModFloat4 => {
+                let b = f32::from_bits(self.opstack.pop_4()?);
+                let a = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_4((a % b).to_bits())?;
+            },
+            ByteCodes::ModFloat8 => {
+                let b = f64::from_bits(self.opstack.pop_8()?);
+                let a = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_8((a % b).to_bits())?;
+            },
+
+            ByteCodes::NegFloat4 => {
+                let value = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_4((-value).to_bits())?;
+            },
+            ByteCodes::NegFloat8 => {
+                let value = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_8((-value).to_bits())?;
+            },
+            ByteCodes::AbsFloat4 => {
+                let value = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_4((value.abs()).to_bits())?;
+            },
+            ByteCodes::AbsFloat8 => {
+                let value = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_8((value.abs()).to_bits())?;
+            },
+            ByteCodes::SqrtFloat4 => {
+                let value = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_4((value.sqrt()).to_bits())?;
+            },
+            ByteCodes::SqrtFloat8 => {
+                let value = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_8((value.sqrt()).to_bits())?;
+            },
+            ByteCodes::FloorFloat4 => {
+                let value = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_4((value.floor()).to_bits())?;
+            },
+            ByteCodes::FloorFloat8 => {
+                let value = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_8((value.floor()).to_bits())?;
+            },
+            ByteCodes::CeilFloat4 => {
+                let value = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_4((value.ceil()).to_bits())?;
+            },
+            ByteCodes::CeilFloat8 => {
+                let value = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_8((value.ceil()).to_bits())?;
+            },
+            ByteCodes::MinFloat4 => {
+                let b = f32::from_bits(self.opstack.pop_4()?);
+                let a = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_4(a.min(b).to_bits())?;
+            },
+            ByteCodes::MinFloat8 => {
+                let b = f64::from_bits(self.opstack.pop_8()?);
+                let a = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_8(a.min(b).to_bits())?;
+            },
+            ByteCodes::MaxFloat4 => {
+                let b = f32::from_bits(self.opstack.pop_4()?);
+                let a = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_4(a.max(b).to_bits())?;
+            },
+            ByteCodes::MaxFloat8 => {
+                let b = f64::from_bits(self.opstack.pop_8()?);
+                let a = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_8(a.max(b).to_bits())?;
+            },
+            ByteCodes::SinFloat4 => {
+                let value = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_4((value.sin()).to_bits())?;
+            },
+            ByteCodes::SinFloat8 => {
+                let value = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_8((value.sin()).to_bits())?;
+            },
+            ByteCodes::CosFloat4 => {
+                let value = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_4((value.cos()).to_bits())?;
+            },
+            ByteCodes::CosFloat8 => {
+                let value = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_8((value.cos()).to_bits())?;
+            },
+            ByteCodes::ExpFloat4 => {
+                let value = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_4((value.exp()).to_bits())?;
+            },
+            ByteCodes::ExpFloat8 => {
+                let value = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_8((value.exp()).to_bits())?;
+            },
+            ByteCodes::LogFloat4 => {
+                let value = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_4((value.ln()).to_bits())?;
+            },
+            ByteCodes::LogFloat8 => {
+                let value = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_8((value.ln()).to_bits())?;
+            },
+            ByteCodes::PowFloat4 => {
+                let b = f32::from_bits(self.opstack.pop_4()?);
+                let a = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_4(a.powf(b).to_bits())?;
+            },
+            ByteCodes::PowFloat8 => {
+                let b = f64::from_bits(self.opstack.pop_8()?);
+                let a = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_8(a.powf(b).to_bits())?;
+            },
+
+            ByteCodes::BitAnd1 => {
+                let a = self.opstack.pop_1()?;
+                let b = self.opstack.pop_1()?;
+                self.opstack.push_1(a & b)?;
+            },
+            ByteCodes::BitAnd2 => {
+                let a = self.opstack.pop_2()?;
+                let b = self.opstack.pop_2()?;
+                self.opstack.push_2(a & b)?;
+            },
+            ByteCodes::BitAnd4 => {
+                let a = self.opstack.pop_4()?;
+                let b = self.opstack.pop_4()?;
+                self.opstack.push_4(a & b)?;
+            },
+            ByteCodes::BitAnd8 => {
+                let a = self.opstack.pop_8()?;
+                let b = self.opstack.pop_8()?;
+                self.opstack.push_8(a & b)?;
+            },
+
+            ByteCodes::BitOr1 => {
+                let a = self.opstack.pop_1()?;
+                let b = self.opstack.pop_1()?;
+                self.opstack.push_1(a | b)?;
+            },
+            ByteCodes::BitOr2 => {
+                let a = self.opstack.pop_2()?;
+                let b = self.opstack.pop_2()?;
+                self.opstack.push_2(a | b)?;
+            },
+            ByteCodes::BitOr4 => {
+                let a = self.opstack.pop_4()?;
+                let b = self.opstack.pop_4()?;
+                self.opstack.push_4(a | b)?;
+            },
+            ByteCodes::BitOr8 => {
+                let a = self.opstack.pop_8()?;
+                let b = self.opstack.pop_8()?;
+                self.opstack.push_8(a | b)?;
+            },
+
+            ByteCodes::BitXor1 => {
+                let a = self.opstack.pop_1()?;
+                let b = self.opstack.pop_1()?;
+                self.opstack.push_1(a ^ b)?;
+            },
+            ByteCodes::BitXor2 => {
+                let a = self.opstack.pop_2()?;
+                let b = self.opstack.pop_2()?;
+                self.opstack.push_2(a ^ b)?;
+            },
+            ByteCodes::BitXor4 => {
+                let a = self.opstack.pop_4()?;
+                let b = self.opstack.pop_4()?;
+                self.opstack.push_4(a ^ b)?;
+            },
+            ByteCodes::BitXor8 => {
+                let a = self.opstack.pop_8()?;
+                let b = self.opstack.pop_8()?;
+                self.opstack.push_8(a ^ b)?;
+            },
+
+            ByteCodes::BitNot1 => {
+                let a = self.opstack.pop_1()?;
+                self.opstack.push_1(!a)?;
+            },
+            ByteCodes::BitNot2 => {
+                let a = self.opstack.pop_2()?;
+                self.opstack.push_2(!a)?;
+            },
+            ByteCodes::BitNot4 => {
+                let a = self.opstack.pop_4()?;
+                self.opstack.push_4(!a)?;
+            },
+            ByteCodes::BitNot8 => {
+                let a = self.opstack.pop_8()?;
+                self.opstack.push_8(!a)?;
+            },
+
+            // Shifting by the width of the value or more shifts all of its bits out, rather than masking the count
+            ByteCodes::ShiftLeft1 => {
+                let count = self.opstack.pop_1()? as u32;
+                let value = self.opstack.pop_1()?;
+                self.opstack.push_1(value.checked_shl(count).unwrap_or(0))?;
+            },
+            ByteCodes::ShiftLeft2 => {
+                let count = self.opstack.pop_1()? as u32;
+                let value = self.opstack.pop_2()?;
+                self.opstack.push_2(value.checked_shl(count).unwrap_or(0))?;
+            },
+            ByteCodes::ShiftLeft4 => {
+                let count = self.opstack.pop_1()? as u32;
+                let value = self.opstack.pop_4()?;
+                self.opstack.push_4(value.checked_shl(count).unwrap_or(0))?;
+            },
+            ByteCodes::ShiftLeft8 => {
+                let count = self.opstack.pop_1()? as u32;
+                let value = self.opstack.pop_8()?;
+                self.opstack.push_8(value.checked_shl(count).unwrap_or(0))?;
+            },
+
+            ByteCodes::ShiftRight1 => {
+                let count = self.opstack.pop_1()? as u32;
+                let value = self.opstack.pop_1()?;
+                self.opstack.push_1(value.checked_shr(count).unwrap_or(0))?;
+            },
+            ByteCodes::ShiftRight2 => {
+                let count = self.opstack.pop_1()? as u32;
+                let value = self.opstack.pop_2()?;
+                self.opstack.push_2(value.checked_shr(count).unwrap_or(0))?;
+            },
+            ByteCodes::ShiftRight4 => {
+                let count = self.opstack.pop_1()? as u32;
+                let value = self.opstack.pop_4()?;
+                self.opstack.push_4(value.checked_shr(count).unwrap_or(0))?;
+            },
+            ByteCodes::ShiftRight8 => {
+                let count = self.opstack.pop_1()? as u32;
+                let value = self.opstack.pop_8()?;
+                self.opstack.push_8(value.checked_shr(count).unwrap_or(0))?;
+            },
+
+            ByteCodes::ShiftRightArith1 => {
+                let count = self.opstack.pop_1()? as u32;
+                let value = self.opstack.pop_1()?;
+                self.opstack.push_1((value as i8).wrapping_shr(count.min(7)) as u8)?;
+            },
+            ByteCodes::ShiftRightArith2 => {
+                let count = self.opstack.pop_1()? as u32;
+                let value = self.opstack.pop_2()?;
+                self.opstack.push_2((value as i16).wrapping_shr(count.min(15)) as u16)?;
+            },
+            ByteCodes::ShiftRightArith4 => {
+                let count = self.opstack.pop_1()? as u32;
+                let value = self.opstack.pop_4()?;
+                self.opstack.push_4((value as i32).wrapping_shr(count.min(31)) as u32)?;
+            },
+            ByteCodes::ShiftRightArith8 => {
+                let count = self.opstack.pop_1()? as u32;
+                let value = self.opstack.pop_8()?;
+                self.opstack.push_8((value as i64).wrapping_shr(count.min(63)) as u64)?;
+            },
+
+            ByteCodes::RotateLeft1 => {
+                let count = self.opstack.pop_1()? as u32;
+                let value = self.opstack.pop_1()?;
+                self.opstack.push_1(value.rotate_left(count))?;
+            },
+            ByteCodes::RotateLeft2 => {
+                let count = self.opstack.pop_1()? as u32;
+                let value = self.opstack.pop_2()?;
+                self.opstack.push_2(value.rotate_left(count))?;
+            },
+            ByteCodes::RotateLeft4 => {
+                let count = self.opstack.pop_1()? as u32;
+                let value = self.opstack.pop_4()?;
+                self.opstack.push_4(value.rotate_left(count))?;
+            },
+            ByteCodes::RotateLeft8 => {
+                let count = self.opstack.pop_1()? as u32;
+                let value = self.opstack.pop_8()?;
+                self.opstack.push_8(value.rotate_left(count))?;
+            },
+
+            ByteCodes::RotateRight1 => {
+                let count = self.opstack.pop_1()? as u32;
+                let value = self.opstack.pop_1()?;
+                self.opstack.push_1(value.rotate_right(count))?;
+            },
+            ByteCodes::RotateRight2 => {
+                let count = self.opstack.pop_1()? as u32;
+                let value = self.opstack.pop_2()?;
+                self.opstack.push_2(value.rotate_right(count))?;
+            },
+            ByteCodes::RotateRight4 => {
+                let count = self.opstack.pop_1()? as u32;
+                let value = self.opstack.pop_4()?;
+                self.opstack.push_4(value.rotate_right(count))?;
+            },
+            ByteCodes::RotateRight8 => {
+                let count = self.opstack.pop_1()? as u32;
+                let value = self.opstack.pop_8()?;
+                self.opstack.push_8(value.rotate_right(count))?;
+            },
+
+            ByteCodes::ByteSwap2 => {
+                let value = self.opstack.pop_2()?;
+                self.opstack.push_2(value.swap_bytes())?;
+            },
+            ByteCodes::ByteSwap4 => {
+                let value = self.opstack.pop_4()?;
+                self.opstack.push_4(value.swap_bytes())?;
+            },
+            ByteCodes::ByteSwap8 => {
+                let value = self.opstack.pop_8()?;
+                self.opstack.push_8(value.swap_bytes())?;
+            },
+
+            ByteCodes::EqualInt1 => {
+                let b = self.opstack.pop_1()?;
+                let a = self.opstack.pop_1()?;
+                self.opstack.push_1((a == b) as u8)?;
+            },
+            ByteCodes::EqualInt2 => {
+                let b = self.opstack.pop_2()?;
+                let a = self.opstack.pop_2()?;
+                self.opstack.push_1((a == b) as u8)?;
+            },
+            ByteCodes::EqualInt4 => {
+                let b = self.opstack.pop_4()?;
+                let a = self.opstack.pop_4()?;
+                self.opstack.push_1((a == b) as u8)?;
+            },
+            ByteCodes::EqualInt8 => {
+                let b = self.opstack.pop_8()?;
+                let a = self.opstack.pop_8()?;
+                self.opstack.push_1((a == b) as u8)?;
+            },
+            ByteCodes::NotEqualInt1 => {
+                let b = self.opstack.pop_1()?;
+                let a = self.opstack.pop_1()?;
+                self.opstack.push_1((a != b) as u8)?;
+            },
+            ByteCodes::NotEqualInt2 => {
+                let b = self.opstack.pop_2()?;
+                let a = self.opstack.pop_2()?;
+                self.opstack.push_1((a != b) as u8)?;
+            },
+            ByteCodes::NotEqualInt4 => {
+                let b = self.opstack.pop_4()?;
+                let a = self.opstack.pop_4()?;
+                self.opstack.push_1((a != b) as u8)?;
+            },
+            ByteCodes::NotEqualInt8 => {
+                let b = self.opstack.pop_8()?;
+                let a = self.opstack.pop_8()?;
+                self.opstack.push_1((a != b) as u8)?;
+            },
+            ByteCodes::LessInt1 => {
+                let b = self.opstack.pop_1()? as i8;
+                let a = self.opstack.pop_1()? as i8;
+                self.opstack.push_1((a < b) as u8)?;
+            },
+            ByteCodes::LessInt2 => {
+                let b = self.opstack.pop_2()? as i16;
+                let a = self.opstack.pop_2()? as i16;
+                self.opstack.push_1((a < b) as u8)?;
+            },
+            ByteCodes::LessInt4 => {
+                let b = self.opstack.pop_4()? as i32;
+                let a = self.opstack.pop_4()? as i32;
+                self.opstack.push_1((a < b) as u8)?;
+            },
+            ByteCodes::LessInt8 => {
+                let b = self.opstack.pop_8()? as i64;
+                let a = self.opstack.pop_8()? as i64;
+                self.opstack.push_1((a < b) as u8)?;
+            },
+            ByteCodes::LessEqualInt1 => {
+                let b = self.opstack.pop_1()? as i8;
+                let a = self.opstack.pop_1()? as i8;
+                self.opstack.push_1((a <= b) as u8)?;
+            },
+            ByteCodes::LessEqualInt2 => {
+                let b = self.opstack.pop_2()? as i16;
+                let a = self.opstack.pop_2()? as i16;
+                self.opstack.push_1((a <= b) as u8)?;
+            },
+            ByteCodes::LessEqualInt4 => {
+                let b = self.opstack.pop_4()? as i32;
+                let a = self.opstack.pop_4()? as i32;
+                self.opstack.push_1((a <= b) as u8)?;
+            },
+            ByteCodes::LessEqualInt8 => {
+                let b = self.opstack.pop_8()? as i64;
+                let a = self.opstack.pop_8()? as i64;
+                self.opstack.push_1((a <= b) as u8)?;
+            },
+            ByteCodes::GreaterInt1 => {
+                let b = self.opstack.pop_1()? as i8;
+                let a = self.opstack.pop_1()? as i8;
+                self.opstack.push_1((a > b) as u8)?;
+            },
+            ByteCodes::GreaterInt2 => {
+                let b = self.opstack.pop_2()? as i16;
+                let a = self.opstack.pop_2()? as i16;
+                self.opstack.push_1((a > b) as u8)?;
+            },
+            ByteCodes::GreaterInt4 => {
+                let b = self.opstack.pop_4()? as i32;
+                let a = self.opstack.pop_4()? as i32;
+                self.opstack.push_1((a > b) as u8)?;
+            },
+            ByteCodes::GreaterInt8 => {
+                let b = self.opstack.pop_8()? as i64;
+                let a = self.opstack.pop_8()? as i64;
+                self.opstack.push_1((a > b) as u8)?;
+            },
+            ByteCodes::GreaterEqualInt1 => {
+                let b = self.opstack.pop_1()? as i8;
+                let a = self.opstack.pop_1()? as i8;
+                self.opstack.push_1((a >= b) as u8)?;
+            },
+            ByteCodes::GreaterEqualInt2 => {
+                let b = self.opstack.pop_2()? as i16;
+                let a = self.opstack.pop_2()? as i16;
+                self.opstack.push_1((a >= b) as u8)?;
+            },
+            ByteCodes::GreaterEqualInt4 => {
+                let b = self.opstack.pop_4()? as i32;
+                let a = self.opstack.pop_4()? as i32;
+                self.opstack.push_1((a >= b) as u8)?;
+            },
+            ByteCodes::GreaterEqualInt8 => {
+                let b = self.opstack.pop_8()? as i64;
+                let a = self.opstack.pop_8()? as i64;
+                self.opstack.push_1((a >= b) as u8)?;
+            },
+
+            ByteCodes::LessUint1 => {
+                let b = self.opstack.pop_1()?;
+                let a = self.opstack.pop_1()?;
+                self.opstack.push_1((a < b) as u8)?;
+            },
+            ByteCodes::LessUint2 => {
+                let b = self.opstack.pop_2()?;
+                let a = self.opstack.pop_2()?;
+                self.opstack.push_1((a < b) as u8)?;
+            },
+            ByteCodes::LessUint4 => {
+                let b = self.opstack.pop_4()?;
+                let a = self.opstack.pop_4()?;
+                self.opstack.push_1((a < b) as u8)?;
+            },
+            ByteCodes::LessUint8 => {
+                let b = self.opstack.pop_8()?;
+                let a = self.opstack.pop_8()?;
+                self.opstack.push_1((a < b) as u8)?;
+            },
+            ByteCodes::LessEqualUint1 => {
+                let b = self.opstack.pop_1()?;
+                let a = self.opstack.pop_1()?;
+                self.opstack.push_1((a <= b) as u8)?;
+            },
+            ByteCodes::LessEqualUint2 => {
+                let b = self.opstack.pop_2()?;
+                let a = self.opstack.pop_2()?;
+                self.opstack.push_1((a <= b) as u8)?;
+            },
+            ByteCodes::LessEqualUint4 => {
+                let b = self.opstack.pop_4()?;
+                let a = self.opstack.pop_4()?;
+                self.opstack.push_1((a <= b) as u8)?;
+            },
+            ByteCodes::LessEqualUint8 => {
+                let b = self.opstack.pop_8()?;
+                let a = self.opstack.pop_8()?;
+                self.opstack.push_1((a <= b) as u8)?;
+            },
+            ByteCodes::GreaterUint1 => {
+                let b = self.opstack.pop_1()?;
+                let a = self.opstack.pop_1()?;
+                self.opstack.push_1((a > b) as u8)?;
+            },
+            ByteCodes::GreaterUint2 => {
+                let b = self.opstack.pop_2()?;
+                let a = self.opstack.pop_2()?;
+                self.opstack.push_1((a > b) as u8)?;
+            },
+            ByteCodes::GreaterUint4 => {
+                let b = self.opstack.pop_4()?;
+                let a = self.opstack.pop_4()?;
+                self.opstack.push_1((a > b) as u8)?;
+            },
+            ByteCodes::GreaterUint8 => {
+                let b = self.opstack.pop_8()?;
+                let a = self.opstack.pop_8()?;
+                self.opstack.push_1((a > b) as u8)?;
+            },
+            ByteCodes::GreaterEqualUint1 => {
+                let b = self.opstack.pop_1()?;
+                let a = self.opstack.pop_1()?;
+                self.opstack.push_1((a >= b) as u8)?;
+            },
+            ByteCodes::GreaterEqualUint2 => {
+                let b = self.opstack.pop_2()?;
+                let a = self.opstack.pop_2()?;
+                self.opstack.push_1((a >= b) as u8)?;
+            },
+            ByteCodes::GreaterEqualUint4 => {
+                let b = self.opstack.pop_4()?;
+                let a = self.opstack.pop_4()?;
+                self.opstack.push_1((a >= b) as u8)?;
+            },
+            ByteCodes::GreaterEqualUint8 => {
+                let b = self.opstack.pop_8()?;
+                let a = self.opstack.pop_8()?;
+                self.opstack.push_1((a >= b) as u8)?;
+            },
+
+            ByteCodes::EqualFloat4 => {
+                let b = f32::from_bits(self.opstack.pop_4()?);
+                let a = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_1((a == b) as u8)?;
+            },
+            ByteCodes::EqualFloat8 => {
+                let b = f64::from_bits(self.opstack.pop_8()?);
+                let a = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_1((a == b) as u8)?;
+            },
+            ByteCodes::NotEqualFloat4 => {
+                let b = f32::from_bits(self.opstack.pop_4()?);
+                let a = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_1((a != b) as u8)?;
+            },
+            ByteCodes::NotEqualFloat8 => {
+                let b = f64::from_bits(self.opstack.pop_8()?);
+                let a = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_1((a != b) as u8)?;
+            },
+            ByteCodes::LessFloat4 => {
+                let b = f32::from_bits(self.opstack.pop_4()?);
+                let a = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_1((a < b) as u8)?;
+            },
+            ByteCodes::LessFloat8 => {
+                let b = f64::from_bits(self.opstack.pop_8()?);
+                let a = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_1((a < b) as u8)?;
+            },
+            ByteCodes::LessEqualFloat4 => {
+                let b = f32::from_bits(self.opstack.pop_4()?);
+                let a = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_1((a <= b) as u8)?;
+            },
+            ByteCodes::LessEqualFloat8 => {
+                let b = f64::from_bits(self.opstack.pop_8()?);
+                let a = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_1((a <= b) as u8)?;
+            },
+            ByteCodes::GreaterFloat4 => {
+                let b = f32::from_bits(self.opstack.pop_4()?);
+                let a = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_1((a > b) as u8)?;
+            },
+            ByteCodes::GreaterFloat8 => {
+                let b = f64::from_bits(self.opstack.pop_8()?);
+                let a = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_1((a > b) as u8)?;
+            },
+            ByteCodes::GreaterEqualFloat4 => {
+                let b = f32::from_bits(self.opstack.pop_4()?);
+                let a = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_1((a >= b) as u8)?;
+            },
+            ByteCodes::GreaterEqualFloat8 => {
+                let b = f64::from_bits(self.opstack.pop_8()?);
+                let a = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_1((a >= b) as u8)?;
+            },
+
+            ByteCodes::Select1 => {
+                let condition = self.opstack.pop_1()?;
+                let b = self.opstack.pop_1()?;
+                let a = self.opstack.pop_1()?;
+                self.opstack.push_1(if condition != 0 { a } else { b })?;
+            },
+            ByteCodes::Select2 => {
+                let condition = self.opstack.pop_1()?;
+                let b = self.opstack.pop_2()?;
+                let a = self.opstack.pop_2()?;
+                self.opstack.push_2(if condition != 0 { a } else { b })?;
+            },
+            ByteCodes::Select4 => {
+                let condition = self.opstack.pop_1()?;
+                let b = self.opstack.pop_4()?;
+                let a = self.opstack.pop_4()?;
+                self.opstack.push_4(if condition != 0 { a } else { b })?;
+            },
+            ByteCodes::Select8 => {
+                let condition = self.opstack.pop_1()?;
+                let b = self.opstack.pop_8()?;
+                let a = self.opstack.pop_8()?;
+                self.opstack.push_8(if condition != 0 { a } else { b })?;
+            },
+
+            ByteCodes::SignExtend1To2 => {
+                let value = self.opstack.pop_1()? as i8;
+                self.opstack.push_2(value as i16 as u16)?;
+            },
+            ByteCodes::SignExtend1To4 => {
+                let value = self.opstack.pop_1()? as i8;
+                self.opstack.push_4(value as i32 as u32)?;
+            },
+            ByteCodes::SignExtend1To8 => {
+                let value = self.opstack.pop_1()? as i8;
+                self.opstack.push_8(value as i64 as u64)?;
+            },
+            ByteCodes::SignExtend2To4 => {
+                let value = self.opstack.pop_2()? as i16;
+                self.opstack.push_4(value as i32 as u32)?;
+            },
+            ByteCodes::SignExtend2To8 => {
+                let value = self.opstack.pop_2()? as i16;
+                self.opstack.push_8(value as i64 as u64)?;
+            },
+            ByteCodes::SignExtend4To8 => {
+                let value = self.opstack.pop_4()? as i32;
+                self.opstack.push_8(value as i64 as u64)?;
+            },
+
+            ByteCodes::ZeroExtend1To2 => {
+                let value = self.opstack.pop_1()?;
+                self.opstack.push_2(value as u16)?;
+            },
+            ByteCodes::ZeroExtend1To4 => {
+                let value = self.opstack.pop_1()?;
+                self.opstack.push_4(value as u32)?;
+            },
+            ByteCodes::ZeroExtend1To8 => {
+                let value = self.opstack.pop_1()?;
+                self.opstack.push_8(value as u64)?;
+            },
+            ByteCodes::ZeroExtend2To4 => {
+                let value = self.opstack.pop_2()?;
+                self.opstack.push_4(value as u32)?;
+            },
+            ByteCodes::ZeroExtend2To8 => {
+                let value = self.opstack.pop_2()?;
+                self.opstack.push_8(value as u64)?;
+            },
+            ByteCodes::ZeroExtend4To8 => {
+                let value = self.opstack.pop_4()?;
+                self.opstack.push_8(value as u64)?;
+            },
+
+            ByteCodes::Truncate2To1 => {
+                let value = self.opstack.pop_2()?;
+                self.opstack.push_1(value as u8)?;
+            },
+            ByteCodes::Truncate4To1 => {
+                let value = self.opstack.pop_4()?;
+                self.opstack.push_1(value as u8)?;
+            },
+            ByteCodes::Truncate8To1 => {
+                let value = self.opstack.pop_8()?;
+                self.opstack.push_1(value as u8)?;
+            },
+            ByteCodes::Truncate4To2 => {
+                let value = self.opstack.pop_4()?;
+                self.opstack.push_2(value as u16)?;
+            },
+            ByteCodes::Truncate8To2 => {
+                let value = self.opstack.pop_8()?;
+                self.opstack.push_2(value as u16)?;
+            },
+            ByteCodes::Truncate8To4 => {
+                let value = self.opstack.pop_8()?;
+                self.opstack.push_4(value as u32)?;
+            },
+
+            ByteCodes::Int1ToFloat4 => {
+                let value = self.opstack.pop_1()? as i8;
+                self.opstack.push_4((value as f32).to_bits())?;
+            },
+            ByteCodes::Int1ToFloat8 => {
+                let value = self.opstack.pop_1()? as i8;
+                self.opstack.push_8((value as f64).to_bits())?;
+            },
+            ByteCodes::Int2ToFloat4 => {
+                let value = self.opstack.pop_2()? as i16;
+                self.opstack.push_4((value as f32).to_bits())?;
+            },
+            ByteCodes::Int2ToFloat8 => {
+                let value = self.opstack.pop_2()? as i16;
+                self.opstack.push_8((value as f64).to_bits())?;
+            },
+            ByteCodes::Int4ToFloat4 => {
+                let value = self.opstack.pop_4()? as i32;
+                self.opstack.push_4((value as f32).to_bits())?;
+            },
+            ByteCodes::Int4ToFloat8 => {
+                let value = self.opstack.pop_4()? as i32;
+                self.opstack.push_8((value as f64).to_bits())?;
+            },
+            ByteCodes::Int8ToFloat4 => {
+                let value = self.opstack.pop_8()? as i64;
+                self.opstack.push_4((value as f32).to_bits())?;
+            },
+            ByteCodes::Int8ToFloat8 => {
+                let value = self.opstack.pop_8()? as i64;
+                self.opstack.push_8((value as f64).to_bits())?;
+            },
+            ByteCodes::Uint1ToFloat4 => {
+                let value = self.opstack.pop_1()?;
+                self.opstack.push_4((value as f32).to_bits())?;
+            },
+            ByteCodes::Uint1ToFloat8 => {
+                let value = self.opstack.pop_1()?;
+                self.opstack.push_8((value as f64).to_bits())?;
+            },
+            ByteCodes::Uint2ToFloat4 => {
+                let value = self.opstack.pop_2()?;
+                self.opstack.push_4((value as f32).to_bits())?;
+            },
+            ByteCodes::Uint2ToFloat8 => {
+                let value = self.opstack.pop_2()?;
+                self.opstack.push_8((value as f64).to_bits())?;
+            },
+            ByteCodes::Uint4ToFloat4 => {
+                let value = self.opstack.pop_4()?;
+                self.opstack.push_4((value as f32).to_bits())?;
+            },
+            ByteCodes::Uint4ToFloat8 => {
+                let value = self.opstack.pop_4()?;
+                self.opstack.push_8((value as f64).to_bits())?;
+            },
+            ByteCodes::Uint8ToFloat4 => {
+                let value = self.opstack.pop_8()?;
+                self.opstack.push_4((value as f32).to_bits())?;
+            },
+            ByteCodes::Uint8ToFloat8 => {
+                let value = self.opstack.pop_8()?;
+                self.opstack.push_8((value as f64).to_bits())?;
+            },
+
+            ByteCodes::Float4ToInt1 => {
+                let value = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_1(value as i8 as u8)?;
+            },
+            ByteCodes::Float8ToInt1 => {
+                let value = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_1(value as i8 as u8)?;
+            },
+            ByteCodes::Float4ToInt2 => {
+                let value = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_2(value as i16 as u16)?;
+            },
+            ByteCodes::Float8ToInt2 => {
+                let value = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_2(value as i16 as u16)?;
+            },
+            ByteCodes::Float4ToInt4 => {
+                let value = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_4(value as i32 as u32)?;
+            },
+            ByteCodes::Float8ToInt4 => {
+                let value = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_4(value as i32 as u32)?;
+            },
+            ByteCodes::Float4ToInt8 => {
+                let value = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_8(value as i64 as u64)?;
+            },
+            ByteCodes::Float8ToInt8 => {
+                let value = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_8(value as i64 as u64)?;
+            },
+            ByteCodes::Float4ToUint1 => {
+                let value = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_1(value as u8)?;
+            },
+            ByteCodes::Float8ToUint1 => {
+                let value = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_1(value as u8)?;
+            },
+            ByteCodes::Float4ToUint2 => {
+                let value = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_2(value as u16)?;
+            },
+            ByteCodes::Float8ToUint2 => {
+                let value = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_2(value as u16)?;
+            },
+            ByteCodes::Float4ToUint4 => {
+                let value = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_4(value as u32)?;
+            },
+            ByteCodes::Float8ToUint4 => {
+                let value = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_4(value as u32)?;
+            },
+            ByteCodes::Float4ToUint8 => {
+                let value = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_8(value as u64)?;
+            },
+            ByteCodes::Float8ToUint8 => {
+                let value = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_8(value as u64)?;
+            },
+
+            ByteCodes::Float4ToFloat8 => {
+                let value = f32::from_bits(self.opstack.pop_4()?);
+                self.opstack.push_8((value as f64).to_bits())?;
+            },
+            ByteCodes::Float8ToFloat4 => {
+                let value = f64::from_bits(self.opstack.pop_8()?);
+                self.opstack.push_4((value as f32).to_bits())?;
+            },
+
+            ByteCodes::Memmove1 => {
+                let dest = self.opstack.pop_8()?;
+                let src = self.opstack.pop_8()?;
+                let src = self.translate_read(src, 1)?;
+                let dest = self.translate_write(dest, 1)?;
+                unsafe {
+                    dest.write_unaligned(src.read_unaligned());
+                }
+            },
+            ByteCodes::Memmove2 => {
+                let dest = self.opstack.pop_8()?;
+                let src = self.opstack.pop_8()?;
+                let src = self.translate_read(src, 2)? as *const u16;
+                let dest = self.translate_write(dest, 2)? as *mut u16;
+                unsafe {
+                    dest.write_unaligned(src.read_unaligned());
+                }
+            },
+            ByteCodes::Memmove4 => {
+                let dest = self.opstack.pop_8()?;
+                let src = self.opstack.pop_8()?;
+                let src = self.translate_read(src, 4)? as *const u32;
+                let dest = self.translate_write(dest, 4)? as *mut u32;
+                unsafe {
+                    dest.write_unaligned(src.read_unaligned());
+                }
+            },
+            ByteCodes::Memmove8 => {
+                let dest = self.opstack.pop_8()?;
+                let src = self.opstack.pop_8()?;
+                let src = self.translate_read(src, 8)? as *const u64;
+                let dest = self.translate_write(dest, 8)? as *mut u64;
+                unsafe {
+                    dest.write_unaligned(src.read_unaligned());
+                }
+            },
+            ByteCodes::MemmoveBytes => {
+                let dest = self.opstack.pop_8()?;
+                let src = self.opstack.pop_8()?;
+                let count = self.opstack.pop_8()? as usize;
+                let src = self.translate_read(src, count)?;
+                let dest = self.translate_write(dest, count)?;
+                unsafe {
+                    ptr::copy(src, dest, count);
+                }
+            },
+
+            ByteCodes::VirtualConstToReal => {
+                let vsrc = VirtualAddress(program.fetch_8()? as Address);
+                self.opstack.push_8(program.virtual_to_real(vsrc) as u64)?;
+            },
+            ByteCodes::VirtualToReal => {
+                let vsrc = VirtualAddress(self.opstack.pop_8()? as Address);
+                self.opstack.push_8(program.virtual_to_real(vsrc) as u64)?;
+            }
+
+            ByteCodes::LoadData1 => {
+                let vsrc = VirtualAddress(program.fetch_8()? as Address);
+                self.opstack.push_1(program.get_data1(vsrc)?)?;
+            },
+            ByteCodes::LoadData2 => {
+                let vsrc = VirtualAddress(program.fetch_8()? as Address);
+                self.opstack.push_2(program.get_data2(vsrc)?)?;
+            },
+            ByteCodes::LoadData4 => {
+                let vsrc = VirtualAddress(program.fetch_8()? as Address);
+                self.opstack.push_4(program.get_data4(vsrc)?)?;
+            },
+            ByteCodes::LoadData8 => {
+                let vsrc = VirtualAddress(program.fetch_8()? as Address);
+                self.opstack.push_8(program.get_data8(vsrc)?)?;
+            },
+            ByteCodes::StoreData1 => {
+                let vdest = VirtualAddress(program.fetch_8()? as Address);
+                let value = self.opstack.pop_1()?;
+                program.set_data1(vdest, value)?;
+            },
+            ByteCodes::StoreData2 => {
+                let vdest = VirtualAddress(program.fetch_8()? as Address);
+                let value = self.opstack.pop_2()?;
+                program.set_data2(vdest, value)?;
+            },
+            ByteCodes::StoreData4 => {
+                let vdest = VirtualAddress(program.fetch_8()? as Address);
+                let value = self.opstack.pop_4()?;
+                program.set_data4(vdest, value)?;
+            },
+            ByteCodes::StoreData8 => {
+                let vdest = VirtualAddress(program.fetch_8()? as Address);
+                let value = self.opstack.pop_8()?;
+                program.set_data8(vdest, value)?;
+            },
+            ByteCodes::VirtualDataConstToReal => {
+                let vsrc = VirtualAddress(program.fetch_8()? as Address);
+                self.opstack.push_8(program.data_virtual_to_real(vsrc) as u64)?;
+            },
+            ByteCodes::VirtualDataToReal => {
+                let vsrc = VirtualAddress(self.opstack.pop_8()? as Address);
+                self.opstack.push_8(program.data_virtual_to_real(vsrc) as u64)?;
+            },
+
+            ByteCodes::LoadStatic1 => {
+                let vsrc = VirtualAddress(program.fetch_8()? as Address);
+                self.opstack.push_1(program.get_static1(vsrc)?)?;
+            },
+            ByteCodes::LoadStatic2 => {
+                let vsrc = VirtualAddress(program.fetch_8()? as Address);
+                self.opstack.push_2(program.get_static2(vsrc)?)?;
+            },
+            ByteCodes::LoadStatic4 => {
+                let vsrc = VirtualAddress(program.fetch_8()? as Address);
+                self.opstack.push_4(program.get_static4(vsrc)?)?;
+            },
+            ByteCodes::LoadStatic8 => {
+                let vsrc = VirtualAddress(program.fetch_8()? as Address);
+                self.opstack.push_8(program.get_static8(vsrc)?)?;
+            },
+            ByteCodes::LoadStaticBytes => {
+                let vsrc = VirtualAddress(program.fetch_8()? as Address);
+                let count = program.fetch_8()? as usize;
+                self.opstack.push_bytes(program.get_static_bytes(vsrc, count)?)?;
+            },
+
+            ByteCodes::PopConst => {
+                let count = program.fetch_8()? as usize;
+                self.opstack.pop_by(count)?;
+            },
+            ByteCodes::PopBytes => {
+                let count = self.opstack.pop_8()? as usize;
+                self.opstack.pop_by(count)?;
+            },
+
+            ByteCodes::Load1 => {
+                let src = self.opstack.pop_8()?;
+                let src = self.translate_read(src, 1)?;
+                self.opstack.push_1(unsafe { src.read_unaligned() })?;
+            },
+            ByteCodes::Load2 => {
+                let src = self.opstack.pop_8()?;
+                let src = self.translate_read(src, 2)? as *const u16;
+                self.opstack.push_2(unsafe { src.read_unaligned() })?;
+            },
+            ByteCodes::Load4 => {
+                let src = self.opstack.pop_8()?;
+                let src = self.translate_read(src, 4)? as *const u32;
+                self.opstack.push_4(unsafe { src.read_unaligned() })?;
+            },
+            ByteCodes::Load8 => {
+                let src = self.opstack.pop_8()?;
+                let src = self.translate_read(src, 8)? as *const u64;
+                self.opstack.push_8(unsafe { src.read_unaligned() })?;
+            },
+            ByteCodes::LoadBytes => {
+                let src = self.opstack.pop_8()?;
+                let count = self.opstack.pop_8()? as usize;
+                let src = self.translate_read(src, count)?;
+                self.opstack.push_from(src, count)?;
+            },
+
+            ByteCodes::LoadConst1 => {
+                self.opstack.push_1(program.fetch_1()?)?;
+            },
+            ByteCodes::LoadConst2 => {
+                self.opstack.push_2(program.fetch_2()?)?;
+            },
+            ByteCodes::LoadConst4 => {
+                self.opstack.push_4(program.fetch_4()?)?;
+            },
+            ByteCodes::LoadConst8 => {
+                self.opstack.push_8(program.fetch_8()?)?;
+            },
+            ByteCodes::LoadConstBytes => {
+                let count = program.fetch_8()? as usize;
+                self.opstack.push_bytes(program.fetch_bytes(count)?)?;
+            },
+
+            ByteCodes::LoadProgramCounter => {
+                self.opstack.push_8(program.program_counter().0 as u64)?;
+            },
+            ByteCodes::LoadStackPointer => {
+                self.opstack.push_8((self.stack_base + self.opstack.free_size()) as u64)?;
+            },
+            ByteCodes::LoadStackSize => {
+                self.opstack.push_8(self.opstack._stack.len() as u64)?;
+            },
+            ByteCodes::LoadStackBottom => {
+                self.opstack.push_8(self.stack_base as u64)?;
+            },
+
+            ByteCodes::Store1 => {
+                let dest = self.opstack.pop_8()?;
+                let dest = self.translate_write(dest, 1)?;
+                let value = self.opstack.pop_1()?;
+                unsafe {
+                    dest.write_unaligned(value);
+                }
+            },
+            ByteCodes::Store2 => {
+                let dest = self.opstack.pop_8()?;
+                let dest = self.translate_write(dest, 2)? as *mut u16;
+                let value = self.opstack.pop_2()?;
+                unsafe {
+                    dest.write_unaligned(value);
+                }
+            },
+            ByteCodes::Store4 => {
+                let dest = self.opstack.pop_8()?;
+                let dest = self.translate_write(dest, 4)? as *mut u32;
+                let value = self.opstack.pop_4()?;
+                unsafe {
+                    dest.write_unaligned(value);
+                }
+            },
+            ByteCodes::Store8 => {
+                let dest = self.opstack.pop_8()?;
+                let dest = self.translate_write(dest, 8)? as *mut u64;
+                let value = self.opstack.pop_8()?;
+                unsafe {
+                    dest.write_unaligned(value);
+                }
+            },
+            ByteCodes::StoreBytes => {
+                let dest = self.opstack.pop_8()?;
+                let count = self.opstack.pop_8()? as usize;
+                let dest = self.translate_write(dest, count)?;
+                unsafe {
+                    // The destination may overlap the popped bytes if it's on the stack
+                    ptr::copy(self.opstack.pop_bytes(count)?.as_ptr(), dest, count);
+                }
+            },
+
+            ByteCodes::Malloc => {
+                let size = self.opstack.pop_8()? as usize;
+                self.check_heap_limit(self.heap_block_size(size))?;
+                let addr = self.allocate(size, 1, false);
+                self.push_allocation(addr)?;
+            },
+            ByteCodes::Realloc => {
+                let addr = self.opstack.pop_8()? as Address;
+                let new_size = self.opstack.pop_8()? as usize;
+                // The current size of the block already includes its redzone
+                self.check_heap_limit(self.heap_block_size(new_size).saturating_sub(self.memory.block_size(addr).unwrap_or(0)))?;
+                let new_addr = self.reallocate(addr, new_size);
+                self.push_allocation(new_addr)?;
+            },
+            ByteCodes::Free => {
+                let addr = self.opstack.pop_8()? as Address;
+                if let Err(error_code) = self.free(addr) {
+                    self.error_code = error_code;
+                }
+            },
+            ByteCodes::Calloc => {
+                let size = self.opstack.pop_8()? as usize;
+                self.check_heap_limit(self.heap_block_size(size))?;
+                let addr = self.allocate(size, 1, true);
+                self.push_allocation(addr)?;
+            },
+            ByteCodes::MallocAligned => {
+                let align = self.opstack.pop_8()? as usize;
+                let size = self.opstack.pop_8()? as usize;
+                self.check_heap_limit(self.heap_block_size(size))?;
+                let addr = self.allocate(size, align, false);
+                self.push_allocation(addr)?;
+            },
+
+            ByteCodes::Exit => {
+                let exit_code = self.opstack.pop_4()? as i32;
+                return Ok(Some(self.decode_error_code(exit_code)?));
+            },
+
+            ByteCodes::Intr => {
+                let intr_code = self.opstack.pop_1()?;
+                let intr_code = self.decode_interrupt(intr_code)?;
+                self.handle_interrupt(intr_code, program)?;
+            },
+
+            ByteCodes::IntrConst => {
+                let intr_code = program.fetch_1()?;
+                let intr_code = self.decode_interrupt(intr_code)?;
+                self.handle_interrupt(intr_code, program)?;
+            },
+
+            ByteCodes::HostCall => {
+                let id = program.fetch_4()?;
+                self.call_host_function(id, program)?;
+            },
+
+            ByteCodes::ReadError => {
+                self.opstack.push_4(self.error_code as u32)?;
+            },
+
+            ByteCodes::SetErrorConst => {
+                let error_code = program.fetch_4()? as i32;
+                self.error_code = self.decode_error_code(error_code)?;
+            },
+
+            ByteCodes::SetError => {
+                let error_code = self.opstack.pop_4()? as i32;
+                self.error_code = self.decode_error_code(error_code)?;
+            },             
+
+            ByteCodes::Duplicate1 => {
+                self.opstack.push_1(
+                    self.opstack.peek_1()?
+                )?;
+            },
+
+            ByteCodes::Duplicate2 => {
+                self.opstack.push_2(
+                    self.opstack.peek_2()?
+                )?;
+            },
+
+            ByteCodes::Duplicate4 => {
+                self.opstack.push_4(
+                    self.opstack.peek_4()?
+                )?;
+            },
+
+            ByteCodes::Duplicate8 => {
+                self.opstack.push_8(
+                    self.opstack.peek_8()?
+                )?;
+            },
+
+            ByteCodes::DuplicateBytes => {
+                let count = self.opstack.pop_8()? as usize;
+                let bytes = self.opstack.peek_bytes(count)?.as_ptr();
+                self.opstack.push_from(bytes, count)?;
+            },
 
-                ByteCodes::JumpConst => {
-                    let target = VirtualAddress(program.fetch_8() as usize);
+            ByteCodes::Swap1 => {
+                self.opstack.roll(1, 1)?;
+            },
+            ByteCodes::Swap2 => {
+                self.opstack.roll(2, 1)?;
+            },
+            ByteCodes::Swap4 => {
+                self.opstack.roll(4, 1)?;
+            },
+            ByteCodes::Swap8 => {
+                self.opstack.roll(8, 1)?;
+            },
+            ByteCodes::Over1 => {
+                self.opstack.pick(1, 1)?;
+            },
+            ByteCodes::Over2 => {
+                self.opstack.pick(2, 1)?;
+            },
+            ByteCodes::Over4 => {
+                self.opstack.pick(4, 1)?;
+            },
+            ByteCodes::Over8 => {
+                self.opstack.pick(8, 1)?;
+            },
+            ByteCodes::Rotate1 => {
+                self.opstack.roll(1, 2)?;
+            },
+            ByteCodes::Rotate2 => {
+                self.opstack.roll(2, 2)?;
+            },
+            ByteCodes::Rotate4 => {
+                self.opstack.roll(4, 2)?;
+            },
+            ByteCodes::Rotate8 => {
+                self.opstack.roll(8, 2)?;
+            },
+            ByteCodes::Pick1 => {
+                let index = program.fetch_1()? as usize;
+                self.opstack.pick(1, index)?;
+            },
+            ByteCodes::Pick2 => {
+                let index = program.fetch_1()? as usize;
+                self.opstack.pick(2, index)?;
+            },
+            ByteCodes::Pick4 => {
+                let index = program.fetch_1()? as usize;
+                self.opstack.pick(4, index)?;
+            },
+            ByteCodes::Pick8 => {
+                let index = program.fetch_1()? as usize;
+                self.opstack.pick(8, index)?;
+            },
+            ByteCodes::Roll1 => {
+                let index = program.fetch_1()? as usize;
+                self.opstack.roll(1, index)?;
+            },
+            ByteCodes::Roll2 => {
+                let index = program.fetch_1()? as usize;
+                self.opstack.roll(2, index)?;
+            },
+            ByteCodes::Roll4 => {
+                let index = program.fetch_1()? as usize;
+                self.opstack.roll(4, index)?;
+            },
+            ByteCodes::Roll8 => {
+                let index = program.fetch_1()? as usize;
+                self.opstack.roll(8, index)?;
+            },
+
+            ByteCodes::JumpConst => {
+                let target = VirtualAddress(program.fetch_8()? as usize);
+                program.jump_to(target);
+            },
+
+            ByteCodes::Jump => {
+                let target = VirtualAddress(self.opstack.pop_8()? as usize);
+                program.jump_to(target);
+            },
+
+            ByteCodes::JumpNotZeroConst1 => {
+                let target = VirtualAddress(program.fetch_8()? as usize);
+                let condition = self.opstack.pop_1()?;
+                if condition != 0 {
                     program.jump_to(target);
-                },
+                }
+            },
 
-                ByteCodes::Jump => {
-                    let target = VirtualAddress(self.opstack.pop_8() as usize);
+            ByteCodes::JumpNotZeroConst2 => {
+                let target = VirtualAddress(program.fetch_8()? as usize);
+                let condition = self.opstack.pop_2()?;
+                if condition != 0 {
                     program.jump_to(target);
-                },
+                }
+            },
 
-                ByteCodes::JumpNotZeroConst1 => {
-                    let target = VirtualAddress(program.fetch_8() as usize);
-                    let condition = self.opstack.pop_1();
-                    if condition != 0 {
-                        program.jump_to(target);
-                    }
-                },
+            ByteCodes::JumpNotZeroConst4 => {
+                let target = VirtualAddress(program.fetch_8()? as usize);
+                let condition = self.opstack.pop_4()?;
+                if condition != 0 {
+                    program.jump_to(target);
+                }
+            },
 
-                ByteCodes::JumpNotZeroConst2 => {
-                    let target = VirtualAddress(program.fetch_8() as usize);
-                    let condition = self.opstack.pop_2();
-                    if condition != 0 {
-                        program.jump_to(target);
-                    }
-                },
+            ByteCodes::JumpNotZeroConst8 => {
+                let target = VirtualAddress(program.fetch_8()? as usize);
+                let condition = self.opstack.pop_8()?;
+                if condition != 0 {
+                    program.jump_to(target);
+                }
+            },
 
-                ByteCodes::JumpNotZeroConst4 => {
-                    let target = VirtualAddress(program.fetch_8() as usize);
-                    let condition = self.opstack.pop_4();
-                    if condition != 0 {
-                        program.jump_to(target);
-                    }
-                },
+            ByteCodes::JumpNotZero1 => {
+                let target = VirtualAddress(self.opstack.pop_8()? as usize);
+                let condition = self.opstack.pop_1()?;
+                if condition != 0 {
+                    program.jump_to(target);
+                }
+            },
 
-                ByteCodes::JumpNotZeroConst8 => {
-                    let target = VirtualAddress(program.fetch_8() as usize);
-                    let condition = self.opstack.pop_8();
-                    if condition != 0 {
-                        program.jump_to(target);
-                    }
-                },
+            ByteCodes::JumpNotZero2 => {
+                let target = VirtualAddress(self.opstack.pop_8()? as usize);
+                let condition = self.opstack.pop_2()?;
+                if condition != 0 {
+                    program.jump_to(target);
+                }
+            },
 
-                ByteCodes::JumpNotZero1 => {
-                    let target = VirtualAddress(self.opstack.pop_8() as usize);
-                    let condition = self.opstack.pop_1();
-                    if condition != 0 {
-                        program.jump_to(target);
-                    }
-                },
+            ByteCodes::JumpNotZero4 => {
+                let target = VirtualAddress(self.opstack.pop_8()? as usize);
+                let condition = self.opstack.pop_4()?;
+                if condition != 0 {
+                    program.jump_to(target);
+                }
+            },
 
-                ByteCodes::JumpNotZero2 => {
-                    let target = VirtualAddress(self.opstack.pop_8() as usize);
-                    let condition = self.opstack.pop_2();
-                    if condition != 0 {
-                        program.jump_to(target);
-                    }
-                },
+            ByteCodes::JumpNotZero8 => {
+                let target = VirtualAddress(self.opstack.pop_8()? as usize);
+                let condition = self.opstack.pop_8()?;
+                if condition != 0 {
+                    program.jump_to(target);
+                }
+            },
 
-                ByteCodes::JumpNotZero4 => {
-                    let target = VirtualAddress(self.opstack.pop_8() as usize);
-                    let condition = self.opstack.pop_4();
-                    if condition != 0 {
-                        program.jump_to(target);
-                    }
-                },
+            ByteCodes::JumpZeroConst1 => {
+                let target = VirtualAddress(program.fetch_8()? as usize);
+                let condition = self.opstack.pop_1()?;
+                if condition == 0 {
+                    program.jump_to(target);
+                }
+            },
 
-                ByteCodes::JumpNotZero8 => {
-                    let target = VirtualAddress(self.opstack.pop_8() as usize);
-                    let condition = self.opstack.pop_8();
-                    if condition != 0 {
-                        program.jump_to(target);
-                    }
-                },
+            ByteCodes::JumpZeroConst2 => {
+                let target = VirtualAddress(program.fetch_8()? as usize);
+                let condition = self.opstack.pop_2()?;
+                if condition == 0 {
+                    program.jump_to(target);
+                }
+            },
 
-                ByteCodes::JumpZeroConst1 => {
-                    let target = VirtualAddress(program.fetch_8() as usize);
-                    let condition = self.opstack.pop_1();
-                    if condition == 0 {
-                        program.jump_to(target);
-                    }
-                },
+            ByteCodes::JumpZeroConst4 => {
+                let target = VirtualAddress(program.fetch_8()? as usize);
+                let condition = self.opstack.pop_4()?;
+                if condition == 0 {
+                    program.jump_to(target);
+                }
+            },
 
-                ByteCodes::JumpZeroConst2 => {
-                    let target = VirtualAddress(program.fetch_8() as usize);
-                    let condition = self.opstack.pop_2();
-                    if condition == 0 {
-                        program.jump_to(target);
-                    }
-                },
+            ByteCodes::JumpZeroConst8 => {
+                let target = VirtualAddress(program.fetch_8()? as usize);
+                let condition = self.opstack.pop_8()?;
+                if condition == 0 {
+                    program.jump_to(target);
+                }
+            },
 
-                ByteCodes::JumpZeroConst4 => {
-                    let target = VirtualAddress(program.fetch_8() as usize);
-                    let condition = self.opstack.pop_4();
-                    if condition == 0 {
-                        program.jump_to(target);
-                    }
-                },
+            ByteCodes::JumpZero1 => {
+                let target = VirtualAddress(self.opstack.pop_8()? as usize);
+                let condition = self.opstack.pop_1()?;
+                if condition == 0 {
+                    program.jump_to(target);
+                }
+            },
 
-                ByteCodes::JumpZeroConst8 => {
-                    let target = VirtualAddress(program.fetch_8() as usize);
-                    let condition = self.opstack.pop_8();
-                    if condition == 0 {
-                        program.jump_to(target);
-                    }
-                },
+            ByteCodes::JumpZero2 => {
+                let target = VirtualAddress(self.opstack.pop_8()? as usize);
+                let condition = self.opstack.pop_2()?;
+                if condition == 0 {
+                    program.jump_to(target);
+                }
+            },
 
-                ByteCodes::JumpZero1 => {
-                    let target = VirtualAddress(self.opstack.pop_8() as usize);
-                    let condition = self.opstack.pop_1();
-                    if condition == 0 {
-                        program.jump_to(target);
-                    }
-                },
+            ByteCodes::JumpZero4 => {
+                let target = VirtualAddress(self.opstack.pop_8()? as usize);
+                let condition = self.opstack.pop_4()?;
+                if condition == 0 {
+                    program.jump_to(target);
+                }
+            },
 
-                ByteCodes::JumpZero2 => {
-                    let target = VirtualAddress(self.opstack.pop_8() as usize);
-                    let condition = self.opstack.pop_2();
-                    if condition == 0 {
-                        program.jump_to(target);
-                    }
-                },
+            ByteCodes::JumpZero8 => {
+                let target = VirtualAddress(self.opstack.pop_8()? as usize);
+                let condition = self.opstack.pop_8()?;
+                if condition == 0 {
+                    program.jump_to(target);
+                }
+            },
 
-                ByteCodes::JumpZero4 => {
-                    let target = VirtualAddress(self.opstack.pop_8() as usize);
-                    let condition = self.opstack.pop_4();
-                    if condition == 0 {
-                        program.jump_to(target);
-                    }
-                },
+            ByteCodes::JumpError => {
+                let target = VirtualAddress(self.opstack.pop_8()? as usize);
+                if !matches!(self.error_code, ErrorCodes::NoError) {
+                    program.jump_to(target);
+                }
+            },
 
-                ByteCodes::JumpZero8 => {
-                    let target = VirtualAddress(self.opstack.pop_8() as usize);
-                    let condition = self.opstack.pop_8();
-                    if condition == 0 {
-                        program.jump_to(target);
-                    }
-                },
+            ByteCodes::JumpNoError => {
+                let target = VirtualAddress(self.opstack.pop_8()? as usize);
+                if matches!(self.error_code, ErrorCodes::NoError) {
+                    program.jump_to(target);
+                }
+            },
 
-                ByteCodes::JumpError => {
-                    let target = VirtualAddress(self.opstack.pop_8() as usize);
-                    if !matches!(self.error_code, ErrorCodes::NoError) {
-                        program.jump_to(target);
-                    }
-                },
+            ByteCodes::JumpErrorConst => {
+                let target = VirtualAddress(program.fetch_8()? as usize);
+                if !matches!(self.error_code, ErrorCodes::NoError) {
+                    program.jump_to(target);
+                }
+            },
 
-                ByteCodes::JumpNoError => {
-                    let target = VirtualAddress(self.opstack.pop_8() as usize);
-                    if matches!(self.error_code, ErrorCodes::NoError) {
-                        program.jump_to(target);
-                    }
-                },
+            ByteCodes::JumpNoErrorConst => {
+                let target = VirtualAddress(program.fetch_8()? as usize);
+                if matches!(self.error_code, ErrorCodes::NoError) {
+                    program.jump_to(target);
+                }
+            },
 
-                ByteCodes::JumpErrorConst => {
-                    let target = VirtualAddress(program.fetch_8() as usize);
-                    if !matches!(self.error_code, ErrorCodes::NoError) {
-                        program.jump_to(target);
-                    }
-                },
+            ByteCodes::Call => {
+                let call_site = VirtualAddress(program.program_counter().0 - INSTRUCTION_SIZE);
+                let target = VirtualAddress(program.fetch_8()? as usize);
+                // Push the return address (the instruction next to the current call instruction) onto the call stack
+                self.push_call_frame(call_site, program.program_counter())?;
+                program.jump_to(target);
+            },
 
-                ByteCodes::JumpNoErrorConst => {
-                    let target = VirtualAddress(program.fetch_8() as usize);
-                    if matches!(self.error_code, ErrorCodes::NoError) {
-                        program.jump_to(target);
-                    }
-                },
+            ByteCodes::CallIndirect => {
+                let call_site = VirtualAddress(program.program_counter().0 - INSTRUCTION_SIZE);
+                let target = VirtualAddress(self.opstack.pop_8()? as usize);
+                self.push_call_frame(call_site, program.program_counter())?;
+                program.jump_to(target);
+            },
+
+            ByteCodes::TailCall => {
+                // The callee reuses the current call frame and will return directly to the current caller.
+                let target = VirtualAddress(program.fetch_8()? as usize);
+                program.jump_to(target);
+            },
+
+            ByteCodes::Return => {
+                let frame = self.call_stack.pop().ok_or(FaultKind::CallStackUnderflow)?;
+                program.jump_to(frame.return_address);
+            },
+
+            ByteCodes::Enter => {
+                let size = program.fetch_8()? as usize;
+                // Save the caller's frame pointer below the new frame
+                self.opstack.push_8(self.frame_pointer as u64)?;
+                self.frame_pointer = self.opstack.depth();
+                self.opstack.push_by(size)?;
+            },
+            ByteCodes::Leave => {
+                self.opstack.pop_to_depth(self.frame_pointer)?;
+                self.frame_pointer = self.opstack.pop_8()? as usize;
+            },
+            ByteCodes::LoadLocal1 => {
+                let offset = program.fetch_8()? as usize;
+                let depth = self.local_depth(offset, 1)?;
+                self.opstack.load_at(depth, 1)?;
+            },
+            ByteCodes::LoadLocal2 => {
+                let offset = program.fetch_8()? as usize;
+                let depth = self.local_depth(offset, 2)?;
+                self.opstack.load_at(depth, 2)?;
+            },
+            ByteCodes::LoadLocal4 => {
+                let offset = program.fetch_8()? as usize;
+                let depth = self.local_depth(offset, 4)?;
+                self.opstack.load_at(depth, 4)?;
+            },
+            ByteCodes::LoadLocal8 => {
+                let offset = program.fetch_8()? as usize;
+                let depth = self.local_depth(offset, 8)?;
+                self.opstack.load_at(depth, 8)?;
+            },
+            ByteCodes::StoreLocal1 => {
+                let offset = program.fetch_8()? as usize;
+                let depth = self.local_depth(offset, 1)?;
+                self.opstack.store_at(depth, 1)?;
+            },
+            ByteCodes::StoreLocal2 => {
+                let offset = program.fetch_8()? as usize;
+                let depth = self.local_depth(offset, 2)?;
+                self.opstack.store_at(depth, 2)?;
+            },
+            ByteCodes::StoreLocal4 => {
+                let offset = program.fetch_8()? as usize;
+                let depth = self.local_depth(offset, 4)?;
+                self.opstack.store_at(depth, 4)?;
+            },
+            ByteCodes::StoreLocal8 => {
+                let offset = program.fetch_8()? as usize;
+                let depth = self.local_depth(offset, 8)?;
+                self.opstack.store_at(depth, 8)?;
+            },
+            ByteCodes::LoadArg1 => {
+                let offset = program.fetch_8()? as usize;
+                let depth = self.arg_depth(offset)?;
+                self.opstack.load_at(depth, 1)?;
+            },
+            ByteCodes::LoadArg2 => {
+                let offset = program.fetch_8()? as usize;
+                let depth = self.arg_depth(offset)?;
+                self.opstack.load_at(depth, 2)?;
+            },
+            ByteCodes::LoadArg4 => {
+                let offset = program.fetch_8()? as usize;
+                let depth = self.arg_depth(offset)?;
+                self.opstack.load_at(depth, 4)?;
+            },
+            ByteCodes::LoadArg8 => {
+                let offset = program.fetch_8()? as usize;
+                let depth = self.arg_depth(offset)?;
+                self.opstack.load_at(depth, 8)?;
+            },
+            ByteCodes::StoreArg1 => {
+                let offset = program.fetch_8()? as usize;
+                let depth = self.arg_depth(offset)?;
+                self.opstack.store_at(depth, 1)?;
+            },
+            ByteCodes::StoreArg2 => {
+                let offset = program.fetch_8()? as usize;
+                let depth = self.arg_depth(offset)?;
+                self.opstack.store_at(depth, 2)?;
+            },
+            ByteCodes::StoreArg4 => {
+                let offset = program.fetch_8()? as usize;
+                let depth = self.arg_depth(offset)?;
+                self.opstack.store_at(depth, 4)?;
+            },
+            ByteCodes::StoreArg8 => {
+                let offset = program.fetch_8()? as usize;
+                let depth = self.arg_depth(offset)?;
+                self.opstack.store_at(depth, 8)?;
+            },
+
+            ByteCodes::Nop => { /* Do nothing */ },
+
+        }
+
+        Ok(None)
+    }
+
+
+    /// Write the output of a printing interrupt, enforcing the output limit.
+    fn print(&mut self, output: &str) -> Result<(), FaultKind> {
+        self.print_bytes(output.as_bytes())
+    }
 
-                ByteCodes::Call => {
-                    let target = VirtualAddress(program.fetch_8() as usize);
-                    // Push the return address (the instruction next to the current call instruction)
-                    self.opstack.push_8(program.program_counter().0 as u64);
-                    program.jump_to(target);
-                },
 
-                ByteCodes::Nop => { /* Do nothing */ },
+    /// Write raw bytes supplied by the program, which are not required to be valid UTF-8.
+    fn print_bytes(&mut self, output: &[u8]) -> Result<(), FaultKind> {
+        self.output_bytes += output.len();
+        if self.limits.max_output_bytes.is_some_and(|max| self.output_bytes > max) {
+            return Err(FaultKind::LimitExceeded(Limit::Output));
+        }
+        if self.output.write_all(output).is_err() {
+            self.error_code = ErrorCodes::GenericError;
+        }
+        Ok(())
+    }
+
+
+    /// Print the integer of `size` bytes on top of the stack.
+    fn print_integer(&mut self, size: usize, conversion: Conversion) -> Result<(), FaultKind> {
+        let mut value = [0; 8];
+        value[..size].copy_from_slice(self.opstack.pop_bytes(size)?);
+        self.print(&format::format_integer(u64::from_le_bytes(value), size, conversion))
+    }
+
+
+    /// Print the format string on top of the stack, replacing its conversion specifications with the arguments below it.
+    /// An invalid format string or argument sets the `InvalidArgument` error code and nothing is printed.
+    fn print_format(&mut self) -> Result<(), FaultKind> {
 
+        // The format string is only peeked, so that an invalid one leaves the whole stack untouched
+        let length = self.opstack.peek_8()? as usize;
+        let format_addr = u64::from_le_bytes(self.opstack.peek_bytes(16)?[8..].try_into().unwrap());
+        let format = unsafe {
+            slice::from_raw_parts(self.translate_read(format_addr, length)?, length)
+        };
+        let Some(pieces) = str::from_utf8(format).ok().and_then(format::parse) else {
+            self.error_code = ErrorCodes::InvalidArgument;
+            return Ok(());
+        };
+
+        // Pop the format string and all the arguments at once, so that the stack is never left partially consumed.
+        // The arguments are pushed in order, so the last one is on top of the stack
+        let arguments_size: usize = pieces.iter()
+            .map(|piece| if let Piece::Spec(spec) = piece { spec.argument_size() } else { 0 })
+            .sum();
+        let popped = self.opstack.pop_bytes(16 + arguments_size)?.to_vec();
+        let mut arguments = Vec::new();
+        let mut rest = &popped[16..];
+        for piece in pieces.iter().rev() {
+            if let Piece::Spec(spec) = piece {
+                let (argument, after) = rest.split_at(spec.argument_size());
+                arguments.push(argument);
+                rest = after;
             }
         }
 
-        // The program has no more instruction to execute and an exit code was not provided.
-        // Assume the program ended successfully.
-        self.error_code
+        let mut output = String::new();
+        for piece in &pieces {
+            let spec = match piece {
+                Piece::Literal(literal) => {
+                    output.push_str(literal);
+                    continue;
+                },
+                Piece::Spec(spec) => spec
+            };
+            let argument = arguments.pop().expect("Every specification should have an argument");
+
+            let text = if spec.conversion == Conversion::String {
+                // The length was pushed after the address, so it comes first in the popped bytes
+                let length = u64::from_le_bytes(argument[..8].try_into().unwrap()) as usize;
+                let address = u64::from_le_bytes(argument[8..].try_into().unwrap());
+                let bytes = unsafe {
+                    slice::from_raw_parts(self.translate_read(address, length)?, length)
+                };
+                Some(String::from_utf8_lossy(bytes).into_owned())
+            } else {
+                spec.format_number(argument)
+            };
+
+            let Some(text) = text else {
+                self.error_code = ErrorCodes::InvalidArgument;
+                return Ok(());
+            };
+            output.push_str(&spec.pad(text));
+        }
+
+        self.print(&output)
+    }
+
+
+
+    /// Format the integer of `size` bytes below the buffer address and size on top of the stack into the buffer, and push the length of the text.
+    fn format_integer_to_buffer(&mut self, size: usize, conversion: Conversion) -> Result<(), FaultKind> {
+        let buf_size = self.opstack.pop_8()? as usize;
+        let buf_addr = self.opstack.pop_8()?;
+        let mut value = [0; 8];
+        value[..size].copy_from_slice(self.opstack.pop_bytes(size)?);
+
+        let text = format::format_integer(u64::from_le_bytes(value), size, conversion);
+        let length = self.copy_to_buffer(text.as_bytes(), buf_addr, buf_size)?;
+        self.opstack.push_8(length as u64)
+    }
+
+
+    /// Format the float of `size` bytes with the precision byte below the buffer address and size on top of the stack into the buffer,
+    /// and push the length of the text.
+    fn format_float_to_buffer(&mut self, size: usize) -> Result<(), FaultKind> {
+        let buf_size = self.opstack.pop_8()? as usize;
+        let buf_addr = self.opstack.pop_8()?;
+        let precision = format::float_precision(self.opstack.pop_1()?);
+        let mut value = [0; 8];
+        value[..size].copy_from_slice(self.opstack.pop_bytes(size)?);
+
+        let text = format::format_float(u64::from_le_bytes(value), size, precision);
+        let length = self.copy_to_buffer(text.as_bytes(), buf_addr, buf_size)?;
+        self.opstack.push_8(length as u64)
+    }
+
+
+    /// Parse the string on top of the stack as a number of `size` bytes and push it.
+    /// Malformed input pushes zero and sets the error code.
+    fn parse_number(&mut self, size: usize, conversion: Conversion) -> Result<(), FaultKind> {
+        let length = self.opstack.pop_8()? as usize;
+        let str_addr = self.opstack.pop_8()?;
+        let bytes = unsafe {
+            slice::from_raw_parts(self.translate_read(str_addr, length)?, length)
+        };
+
+        let value = str::from_utf8(bytes).map_err(|_| ErrorCodes::InvalidArgument).and_then(|text| match conversion {
+            Conversion::Float => format::parse_float(text, size),
+            conversion => format::parse_integer(text, size, conversion == Conversion::Signed)
+        });
+        let value = value.unwrap_or_else(|error_code| {
+            self.error_code = error_code;
+            0
+        });
+        self.opstack.push_bytes(&value.to_le_bytes()[..size])
+    }
+
+
+    /// Flush the console output, setting the error code if it fails.
+    /// The output is also flushed before reading input, so that prompts are visible.
+    fn flush_output(&mut self) {
+        if self.output.flush().is_err() {
+            self.error_code = ErrorCodes::GenericError;
+        }
     }
 
 
-    fn handle_interrupt(&mut self, intr_code: Interrupts, program: &mut Program) {
+    fn handle_interrupt(&mut self, intr_code: Interrupts, program: &mut Program) -> Result<(), FaultKind> {
 
         match intr_code {
 
             Interrupts::Print1 => {
-                let value = self.opstack.pop_1();
-                print!("{}", value);
+                let value = self.opstack.pop_1()?;
+                self.print(&value.to_string())?;
             },
             Interrupts::Print2 => {
-                let value = self.opstack.pop_2();
-                print!("{}", value);
+                let value = self.opstack.pop_2()?;
+                self.print(&value.to_string())?;
             },
             Interrupts::Print4 => {
-                let value = self.opstack.pop_4();
-                print!("{}", value);
+                let value = self.opstack.pop_4()?;
+                self.print(&value.to_string())?;
             },
             Interrupts::Print8 => {
-                let value = self.opstack.pop_8();
-                print!("{}", value);
+                let value = self.opstack.pop_8()?;
+                self.print(&value.to_string())?;
             },
             Interrupts::PrintBytes => {
-                let count = self.opstack.pop_8() as usize;
-                let bytes_addr = self.opstack.pop_8() as *const u8;
+                let count = self.opstack.pop_8()? as usize;
+                let bytes_addr = self.opstack.pop_8()?;
                 let bytes = unsafe {
-                    slice::from_raw_parts(bytes_addr, count)
+                    slice::from_raw_parts(self.translate_read(bytes_addr, count)?, count)
                 };
-                print!("{:?}", bytes);
+                self.print(&format!("{:?}", bytes))?;
             },
             Interrupts::PrintChar => {
-                let value = self.opstack.pop_1();
-                print!("{}", value as char);
+                let value = self.opstack.pop_1()?;
+                self.print((value as char).encode_utf8(&mut [0; 4]))?;
             },
             Interrupts::PrintString => {
-                let length = self.opstack.pop_8() as usize;
-                let str_addr = self.opstack.pop_8() as *const u8;
-                // Use unchecked because it's the programmer's responsibility to ensure the string is valid
-                unsafe {
-                    let string = slice::from_raw_parts(str_addr, length);
-                    let string = std::str::from_utf8_unchecked(string);
-                    print!("{}", string);
-                }
+                let length = self.opstack.pop_8()? as usize;
+                let str_addr = self.opstack.pop_8()?;
+                let string = unsafe {
+                    slice::from_raw_parts(self.translate_read(str_addr, length)?, length)
+                };
+                self.print_bytes(string)?;
             },
             Interrupts::PrintStaticBytes => {
-                let count = self.opstack.pop_8() as usize;
-                let bytes_vaddr = VirtualAddress(self.opstack.pop_8() as usize);
-                let bytes = program.get_static_bytes(bytes_vaddr, count);
-                print!("{:?}", bytes);
+                let count = self.opstack.pop_8()? as usize;
+                let bytes_vaddr = VirtualAddress(self.opstack.pop_8()? as usize);
+                let bytes = program.get_static_bytes(bytes_vaddr, count)?;
+                self.print(&format!("{:?}", bytes))?;
             },
             Interrupts::PrintStaticString => {
-                let length = self.opstack.pop_8() as usize;
-                let str_vaddr = VirtualAddress(self.opstack.pop_8() as usize);
-                let string = unsafe {
-                    std::str::from_utf8_unchecked(
-                        program.get_static_bytes(str_vaddr, length)
-                    )
-                };
-                print!("{}", string);
+                let length = self.opstack.pop_8()? as usize;
+                let str_vaddr = VirtualAddress(self.opstack.pop_8()? as usize);
+                let string = program.get_static_bytes(str_vaddr, length)?;
+                self.print_bytes(string)?;
             },
             Interrupts::ReadBytes => {
-                let n = self.opstack.pop_8() as usize;
-                let mut buf = Vec::with_capacity(n);
-                if let Err(err) = io::stdin().read_exact(&mut buf) {
+                self.flush_output();
+                let n = self.opstack.pop_8()? as usize;
+                // The bytes end up on the stack, so refuse counts that can't fit before allocating the buffer, even when unchecked
+                if n > self.opstack.free_size() {
+                    return Err(FaultKind::StackOverflow);
+                }
+                let mut buf = vec![0; n];
+                if let Err(err) = self.input.read_exact(&mut buf) {
                    self.error_code = match err.kind() {
                         io::ErrorKind::UnexpectedEof => ErrorCodes::UnexpectedEOF,
                         _ => ErrorCodes::GenericError,
                     }
                 } else {
-                    self.opstack.push_bytes(&buf);
+                    self.opstack.push_bytes(&buf)?;
                 }
             },
             Interrupts::ReadAll => {
+                self.flush_output();
+                // The input ends up on the stack followed by its length, so stop reading once it can't fit, even when unchecked
+                let Some(limit) = self.opstack.free_size().checked_sub(8) else {
+                    return Err(FaultKind::StackOverflow);
+                };
                 let mut buf = Vec::new();
-                match io::stdin().read_to_end(&mut buf) {
+                match self.input.by_ref().take(limit as u64 + 1).read_to_end(&mut buf) {
+                    Ok(bytes_read) if bytes_read > limit => return Err(FaultKind::StackOverflow),
                     Ok(bytes_read) => {
-                        self.opstack.push_bytes(&buf);
-                        self.opstack.push_8(bytes_read as u64);
+                        self.opstack.push_bytes(&buf)?;
+                        self.opstack.push_8(bytes_read as u64)?;
                     },
                     Err(_err) => self.error_code = ErrorCodes::GenericError
                 }
             },
+            Interrupts::Flush => {
+                self.flush_output();
+            },
+            Interrupts::Open => {
+                let flags = self.opstack.pop_1()?;
+                let fd = self.pop_path()?.and_then(|path| self.descriptors.open_file(&path, flags));
+                let fd = self.io_value(fd, 0);
+                self.opstack.push_8(fd)?;
+            },
+            Interrupts::Read => {
+                let count = self.opstack.pop_8()? as usize;
+                let buf_addr = self.opstack.pop_8()?;
+                let fd = self.opstack.pop_8()?;
+                let buf = unsafe {
+                    slice::from_raw_parts_mut(self.translate_write(buf_addr, count)?, count)
+                };
+                let bytes_read = match self.descriptors.reader(fd).and_then(|reader| reader.read(buf).map_err(io_error_code)) {
+                    Ok(0) if count != 0 => Err(ErrorCodes::EOF),
+                    result => result
+                };
+                let bytes_read = self.io_value(bytes_read, 0);
+                self.opstack.push_8(bytes_read as u64)?;
+            },
+            Interrupts::Write => {
+                let count = self.opstack.pop_8()? as usize;
+                let buf_addr = self.opstack.pop_8()?;
+                let fd = self.opstack.pop_8()?;
+                let buf = unsafe {
+                    slice::from_raw_parts(self.translate_read(buf_addr, count)?, count)
+                };
+                let bytes_written = self.descriptors.writer(fd)
+                    .and_then(|writer| writer.write_all(buf).map_err(io_error_code))
+                    .map(|_| count);
+                let bytes_written = self.io_value(bytes_written, 0);
+                self.opstack.push_8(bytes_written as u64)?;
+            },
+            Interrupts::Seek => {
+                let whence = self.opstack.pop_1()?;
+                let offset = self.opstack.pop_8()? as i64;
+                let fd = self.opstack.pop_8()?;
+                let target = match whence {
+                    SEEK_START => Ok(SeekFrom::Start(offset as u64)),
+                    SEEK_CURRENT => Ok(SeekFrom::Current(offset)),
+                    SEEK_END => Ok(SeekFrom::End(offset)),
+                    _ => Err(ErrorCodes::InvalidArgument)
+                };
+                let position = target.and_then(|target| self.descriptors.file(fd)
+                    .and_then(|file| file.seek(target).map_err(io_error_code)));
+                let position = self.io_value(position, 0);
+                self.opstack.push_8(position)?;
+            },
+            Interrupts::Close => {
+                let fd = self.opstack.pop_8()?;
+                let result = self.descriptors.close(fd);
+                self.io_value(result, ());
+            },
+            Interrupts::Stat => {
+                let status = self.pop_path()?.and_then(|path| files::stat(&path));
+                let status = self.io_value(status, FileStatus::default());
+                self.opstack.push_8(status.size)?;
+                self.opstack.push_8(status.modified)?;
+                self.opstack.push_1(status.kind)?;
+            },
+            Interrupts::Unlink => {
+                let result = self.pop_path()?.and_then(|path| fs::remove_file(path).map_err(io_error_code));
+                self.io_value(result, ());
+            },
+            Interrupts::OpenDir => {
+                let fd = self.pop_path()?.and_then(|path| self.descriptors.open_directory(&path));
+                let fd = self.io_value(fd, 0);
+                self.opstack.push_8(fd)?;
+            },
+            Interrupts::ReadDir => {
+                let size = self.opstack.pop_8()? as usize;
+                let buf_addr = self.opstack.pop_8()?;
+                let fd = self.opstack.pop_8()?;
+                let buf = unsafe {
+                    slice::from_raw_parts_mut(self.translate_write(buf_addr, size)?, size)
+                };
+                let length = match self.descriptors.read_directory(fd, buf) {
+                    Ok(length) => length,
+                    Err((error_code, length)) => {
+                        self.error_code = error_code;
+                        length
+                    }
+                };
+                self.opstack.push_8(length as u64)?;
+            },
+            Interrupts::ArgCount => {
+                self.opstack.push_8(self.args.len() as u64)?;
+            },
+            Interrupts::Arg => {
+                let size = self.opstack.pop_8()? as usize;
+                let buf_addr = self.opstack.pop_8()?;
+                let index = self.opstack.pop_8()? as usize;
+                let length = match self.args.get(index).cloned() {
+                    Some(arg) => self.copy_to_buffer(arg.as_bytes(), buf_addr, size)?,
+                    None => {
+                        self.error_code = ErrorCodes::InvalidArgument;
+                        0
+                    }
+                };
+                self.opstack.push_8(length as u64)?;
+            },
+            Interrupts::GetEnv => {
+                let size = self.opstack.pop_8()? as usize;
+                let buf_addr = self.opstack.pop_8()?;
+                let name_length = self.opstack.pop_8()? as usize;
+                let name_addr = self.opstack.pop_8()?;
+                let name = unsafe {
+                    slice::from_raw_parts(self.translate_read(name_addr, name_length)?, name_length)
+                };
+                let value = str::from_utf8(name).ok().and_then(|name| self.env.get(name)).cloned();
+                let length = match value {
+                    Some(value) => self.copy_to_buffer(value.as_bytes(), buf_addr, size)?,
+                    None => {
+                        self.error_code = ErrorCodes::VariableNotFound;
+                        0
+                    }
+                };
+                self.opstack.push_8(length as u64)?;
+            },
+            Interrupts::MonotonicTime => {
+                let nanos = self.clock_origin.elapsed().as_nanos() as u64;
+                self.opstack.push_8(nanos)?;
+            },
+            Interrupts::UnixTime => {
+                // A host clock set before the Unix epoch is reported as the epoch
+                let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_nanos() as u64);
+                self.opstack.push_8(nanos)?;
+            },
+            Interrupts::Sleep => {
+                let nanos = self.opstack.pop_8()?;
+                self.flush_output();
+                self.sleep(Duration::from_nanos(nanos))?;
+            },
+            Interrupts::Random => {
+                let value = self.random.next_u64();
+                self.opstack.push_8(value)?;
+            },
+            Interrupts::Spawn => {
+                let flags = self.opstack.pop_1()?;
+                let argc = self.opstack.pop_8()? as usize;
+                let argv_addr = self.opstack.pop_8()?;
+                let spawned = if self.allow_spawn {
+                    // The process may write to the same terminal, so the output printed so far must come first
+                    self.flush_output();
+                    self.read_argv(argv_addr, argc)?.and_then(|argv| self.descriptors.spawn(&argv, flags))
+                } else {
+                    Err(ErrorCodes::PermissionDenied)
+                };
+                let spawned = self.io_value(spawned, SpawnedProcess::default());
+                self.opstack.push_8(spawned.process)?;
+                self.opstack.push_8(spawned.stdin)?;
+                self.opstack.push_8(spawned.stdout)?;
+            },
+            Interrupts::Wait => {
+                let fd = self.opstack.pop_8()?;
+                let exit_code = self.descriptors.wait(fd);
+                let exit_code = self.io_value(exit_code, 0);
+                self.opstack.push_4(exit_code as u32)?;
+            },
+            Interrupts::Listen => {
+                let fd = self.pop_string()?.and_then(|address| self.descriptors.listen(&address));
+                let fd = self.io_value(fd, 0);
+                self.opstack.push_8(fd)?;
+            },
+            Interrupts::Accept => {
+                let fd = self.opstack.pop_8()?;
+                let stream = self.descriptors.accept(fd);
+                let stream = self.io_value(stream, 0);
+                self.opstack.push_8(stream)?;
+            },
+            Interrupts::Connect => {
+                let fd = self.pop_string()?.and_then(|address| self.descriptors.connect(&address));
+                let fd = self.io_value(fd, 0);
+                self.opstack.push_8(fd)?;
+            },
+            Interrupts::Bind => {
+                let fd = self.pop_string()?.and_then(|address| self.descriptors.bind(&address));
+                let fd = self.io_value(fd, 0);
+                self.opstack.push_8(fd)?;
+            },
+            Interrupts::Send => {
+                let count = self.opstack.pop_8()? as usize;
+                let buf_addr = self.opstack.pop_8()?;
+                let fd = self.opstack.pop_8()?;
+                let buf = unsafe {
+                    slice::from_raw_parts(self.translate_read(buf_addr, count)?, count)
+                };
+                let bytes_sent = self.descriptors.send(fd, buf).map(|_| count);
+                let bytes_sent = self.io_value(bytes_sent, 0);
+                self.opstack.push_8(bytes_sent as u64)?;
+            },
+            Interrupts::SendTo => {
+                let address = self.pop_string()?;
+                let count = self.opstack.pop_8()? as usize;
+                let buf_addr = self.opstack.pop_8()?;
+                let fd = self.opstack.pop_8()?;
+                let buf = unsafe {
+                    slice::from_raw_parts(self.translate_read(buf_addr, count)?, count)
+                };
+                let bytes_sent = address.and_then(|address| self.descriptors.send_to(fd, buf, &address));
+                let bytes_sent = self.io_value(bytes_sent, 0);
+                self.opstack.push_8(bytes_sent as u64)?;
+            },
+            Interrupts::Recv => {
+                let size = self.opstack.pop_8()? as usize;
+                let buf_addr = self.opstack.pop_8()?;
+                let fd = self.opstack.pop_8()?;
+                let buf = unsafe {
+                    slice::from_raw_parts_mut(self.translate_write(buf_addr, size)?, size)
+                };
+                let bytes_received = self.descriptors.recv(fd, buf);
+                let bytes_received = self.io_value(bytes_received, 0);
+                self.opstack.push_8(bytes_received as u64)?;
+            },
+            Interrupts::RecvFrom => {
+                let address_size = self.opstack.pop_8()? as usize;
+                let address_addr = self.opstack.pop_8()?;
+                let size = self.opstack.pop_8()? as usize;
+                let buf_addr = self.opstack.pop_8()?;
+                let fd = self.opstack.pop_8()?;
+                let buf = unsafe {
+                    slice::from_raw_parts_mut(self.translate_write(buf_addr, size)?, size)
+                };
+                let (bytes_received, address_length) = match self.descriptors.recv_from(fd, buf) {
+                    Ok((bytes_received, sender)) => (bytes_received, self.copy_to_buffer(sender.to_string().as_bytes(), address_addr, address_size)?),
+                    Err(error_code) => {
+                        self.error_code = error_code;
+                        (0, 0)
+                    }
+                };
+                self.opstack.push_8(bytes_received as u64)?;
+                self.opstack.push_8(address_length as u64)?;
+            },
+            Interrupts::LocalPort => {
+                let fd = self.opstack.pop_8()?;
+                let port = self.descriptors.local_port(fd);
+                let port = self.io_value(port, 0);
+                self.opstack.push_2(port)?;
+            },
+            Interrupts::PrintInt1 => self.print_integer(1, Conversion::Signed)?,
+            Interrupts::PrintInt2 => self.print_integer(2, Conversion::Signed)?,
+            Interrupts::PrintInt4 => self.print_integer(4, Conversion::Signed)?,
+            Interrupts::PrintInt8 => self.print_integer(8, Conversion::Signed)?,
+            Interrupts::PrintHex1 => self.print_integer(1, Conversion::Hex)?,
+            Interrupts::PrintHex2 => self.print_integer(2, Conversion::Hex)?,
+            Interrupts::PrintHex4 => self.print_integer(4, Conversion::Hex)?,
+            Interrupts::PrintHex8 => self.print_integer(8, Conversion::Hex)?,
+            Interrupts::PrintBinary1 => self.print_integer(1, Conversion::Binary)?,
+            Interrupts::PrintBinary2 => self.print_integer(2, Conversion::Binary)?,
+            Interrupts::PrintBinary4 => self.print_integer(4, Conversion::Binary)?,
+            Interrupts::PrintBinary8 => self.print_integer(8, Conversion::Binary)?,
+            Interrupts::PrintFloat4 => {
+                let precision = format::float_precision(self.opstack.pop_1()?);
+                let value = self.opstack.pop_4()?;
+                self.print(&format::format_float(value as u64, 4, precision))?;
+            },
+            Interrupts::PrintFloat8 => {
+                let precision = format::float_precision(self.opstack.pop_1()?);
+                let value = self.opstack.pop_8()?;
+                self.print(&format::format_float(value, 8, precision))?;
+            },
+            Interrupts::PrintCodePoint => {
+                let code_point = self.opstack.pop_4()?;
+                match char::from_u32(code_point) {
+                    Some(c) => self.print(c.encode_utf8(&mut [0; 4]))?,
+                    None => self.error_code = ErrorCodes::InvalidArgument
+                }
+            },
+            Interrupts::PrintFormat => self.print_format()?,
+            Interrupts::FormatUint1 => self.format_integer_to_buffer(1, Conversion::Unsigned)?,
+            Interrupts::FormatUint2 => self.format_integer_to_buffer(2, Conversion::Unsigned)?,
+            Interrupts::FormatUint4 => self.format_integer_to_buffer(4, Conversion::Unsigned)?,
+            Interrupts::FormatUint8 => self.format_integer_to_buffer(8, Conversion::Unsigned)?,
+            Interrupts::FormatInt1 => self.format_integer_to_buffer(1, Conversion::Signed)?,
+            Interrupts::FormatInt2 => self.format_integer_to_buffer(2, Conversion::Signed)?,
+            Interrupts::FormatInt4 => self.format_integer_to_buffer(4, Conversion::Signed)?,
+            Interrupts::FormatInt8 => self.format_integer_to_buffer(8, Conversion::Signed)?,
+            Interrupts::FormatFloat4 => self.format_float_to_buffer(4)?,
+            Interrupts::FormatFloat8 => self.format_float_to_buffer(8)?,
+            Interrupts::ParseUint1 => self.parse_number(1, Conversion::Unsigned)?,
+            Interrupts::ParseUint2 => self.parse_number(2, Conversion::Unsigned)?,
+            Interrupts::ParseUint4 => self.parse_number(4, Conversion::Unsigned)?,
+            Interrupts::ParseUint8 => self.parse_number(8, Conversion::Unsigned)?,
+            Interrupts::ParseInt1 => self.parse_number(1, Conversion::Signed)?,
+            Interrupts::ParseInt2 => self.parse_number(2, Conversion::Signed)?,
+            Interrupts::ParseInt4 => self.parse_number(4, Conversion::Signed)?,
+            Interrupts::ParseInt8 => self.parse_number(8, Conversion::Signed)?,
+            Interrupts::ParseFloat4 => self.parse_number(4, Conversion::Float)?,
+            Interrupts::ParseFloat8 => self.parse_number(8, Conversion::Float)?,
 
         }
+
+        Ok(())
     }
 
 }
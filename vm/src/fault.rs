@@ -0,0 +1,160 @@
+use std::fmt;
+
+use vmlib::{Address, ByteCodes, ErrorCodes, Opcode, VirtualAddress};
+
+use crate::limits::Limit;
+
+
+/// The reason why the execution of a program was aborted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultKind {
+    /// A push would move the top of the stack past the stack bottom.
+    StackOverflow,
+    /// A pop or peek would read past the top of the stack.
+    StackUnderflow,
+    /// The program counter points outside the program space.
+    ProgramCounterOutOfRange,
+    /// An instruction operand extends past the end of the program space.
+    TruncatedOperand,
+    /// A static data access extends past the end of the program space.
+    StaticOutOfRange,
+    /// A data segment access extends past the end of the data segment.
+    DataOutOfRange,
+    /// The fetched byte is not a valid instruction code.
+    UnknownOpcode,
+    /// The interrupt code is not a valid interrupt.
+    UnknownInterrupt(u8),
+    /// The value is not a valid error code.
+    InvalidErrorCode(i32),
+    /// A call would exceed the maximum call stack depth.
+    CallStackOverflow,
+    /// A return was executed with an empty call stack.
+    CallStackUnderflow,
+    /// A frame-relative access falls outside of the operation stack.
+    FrameOutOfRange,
+    /// The metadata appended to the program code cannot be decoded.
+    MalformedMetadata,
+    /// The program is too short to contain the jump to its entry point.
+    MissingEntryPoint,
+    /// A memory access falls outside of the mapped regions of the virtual address space.
+    InvalidMemoryAccess(Address),
+    /// A memory access writes to a read-only region of the virtual address space.
+    ReadOnlyMemoryWrite(Address),
+    /// A memory access extends past the end of a heap block. Only detected in sanitizer mode.
+    HeapBufferOverflow(Address),
+    /// A memory access falls inside a freed heap block. Only detected in sanitizer mode.
+    UseAfterFree(Address),
+    /// Operation stack bytes are read before being written. Only detected in sanitizer mode.
+    UninitializedStackRead,
+    /// A resource limit set for the run was exceeded.
+    LimitExceeded(Limit),
+    /// No host function is registered for the number called by `hostcall`.
+    UnknownHostFunction(u32),
+    /// The host called a function that the loaded program doesn't export.
+    UnknownExport,
+    /// The program exited with the given code before the function called by the host returned.
+    ExitDuringCall(ErrorCodes),
+    /// The function called by the host ran past the end of the program without returning.
+    MissingReturn,
+}
+
+impl fmt::Display for FaultKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FaultKind::StackOverflow => write!(f, "operation stack overflow"),
+            FaultKind::StackUnderflow => write!(f, "operation stack underflow"),
+            FaultKind::ProgramCounterOutOfRange => write!(f, "program counter out of range"),
+            FaultKind::TruncatedOperand => write!(f, "truncated instruction operand"),
+            FaultKind::StaticOutOfRange => write!(f, "static data access out of range"),
+            FaultKind::DataOutOfRange => write!(f, "data segment access out of range"),
+            FaultKind::UnknownOpcode => write!(f, "unknown instruction code"),
+            FaultKind::UnknownInterrupt(code) => write!(f, "unknown interrupt code {code}"),
+            FaultKind::InvalidErrorCode(code) => write!(f, "invalid error code {code}"),
+            FaultKind::CallStackOverflow => write!(f, "call stack overflow"),
+            FaultKind::CallStackUnderflow => write!(f, "return with an empty call stack"),
+            FaultKind::FrameOutOfRange => write!(f, "frame access out of range"),
+            FaultKind::MalformedMetadata => write!(f, "malformed program metadata"),
+            FaultKind::MissingEntryPoint => write!(f, "missing entry point"),
+            FaultKind::InvalidMemoryAccess(address) => write!(f, "invalid memory access at address {address:#x}"),
+            FaultKind::ReadOnlyMemoryWrite(address) => write!(f, "write to read-only memory at address {address:#x}"),
+            FaultKind::HeapBufferOverflow(address) => write!(f, "heap buffer overflow at address {address:#x}"),
+            FaultKind::UseAfterFree(address) => write!(f, "heap use after free at address {address:#x}"),
+            FaultKind::UninitializedStackRead => write!(f, "read of uninitialized stack memory"),
+            FaultKind::LimitExceeded(limit) => write!(f, "{limit} exceeded"),
+            FaultKind::UnknownHostFunction(id) => write!(f, "unknown host function {id}"),
+            FaultKind::UnknownExport => write!(f, "call to a function that is not exported"),
+            FaultKind::ExitDuringCall(code) => write!(f, "program exited with code {code} during a call from the host"),
+            FaultKind::MissingReturn => write!(f, "called function ran past the end of the program without returning"),
+        }
+    }
+}
+
+
+/// A location in the program space, resolved to the closest preceding symbol if the program has debug symbols.
+#[derive(Debug, Clone)]
+pub struct BacktraceFrame {
+    pub address: VirtualAddress,
+    /// Name of the closest symbol at or before `address` and the offset from it.
+    pub symbol: Option<(String, usize)>,
+}
+
+impl fmt::Display for BacktraceFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#x}", self.address.0)?;
+
+        if let Some((name, offset)) = &self.symbol {
+            write!(f, " in {name}+{offset:#x}")?;
+        }
+
+        Ok(())
+    }
+}
+
+
+/// The chain of active calls at the moment of a fault, most recent first.
+/// The first frame is the faulting instruction, the following frames are the call sites.
+#[derive(Debug, Clone, Default)]
+pub struct Backtrace(pub Vec<BacktraceFrame>);
+
+impl fmt::Display for Backtrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Backtrace (most recent call first):")?;
+
+        for (index, frame) in self.0.iter().enumerate() {
+            write!(f, "\n  #{index} {frame}")?;
+        }
+
+        Ok(())
+    }
+}
+
+
+/// A runtime fault detected while executing a program.
+#[derive(Debug, Clone)]
+pub struct VmFault {
+    pub kind: FaultKind,
+    /// Address of the instruction that caused the fault.
+    pub pc: VirtualAddress,
+    /// Raw code of the instruction that caused the fault.
+    /// This is `None` if the program counter was outside the program space.
+    pub opcode: Option<Opcode>,
+    /// Active call chain at the moment of the fault.
+    pub backtrace: Backtrace,
+}
+
+impl fmt::Display for VmFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "VM fault at address {:#x}: {}", self.pc.0, self.kind)?;
+
+        if let Some(opcode) = self.opcode {
+            match ByteCodes::from_code(opcode) {
+                Some(instruction) => write!(f, " (instruction `{}`)", instruction.asm_name())?,
+                None => write!(f, " (instruction code {opcode:#x})")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for VmFault {}
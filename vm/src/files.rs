@@ -0,0 +1,363 @@
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions, ReadDir};
+use std::io::{self, Read, Write};
+use std::iter::Peekable;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
+use std::path::Path;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::time::UNIX_EPOCH;
+
+use vmlib::ErrorCodes;
+use vmlib::files::{FILE_KIND_DIRECTORY, FILE_KIND_FILE, FILE_KIND_NONE, FILE_KIND_OTHER, OPEN_APPEND, OPEN_CREATE, OPEN_EXCLUSIVE, OPEN_READ, OPEN_TRUNCATE, OPEN_WRITE, SPAWN_PIPE_STDIN, SPAWN_PIPE_STDOUT};
+
+
+/// Convert a host I/O error to the error code reported to the program.
+pub fn io_error_code(error: io::Error) -> ErrorCodes {
+    match error.kind() {
+        io::ErrorKind::NotFound => ErrorCodes::FileNotFound,
+        io::ErrorKind::PermissionDenied => ErrorCodes::PermissionDenied,
+        io::ErrorKind::AlreadyExists => ErrorCodes::FileExists,
+        io::ErrorKind::InvalidInput => ErrorCodes::InvalidArgument,
+        io::ErrorKind::UnexpectedEof => ErrorCodes::UnexpectedEOF,
+        io::ErrorKind::ConnectionRefused => ErrorCodes::ConnectionRefused,
+        io::ErrorKind::ConnectionReset | io::ErrorKind::ConnectionAborted | io::ErrorKind::BrokenPipe => ErrorCodes::ConnectionReset,
+        io::ErrorKind::AddrInUse => ErrorCodes::AddressInUse,
+        _ => ErrorCodes::IOError,
+    }
+}
+
+
+/// Resolve a socket address given by the program, such as `localhost:8080`.
+/// Sockets are limited to the loopback interface, so an address of another host is reported with `PermissionDenied`.
+fn loopback_addresses(address: &str) -> Result<Vec<SocketAddr>, ErrorCodes> {
+    let addresses: Vec<SocketAddr> = address.to_socket_addrs().map_err(io_error_code)?.collect();
+    if addresses.iter().all(|address| address.ip().is_loopback()) {
+        Ok(addresses)
+    } else {
+        Err(ErrorCodes::PermissionDenied)
+    }
+}
+
+
+/// A host resource opened by the program.
+enum Descriptor {
+    File(File),
+    /// Directory being listed. The next entry is peeked so that it's not lost if it doesn't fit in the program buffer.
+    Directory(Peekable<ReadDir>),
+    /// Process spawned by the program. Closing it without waiting lets the process run on its own.
+    Process(Child),
+    /// Write end of the pipe connected to the standard input of a spawned process.
+    ProcessStdin(ChildStdin),
+    /// Read end of the pipe connected to the standard output of a spawned process.
+    ProcessStdout(ChildStdout),
+    TcpListener(TcpListener),
+    TcpStream(TcpStream),
+    UdpSocket(UdpSocket),
+}
+
+
+/// Descriptors of a process spawned by the program. The pipe descriptors are 0 if the stream is not piped.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SpawnedProcess {
+    pub process: u64,
+    pub stdin: u64,
+    pub stdout: u64,
+}
+
+
+/// The files and directories opened by the program, by descriptor.
+/// Descriptors are never reused, and 0 is never a valid descriptor so that it can be returned on failure.
+pub struct Descriptors {
+
+    open: HashMap<u64, Descriptor>,
+    next: u64,
+
+}
+
+impl Default for Descriptors {
+    fn default() -> Self {
+        Self {
+            open: HashMap::new(),
+            next: 1,
+        }
+    }
+}
+
+impl Descriptors {
+
+    fn insert(&mut self, descriptor: Descriptor) -> u64 {
+        let fd = self.next;
+        self.next += 1;
+        self.open.insert(fd, descriptor);
+        fd
+    }
+
+
+    /// Open the file at `path` with the given combination of `OPEN_*` flags and return its descriptor.
+    pub fn open_file(&mut self, path: &Path, flags: u8) -> Result<u64, ErrorCodes> {
+
+        if flags & !(OPEN_READ | OPEN_WRITE | OPEN_APPEND | OPEN_CREATE | OPEN_TRUNCATE | OPEN_EXCLUSIVE) != 0 {
+            return Err(ErrorCodes::InvalidArgument);
+        }
+
+        let file = OpenOptions::new()
+            .read(flags & OPEN_READ != 0)
+            .write(flags & OPEN_WRITE != 0)
+            .append(flags & OPEN_APPEND != 0)
+            .create(flags & OPEN_CREATE != 0)
+            .truncate(flags & OPEN_TRUNCATE != 0)
+            .create_new(flags & OPEN_EXCLUSIVE != 0)
+            .open(path)
+            .map_err(io_error_code)?;
+
+        Ok(self.insert(Descriptor::File(file)))
+    }
+
+
+    /// Open the directory at `path` for listing and return its descriptor.
+    pub fn open_directory(&mut self, path: &Path) -> Result<u64, ErrorCodes> {
+        let entries = fs::read_dir(path).map_err(io_error_code)?;
+        Ok(self.insert(Descriptor::Directory(entries.peekable())))
+    }
+
+
+    /// Spawn a process running `argv`, with its standard input and output piped to new descriptors according to the `SPAWN_*` flags.
+    /// Streams that are not piped are inherited from the host process.
+    pub fn spawn(&mut self, argv: &[String], flags: u8) -> Result<SpawnedProcess, ErrorCodes> {
+
+        if flags & !(SPAWN_PIPE_STDIN | SPAWN_PIPE_STDOUT) != 0 {
+            return Err(ErrorCodes::InvalidArgument);
+        }
+        let Some((program, args)) = argv.split_first() else {
+            return Err(ErrorCodes::InvalidArgument);
+        };
+
+        let pipe = |flag: u8| if flags & flag != 0 { Stdio::piped() } else { Stdio::inherit() };
+
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(pipe(SPAWN_PIPE_STDIN))
+            .stdout(pipe(SPAWN_PIPE_STDOUT))
+            .spawn()
+            .map_err(io_error_code)?;
+
+        let stdin = child.stdin.take().map_or(0, |stdin| self.insert(Descriptor::ProcessStdin(stdin)));
+        let stdout = child.stdout.take().map_or(0, |stdout| self.insert(Descriptor::ProcessStdout(stdout)));
+        let process = self.insert(Descriptor::Process(child));
+
+        Ok(SpawnedProcess { process, stdin, stdout })
+    }
+
+
+    /// Wait for the process spawned with the descriptor `fd` to exit, close the descriptor, and return the exit code of the process.
+    /// A process terminated by a signal has no exit code, and is reported with -1.
+    pub fn wait(&mut self, fd: u64) -> Result<i32, ErrorCodes> {
+
+        let Some(Descriptor::Process(child)) = self.open.get_mut(&fd) else {
+            return Err(ErrorCodes::InvalidDescriptor);
+        };
+
+        let status = child.wait().map_err(io_error_code)?;
+        self.open.remove(&fd);
+        Ok(status.code().unwrap_or(-1))
+    }
+
+
+    /// Listen for TCP connections on the socket address `address`, such as `127.0.0.1:8080`, and return the descriptor of the listener.
+    pub fn listen(&mut self, address: &str) -> Result<u64, ErrorCodes> {
+        let listener = TcpListener::bind(&loopback_addresses(address)?[..]).map_err(io_error_code)?;
+        Ok(self.insert(Descriptor::TcpListener(listener)))
+    }
+
+
+    /// Wait for a connection on the listener `fd` and return the descriptor of the connected stream.
+    pub fn accept(&mut self, fd: u64) -> Result<u64, ErrorCodes> {
+        let Some(Descriptor::TcpListener(listener)) = self.open.get_mut(&fd) else {
+            return Err(ErrorCodes::InvalidDescriptor);
+        };
+        let (stream, _) = listener.accept().map_err(io_error_code)?;
+        Ok(self.insert(Descriptor::TcpStream(stream)))
+    }
+
+
+    /// Open a TCP connection to `address` and return the descriptor of the connected stream.
+    pub fn connect(&mut self, address: &str) -> Result<u64, ErrorCodes> {
+        let stream = TcpStream::connect(&loopback_addresses(address)?[..]).map_err(io_error_code)?;
+        Ok(self.insert(Descriptor::TcpStream(stream)))
+    }
+
+
+    /// Open a UDP socket bound to `address` and return its descriptor.
+    pub fn bind(&mut self, address: &str) -> Result<u64, ErrorCodes> {
+        let socket = UdpSocket::bind(&loopback_addresses(address)?[..]).map_err(io_error_code)?;
+        Ok(self.insert(Descriptor::UdpSocket(socket)))
+    }
+
+
+    /// Send `buf` over the TCP stream `fd`.
+    pub fn send(&mut self, fd: u64, buf: &[u8]) -> Result<(), ErrorCodes> {
+        match self.open.get_mut(&fd) {
+            Some(Descriptor::TcpStream(stream)) => stream.write_all(buf).map_err(io_error_code),
+            _ => Err(ErrorCodes::InvalidDescriptor)
+        }
+    }
+
+
+    /// Send `buf` as a datagram to `address` over the UDP socket `fd`.
+    pub fn send_to(&mut self, fd: u64, buf: &[u8], address: &str) -> Result<usize, ErrorCodes> {
+        match self.open.get_mut(&fd) {
+            Some(Descriptor::UdpSocket(socket)) => socket.send_to(buf, &loopback_addresses(address)?[..]).map_err(io_error_code),
+            _ => Err(ErrorCodes::InvalidDescriptor)
+        }
+    }
+
+
+    /// Receive data from the TCP stream or the UDP socket `fd` into `buf`, and return its length.
+    /// Return `EOF` once the peer of a stream closed the connection. A datagram that is larger than `buf` is truncated.
+    pub fn recv(&mut self, fd: u64, buf: &mut [u8]) -> Result<usize, ErrorCodes> {
+        match self.open.get_mut(&fd) {
+            Some(Descriptor::TcpStream(stream)) => match stream.read(buf) {
+                Ok(0) if !buf.is_empty() => Err(ErrorCodes::EOF),
+                result => result.map_err(io_error_code)
+            },
+            Some(Descriptor::UdpSocket(socket)) => socket.recv(buf).map_err(io_error_code),
+            _ => Err(ErrorCodes::InvalidDescriptor)
+        }
+    }
+
+
+    /// Receive a datagram from the UDP socket `fd` into `buf`, and return its length and the address of its sender.
+    /// A datagram that is larger than `buf` is truncated.
+    pub fn recv_from(&mut self, fd: u64, buf: &mut [u8]) -> Result<(usize, SocketAddr), ErrorCodes> {
+        match self.open.get_mut(&fd) {
+            Some(Descriptor::UdpSocket(socket)) => socket.recv_from(buf).map_err(io_error_code),
+            _ => Err(ErrorCodes::InvalidDescriptor)
+        }
+    }
+
+
+    /// Return the local port of the socket `fd`, which is useful after binding to port 0 to let the host pick a free one.
+    pub fn local_port(&mut self, fd: u64) -> Result<u16, ErrorCodes> {
+        let address = match self.open.get(&fd) {
+            Some(Descriptor::TcpListener(listener)) => listener.local_addr(),
+            Some(Descriptor::TcpStream(stream)) => stream.local_addr(),
+            Some(Descriptor::UdpSocket(socket)) => socket.local_addr(),
+            _ => return Err(ErrorCodes::InvalidDescriptor)
+        };
+        address.map(|address| address.port()).map_err(io_error_code)
+    }
+
+
+    /// Return the descriptor `fd` if it can be read from.
+    pub fn reader(&mut self, fd: u64) -> Result<&mut dyn Read, ErrorCodes> {
+        match self.open.get_mut(&fd) {
+            Some(Descriptor::File(file)) => Ok(file),
+            Some(Descriptor::ProcessStdout(stdout)) => Ok(stdout),
+            Some(Descriptor::TcpStream(stream)) => Ok(stream),
+            _ => Err(ErrorCodes::InvalidDescriptor)
+        }
+    }
+
+
+    /// Return the descriptor `fd` if it can be written to.
+    pub fn writer(&mut self, fd: u64) -> Result<&mut dyn Write, ErrorCodes> {
+        match self.open.get_mut(&fd) {
+            Some(Descriptor::File(file)) => Ok(file),
+            Some(Descriptor::ProcessStdin(stdin)) => Ok(stdin),
+            Some(Descriptor::TcpStream(stream)) => Ok(stream),
+            _ => Err(ErrorCodes::InvalidDescriptor)
+        }
+    }
+
+
+    /// Return the descriptor `fd` if it's a file, which unlike pipes supports seeking.
+    pub fn file(&mut self, fd: u64) -> Result<&mut File, ErrorCodes> {
+        match self.open.get_mut(&fd) {
+            Some(Descriptor::File(file)) => Ok(file),
+            _ => Err(ErrorCodes::InvalidDescriptor)
+        }
+    }
+
+
+    /// Copy the name of the next entry of the directory listed by `fd` into `buf` and return its length.
+    /// Return `EOF` once all the entries were listed. If the name doesn't fit, return its length with the `Overflow` error code,
+    /// and keep the entry so that it can be read again with a larger buffer.
+    pub fn read_directory(&mut self, fd: u64, buf: &mut [u8]) -> Result<usize, (ErrorCodes, usize)> {
+
+        let Some(Descriptor::Directory(entries)) = self.open.get_mut(&fd) else {
+            return Err((ErrorCodes::InvalidDescriptor, 0));
+        };
+
+        let entry = match entries.peek() {
+            None => return Err((ErrorCodes::EOF, 0)),
+            Some(Err(_)) => {
+                let error = entries.next().unwrap().unwrap_err();
+                return Err((io_error_code(error), 0));
+            },
+            Some(Ok(entry)) => entry,
+        };
+
+        let name = entry.file_name();
+        let name = name.as_encoded_bytes();
+
+        if name.len() > buf.len() {
+            return Err((ErrorCodes::Overflow, name.len()));
+        }
+
+        buf[..name.len()].copy_from_slice(name);
+        entries.next();
+        Ok(name.len())
+    }
+
+
+    pub fn close(&mut self, fd: u64) -> Result<(), ErrorCodes> {
+        self.open.remove(&fd)
+            .map(|_| ())
+            .ok_or(ErrorCodes::InvalidDescriptor)
+    }
+
+
+    /// Close all the open descriptors.
+    pub fn clear(&mut self) {
+        self.open.clear();
+    }
+
+}
+
+
+/// Information about a file reported by the stat interrupt.
+#[derive(Debug, Clone, Copy)]
+pub struct FileStatus {
+    pub size: u64,
+    /// Last modification time in seconds since the Unix epoch, or 0 if it's not available.
+    pub modified: u64,
+    /// One of the `FILE_KIND_*` constants.
+    pub kind: u8,
+}
+
+impl Default for FileStatus {
+    fn default() -> Self {
+        Self { size: 0, modified: 0, kind: FILE_KIND_NONE }
+    }
+}
+
+
+/// Query the status of the file at `path`, following symbolic links.
+pub fn stat(path: &Path) -> Result<FileStatus, ErrorCodes> {
+
+    let metadata = fs::metadata(path).map_err(io_error_code)?;
+
+    let kind = if metadata.is_file() {
+        FILE_KIND_FILE
+    } else if metadata.is_dir() {
+        FILE_KIND_DIRECTORY
+    } else {
+        FILE_KIND_OTHER
+    };
+
+    let modified = metadata.modified().ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |duration| duration.as_secs());
+
+    Ok(FileStatus { size: metadata.len(), modified, kind })
+}
@@ -0,0 +1,276 @@
+use std::iter::Peekable;
+use std::num::{IntErrorKind, ParseIntError};
+use std::str::CharIndices;
+
+use vmlib::{ErrorCodes, SHORTEST_FLOAT_PRECISION};
+
+
+/// Largest width and precision accepted in format strings, so that a format cannot make the VM allocate unbounded memory.
+const MAX_FIELD_WIDTH: usize = 4096;
+
+
+/// How a value is converted to text by the printing interrupts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Conversion {
+    Unsigned,
+    Signed,
+    Hex,
+    UpperHex,
+    Binary,
+    Float,
+    /// Unicode code point, printed as the character it encodes.
+    CodePoint,
+    /// String given as its address followed by its length.
+    String,
+}
+
+
+/// A conversion specification of a format string, such as `%08x4`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Spec {
+    pub conversion: Conversion,
+    /// Size of the integer or float argument.
+    pub size: usize,
+    /// Minimum number of characters, padded with spaces or zeros.
+    pub width: usize,
+    /// Number of float digits after the decimal point. The shortest representation is used if it's not given.
+    pub precision: Option<usize>,
+    pub left_align: bool,
+    pub zero_pad: bool,
+}
+
+impl Spec {
+
+    /// Number of stack bytes taken by the argument.
+    pub fn argument_size(&self) -> usize {
+        match self.conversion {
+            Conversion::CodePoint => 4,
+            Conversion::String => 16,
+            _ => self.size
+        }
+    }
+
+
+    /// Format the number argument given as its little-endian bytes. Return `None` for code points that are not valid characters.
+    pub fn format_number(&self, bytes: &[u8]) -> Option<String> {
+        let mut value = [0; 8];
+        value[..bytes.len()].copy_from_slice(bytes);
+        let value = u64::from_le_bytes(value);
+
+        match self.conversion {
+            Conversion::Float => Some(format_float(value, self.size, self.precision)),
+            Conversion::CodePoint => char::from_u32(value as u32).map(String::from),
+            conversion => Some(format_integer(value, self.size, conversion))
+        }
+    }
+
+
+    /// Pad the formatted argument to the width of the specification.
+    /// Zeros are inserted after the sign, and they don't apply to strings and characters.
+    pub fn pad(&self, text: String) -> String {
+
+        let length = text.chars().count();
+        if length >= self.width {
+            return text;
+        }
+        let padding = self.width - length;
+
+        if self.left_align {
+            text + &" ".repeat(padding)
+        } else if self.zero_pad && !matches!(self.conversion, Conversion::CodePoint | Conversion::String) {
+            let sign = if text.starts_with('-') { 1 } else { 0 };
+            format!("{}{}{}", &text[..sign], "0".repeat(padding), &text[sign..])
+        } else {
+            " ".repeat(padding) + &text
+        }
+    }
+
+}
+
+
+/// A part of a parsed format string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Piece<'a> {
+    Literal(&'a str),
+    Spec(Spec),
+}
+
+
+/// Parse a format string. Return `None` if a conversion specification is invalid.
+///
+/// A specification is `%`, optional `-` and `0` flags, an optional width, an optional `.precision`,
+/// a conversion character, and the argument size for integers and floats: `%u8`, `%-6i4`, `%.2f8`, `%08x2`, `%c`, `%s`.
+/// `%%` prints a percent sign.
+pub fn parse(format: &str) -> Option<Vec<Piece<'_>>> {
+
+    let mut pieces = Vec::new();
+    let mut rest = format;
+
+    while let Some(start) = rest.find('%') {
+
+        if start > 0 {
+            pieces.push(Piece::Literal(&rest[..start]));
+        }
+        rest = &rest[start + 1..];
+
+        if let Some(after) = rest.strip_prefix('%') {
+            pieces.push(Piece::Literal("%"));
+            rest = after;
+            continue;
+        }
+
+        let mut chars = rest.char_indices().peekable();
+        let mut spec = Spec { conversion: Conversion::Unsigned, size: 0, width: 0, precision: None, left_align: false, zero_pad: false };
+
+        while let Some((_, flag @ ('-' | '0'))) = chars.peek().copied() {
+            match flag {
+                '-' => spec.left_align = true,
+                _ => spec.zero_pad = true,
+            }
+            chars.next();
+        }
+
+        if chars.peek().is_some_and(|(_, c)| c.is_ascii_digit()) {
+            spec.width = parse_number(&mut chars)?;
+        }
+
+        if chars.next_if(|&(_, c)| c == '.').is_some() {
+            spec.precision = Some(parse_number(&mut chars)?);
+        }
+
+        spec.conversion = match chars.next()?.1 {
+            'u' => Conversion::Unsigned,
+            'i' => Conversion::Signed,
+            'x' => Conversion::Hex,
+            'X' => Conversion::UpperHex,
+            'b' => Conversion::Binary,
+            'f' => Conversion::Float,
+            'c' => Conversion::CodePoint,
+            's' => Conversion::String,
+            _ => return None
+        };
+
+        if spec.precision.is_some() && spec.conversion != Conversion::Float {
+            return None;
+        }
+
+        if !matches!(spec.conversion, Conversion::CodePoint | Conversion::String) {
+            spec.size = match chars.next()?.1 {
+                '1' => 1,
+                '2' => 2,
+                '4' => 4,
+                '8' => 8,
+                _ => return None
+            };
+            if spec.conversion == Conversion::Float && spec.size < 4 {
+                return None;
+            }
+        }
+
+        rest = match chars.peek() {
+            Some(&(index, _)) => &rest[index..],
+            None => ""
+        };
+        pieces.push(Piece::Spec(spec));
+    }
+
+    if !rest.is_empty() {
+        pieces.push(Piece::Literal(rest));
+    }
+
+    Some(pieces)
+}
+
+
+/// Parse the width or the precision of a specification. Return `None` if there are no digits or the number is too large.
+fn parse_number(chars: &mut Peekable<CharIndices>) -> Option<usize> {
+    let mut number: Option<usize> = None;
+    while let Some((_, digit)) = chars.next_if(|(_, c)| c.is_ascii_digit()) {
+        number = Some(number.unwrap_or(0).saturating_mul(10).saturating_add(digit as usize - '0' as usize));
+    }
+    number.filter(|&number| number <= MAX_FIELD_WIDTH)
+}
+
+
+/// Format the integer of `size` bytes stored in the low bytes of `value`.
+pub fn format_integer(value: u64, size: usize, conversion: Conversion) -> String {
+    let bits = size * 8;
+    let value = if bits < 64 { value & ((1 << bits) - 1) } else { value };
+
+    match conversion {
+        Conversion::Signed => {
+            // Sign-extend the value to 64 bits
+            let shift = 64 - bits;
+            (((value << shift) as i64) >> shift).to_string()
+        },
+        Conversion::Hex => format!("{value:x}"),
+        Conversion::UpperHex => format!("{value:X}"),
+        Conversion::Binary => format!("{value:b}"),
+        _ => value.to_string()
+    }
+}
+
+
+/// Format the float of `size` bytes stored in the low bytes of `value`, with `precision` digits after the decimal point
+/// or with the shortest representation that reads back as the same value.
+pub fn format_float(value: u64, size: usize, precision: Option<usize>) -> String {
+    match (size, precision) {
+        (4, Some(precision)) => format!("{:.precision$}", f32::from_bits(value as u32)),
+        (4, None) => f32::from_bits(value as u32).to_string(),
+        (_, Some(precision)) => format!("{:.precision$}", f64::from_bits(value)),
+        (_, None) => f64::from_bits(value).to_string(),
+    }
+}
+
+
+/// Convert the precision operand of the float printing interrupts.
+pub fn float_precision(operand: u8) -> Option<usize> {
+    (operand != SHORTEST_FLOAT_PRECISION).then_some(operand as usize)
+}
+
+
+/// Parse a decimal integer of `size` bytes, surrounded by optional ASCII whitespace, and return it in the low bytes of the result.
+/// Return the `Overflow` error code if it doesn't fit in `size` bytes and `InvalidArgument` if the text is not a number.
+pub fn parse_integer(text: &str, size: usize, signed: bool) -> Result<u64, ErrorCodes> {
+    let text = text.trim_ascii();
+    let bits = size as u32 * 8;
+
+    let value = if signed {
+        let value = text.parse::<i64>().map_err(int_error_code)?;
+        let max = i64::MAX >> (64 - bits);
+        if !(-max - 1..=max).contains(&value) {
+            return Err(ErrorCodes::Overflow);
+        }
+        value as u64
+    } else {
+        let value = text.parse::<u64>().map_err(int_error_code)?;
+        if value > u64::MAX >> (64 - bits) {
+            return Err(ErrorCodes::Overflow);
+        }
+        value
+    };
+
+    // Truncate the sign extension of negative numbers
+    Ok(if bits < 64 { value & ((1 << bits) - 1) } else { value })
+}
+
+
+fn int_error_code(error: ParseIntError) -> ErrorCodes {
+    match error.kind() {
+        IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => ErrorCodes::Overflow,
+        _ => ErrorCodes::InvalidArgument
+    }
+}
+
+
+/// Parse a float of `size` bytes, surrounded by optional ASCII whitespace, and return its bits in the low bytes of the result.
+/// Return the `InvalidArgument` error code if the text is not a number.
+pub fn parse_float(text: &str, size: usize) -> Result<u64, ErrorCodes> {
+    let text = text.trim_ascii();
+    let bits = if size == 4 {
+        text.parse::<f32>().map(|value| value.to_bits() as u64)
+    } else {
+        text.parse::<f64>().map(f64::to_bits)
+    };
+    bits.map_err(|_| ErrorCodes::InvalidArgument)
+}
@@ -0,0 +1,143 @@
+use std::alloc::{self, Layout};
+use std::collections::{HashMap, HashSet};
+
+use vmlib::{Address, ErrorCodes};
+
+
+/// Heap memory allocated by the program through the host allocator.
+/// Every live block is recorded with its layout, so that it can be correctly deallocated and reallocated.
+#[derive(Default)]
+pub struct Heap {
+
+    /// Live blocks and their layout, by address.
+    blocks: HashMap<Address, Layout>,
+    /// Addresses of the blocks that were freed and not handed out again by the allocator, used to detect double frees.
+    freed: HashSet<Address>,
+    /// Total size of the live blocks.
+    live_bytes: usize,
+
+}
+
+impl Heap {
+
+    /// Allocate a block of `size` bytes with the given alignment.
+    /// Zero-sized blocks are allocated as one-byte blocks so that every allocation returns a unique pointer.
+    pub fn allocate(&mut self, size: usize, align: usize, zeroed: bool) -> Result<*mut u8, ErrorCodes> {
+
+        let layout = Layout::from_size_align(size.max(1), align)
+            .map_err(|_| ErrorCodes::OutOfMemory)?;
+
+        let block = unsafe {
+            if zeroed {
+                alloc::alloc_zeroed(layout)
+            } else {
+                alloc::alloc(layout)
+            }
+        };
+
+        if block.is_null() {
+            return Err(ErrorCodes::OutOfMemory);
+        }
+
+        self.track(block, layout);
+        Ok(block)
+    }
+
+
+    /// Resize the block at `block` to `new_size` bytes, keeping its alignment.
+    /// A null pointer is equivalent to a new allocation.
+    pub fn reallocate(&mut self, block: *mut u8, new_size: usize) -> Result<*mut u8, ErrorCodes> {
+
+        if block.is_null() {
+            return self.allocate(new_size, 1, false);
+        }
+
+        let layout = self.untrack(block)?;
+
+        let new_block = Layout::from_size_align(new_size.max(1), layout.align())
+            .ok()
+            .map(|new_layout| unsafe {
+                (alloc::realloc(block, layout, new_layout.size()), new_layout)
+            })
+            .filter(|(new_block, _)| !new_block.is_null());
+
+        let Some((new_block, new_layout)) = new_block else {
+            // The original block is left untouched if the reallocation fails
+            self.track(block, layout);
+            return Err(ErrorCodes::OutOfMemory);
+        };
+
+        self.track(new_block, new_layout);
+        Ok(new_block)
+    }
+
+
+    /// Deallocate the block at `block`. Freeing a null pointer does nothing.
+    pub fn free(&mut self, block: *mut u8) -> Result<(), ErrorCodes> {
+
+        if block.is_null() {
+            return Ok(());
+        }
+
+        let layout = self.untrack(block)?;
+        unsafe {
+            alloc::dealloc(block, layout);
+        }
+        Ok(())
+    }
+
+
+    /// Return the size of the live block at `block`.
+    pub fn block_size(&self, block: *mut u8) -> Option<usize> {
+        self.blocks.get(&(block as Address)).map(Layout::size)
+    }
+
+
+    /// Total size of the live blocks.
+    pub fn live_bytes(&self) -> usize {
+        self.live_bytes
+    }
+
+
+    /// Return the blocks that are still allocated, sorted by address.
+    pub fn leaked_blocks(&self) -> Vec<(Address, Layout)> {
+        let mut blocks: Vec<(Address, Layout)> = self.blocks.iter()
+            .map(|(&address, &layout)| (address, layout))
+            .collect();
+        blocks.sort_by_key(|&(address, _)| address);
+        blocks
+    }
+
+
+    fn track(&mut self, block: *mut u8, layout: Layout) {
+        self.freed.remove(&(block as Address));
+        self.blocks.insert(block as Address, layout);
+        self.live_bytes += layout.size();
+    }
+
+
+    /// Stop tracking the block at `block` and return its layout.
+    fn untrack(&mut self, block: *mut u8) -> Result<Layout, ErrorCodes> {
+        let address = block as Address;
+        match self.blocks.remove(&address) {
+            Some(layout) => {
+                self.freed.insert(address);
+                self.live_bytes -= layout.size();
+                Ok(layout)
+            },
+            None if self.freed.contains(&address) => Err(ErrorCodes::DoubleFree),
+            None => Err(ErrorCodes::InvalidPointer)
+        }
+    }
+
+}
+
+impl Drop for Heap {
+    fn drop(&mut self) {
+        for (&address, &layout) in &self.blocks {
+            unsafe {
+                alloc::dealloc(address as *mut u8, layout);
+            }
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::ptr;
+
+use vmlib::ErrorCodes;
+
+use crate::exec::VM;
+use crate::fault::FaultKind;
+
+
+/// A Rust function that programs call with `hostcall`. Returning a fault stops the program.
+pub type HostFunction = Box<dyn FnMut(&mut HostContext) -> Result<(), FaultKind>>;
+
+
+/// The host functions registered with a VM, by name and by number.
+#[derive(Default)]
+pub(crate) struct HostFunctions {
+
+    /// Registered functions. A slot is empty only while its function is running.
+    functions: Vec<Option<HostFunction>>,
+    /// Indices of the functions registered by name.
+    names: HashMap<String, usize>,
+    /// Indices of the functions registered by number.
+    ids: HashMap<u32, usize>,
+
+}
+
+impl HostFunctions {
+
+    pub fn register_name(&mut self, name: String, function: HostFunction) {
+        let index = self.push(function);
+        self.names.insert(name, index);
+    }
+
+
+    pub fn register_id(&mut self, id: u32, function: HostFunction) {
+        let index = self.push(function);
+        self.ids.insert(id, index);
+    }
+
+
+    fn push(&mut self, function: HostFunction) -> usize {
+        self.functions.push(Some(function));
+        self.functions.len() - 1
+    }
+
+
+    /// Find the function a program calls with the number `id`.
+    /// Numbers declared by the program in `imports` refer to functions registered by name, other numbers to functions registered by number.
+    pub fn resolve(&self, id: u32, imports: &[String]) -> Option<usize> {
+        match imports.get(id as usize) {
+            Some(name) => self.names.get(name).copied(),
+            None => self.ids.get(&id).copied(),
+        }
+    }
+
+
+    /// Take the function at `index` out of the registry while it runs, since it needs mutable access to the VM that owns it.
+    pub fn take(&mut self, index: usize) -> HostFunction {
+        self.functions[index].take().expect("Host functions cannot be called recursively")
+    }
+
+
+    pub fn restore(&mut self, index: usize, function: HostFunction) {
+        self.functions[index] = Some(function);
+    }
+
+}
+
+
+/// Access to the state of the VM given to a running host function.
+/// Operation stack accesses are always bounds-checked, regardless of the execution mode.
+/// Memory accesses are checked like the accesses of the program itself: they are bounds-checked in the virtual memory model
+/// and checked against the heap blocks and the initialized stack bytes in sanitizer mode.
+pub struct HostContext<'a> {
+    pub(crate) vm: &'a mut VM,
+}
+
+impl HostContext<'_> {
+
+    pub fn pop_1(&mut self) -> Result<u8, FaultKind> {
+        self.vm.opstack.pop_1()
+    }
+
+
+    pub fn pop_2(&mut self) -> Result<u16, FaultKind> {
+        self.vm.opstack.pop_2()
+    }
+
+
+    pub fn pop_4(&mut self) -> Result<u32, FaultKind> {
+        self.vm.opstack.pop_4()
+    }
+
+
+    pub fn pop_8(&mut self) -> Result<u64, FaultKind> {
+        self.vm.opstack.pop_8()
+    }
+
+
+    /// Pop `count` bytes, in the order they are laid out in memory.
+    pub fn pop_bytes(&mut self, count: usize) -> Result<Vec<u8>, FaultKind> {
+        self.vm.opstack.pop_bytes(count).map(|bytes| bytes.to_vec())
+    }
+
+
+    pub fn push_1(&mut self, value: u8) -> Result<(), FaultKind> {
+        self.vm.opstack.push_1(value)
+    }
+
+
+    pub fn push_2(&mut self, value: u16) -> Result<(), FaultKind> {
+        self.vm.opstack.push_2(value)
+    }
+
+
+    pub fn push_4(&mut self, value: u32) -> Result<(), FaultKind> {
+        self.vm.opstack.push_4(value)
+    }
+
+
+    pub fn push_8(&mut self, value: u64) -> Result<(), FaultKind> {
+        self.vm.opstack.push_8(value)
+    }
+
+
+    pub fn push_bytes(&mut self, bytes: &[u8]) -> Result<(), FaultKind> {
+        self.vm.opstack.push_bytes(bytes)
+    }
+
+
+    /// Fill `buf` with the memory at `address`.
+    pub fn read_memory(&self, address: u64, buf: &mut [u8]) -> Result<(), FaultKind> {
+        let src = self.vm.translate_read(address, buf.len())?;
+        unsafe {
+            ptr::copy(src, buf.as_mut_ptr(), buf.len());
+        }
+        Ok(())
+    }
+
+
+    /// Copy `bytes` to the memory at `address`.
+    pub fn write_memory(&mut self, address: u64, bytes: &[u8]) -> Result<(), FaultKind> {
+        let dest = self.vm.translate_write(address, bytes.len())?;
+        unsafe {
+            ptr::copy(bytes.as_ptr(), dest, bytes.len());
+        }
+        Ok(())
+    }
+
+
+    /// Read the null-terminated string at `address`, without its terminator.
+    pub fn read_cstring(&self, address: u64) -> Result<Vec<u8>, FaultKind> {
+        let mut string = Vec::new();
+        loop {
+            let mut byte = [0];
+            self.read_memory(address.wrapping_add(string.len() as u64), &mut byte)?;
+            if byte[0] == 0 {
+                return Ok(string);
+            }
+            string.push(byte[0]);
+        }
+    }
+
+
+    pub fn error_code(&self) -> ErrorCodes {
+        self.vm.error_code
+    }
+
+
+    /// Set the VM error code, which the program can read after the call.
+    pub fn set_error_code(&mut self, error_code: ErrorCodes) {
+        self.vm.error_code = error_code;
+    }
+
+}
@@ -0,0 +1,26 @@
+//! Interpreter for Stack VM byte code.
+//!
+//! A `VM` is configured with `VM::builder` and runs programs produced by the assembler:
+//! `VM::builder().limits(limits).build().run(&bytecode)` returns how the program exited, or the fault that stopped it.
+//! The functions exported by the loaded program can then be called with `VM::call`.
+
+mod exec;
+mod fault;
+mod heap;
+mod memory;
+mod sanitizer;
+mod limits;
+mod builder;
+mod console;
+mod host;
+mod files;
+mod random;
+mod format;
+
+pub use exec::{ExecutionMode, ExitStatus, VM};
+pub use fault::{Backtrace, BacktraceFrame, FaultKind, VmFault};
+pub use memory::MemoryModel;
+pub use limits::{Limit, Limits};
+pub use builder::VmBuilder;
+pub use console::SharedBuffer;
+pub use host::{HostContext, HostFunction};
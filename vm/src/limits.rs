@@ -0,0 +1,42 @@
+use std::fmt;
+use std::time::Duration;
+
+
+/// Resources that can be limited for a single run of a program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    /// Number of executed instructions.
+    Instructions,
+    /// Total size of the live heap blocks.
+    HeapBytes,
+    /// Wall-clock time since the start of the run.
+    Time,
+    /// Number of bytes written by the printing interrupts.
+    Output,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::Instructions => write!(f, "instruction limit"),
+            Limit::HeapBytes => write!(f, "heap size limit"),
+            Limit::Time => write!(f, "time limit"),
+            Limit::Output => write!(f, "output size limit"),
+        }
+    }
+}
+
+
+/// Resource limits enforced while running a program. Exceeding any of them stops the program with a fault.
+/// Every limit is disabled by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// Maximum number of instructions to execute.
+    pub max_instructions: Option<u64>,
+    /// Maximum total size in bytes of the heap blocks that are allocated at the same time.
+    pub max_heap_bytes: Option<usize>,
+    /// Maximum wall-clock duration of the run. Time spent blocked on input is counted, but it cannot be interrupted.
+    pub timeout: Option<Duration>,
+    /// Maximum number of bytes written by the printing interrupts.
+    pub max_output_bytes: Option<usize>,
+}
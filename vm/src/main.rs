@@ -1,10 +1,28 @@
-mod exec;
 mod cli_parser;
 
+use std::alloc::Layout;
 use std::fs;
 
 use clap::Parser;
 use cli_parser::CliParser;
+use vm::{ExecutionMode, Limits, VM};
+use vmlib::{Address, ErrorCodes};
+
+
+fn report_leaks(blocks: &[(Address, Layout)]) {
+
+    if blocks.is_empty() {
+        eprintln!("Leak check: all heap blocks were freed");
+        return;
+    }
+
+    let total: usize = blocks.iter().map(|(_, layout)| layout.size()).sum();
+    eprintln!("Leak check: {} heap blocks ({total} bytes) were never freed:", blocks.len());
+
+    for (address, layout) in blocks {
+        eprintln!("  {address:#x}: {} bytes, aligned to {}", layout.size(), layout.align());
+    }
+}
 
 
 fn main() {
@@ -14,11 +32,55 @@ fn main() {
     let bytecode = fs::read(args.input_file.as_path())
         .unwrap_or_else(|err| panic!("Could not read input file \"{}\".\n{err}", args.input_file.display()));
 
-    let mut vm = exec::VM::new(args.opstack_size);
+    let mode = if args.unchecked {
+        ExecutionMode::Unchecked
+    } else {
+        ExecutionMode::Checked
+    };
 
-    let code = vm.run(&bytecode);
+    let mut builder = VM::builder()
+        .args(args.args)
+        .mode(mode)
+        .memory_model(args.memory)
+        .sanitize(args.sanitize)
+        .allow_spawn(!args.deny_spawn)
+        .limits(Limits {
+            max_instructions: args.max_instructions,
+            max_heap_bytes: args.max_heap,
+            timeout: args.timeout,
+            max_output_bytes: args.max_output,
+        });
+
+    if let Some(size) = args.opstack_size {
+        builder = builder.opstack_size(size);
+    }
+    if let Some(depth) = args.call_stack_depth {
+        builder = builder.call_stack_depth(depth);
+    }
+    if let Some(seed) = args.seed {
+        builder = builder.seed(seed);
+    }
+    if args.inherit_env {
+        // Variables that are not valid Unicode cannot be read by the program, so they are not passed
+        builder = builder.env_vars(std::env::vars_os().filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?))));
+    }
+    builder = builder.env_vars(args.env_vars);
+
+    let mut vm = builder.build();
+
+    let code = match vm.run(&bytecode) {
+        Ok(status) => status.code(),
+        Err(fault) => {
+            eprintln!("{fault}");
+            eprintln!("{}", fault.backtrace);
+            ErrorCodes::GenericError
+        }
+    };
+
+    if args.leak_check {
+        report_leaks(&vm.leaked_blocks());
+    }
 
     println!("Process exited with code {code}");
     std::process::exit(code as i32);
 }
-
@@ -0,0 +1,246 @@
+use std::alloc::Layout;
+use std::collections::{BTreeMap, HashSet};
+
+use vmlib::{Address, ErrorCodes};
+
+use crate::fault::FaultKind;
+use crate::heap::Heap;
+
+
+/// Selects how the pointers used by a program are mapped to host memory.
+/// The memory model doesn't affect the byte code, the same program can run with either model.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum MemoryModel {
+    /// Pointers are raw host addresses. Memory accesses are not checked, it's up to the host OS to stop illegal accesses.
+    #[default]
+    Host,
+    /// Pointers are addresses in an address space owned by the VM. Every memory access is translated and bounds-checked,
+    /// so that the program cannot access host memory outside of the operation stack, the program space, the data segment and its heap blocks.
+    Virtual,
+}
+
+
+/// What a region of the virtual address space is backed by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionKind {
+    Stack,
+    Program,
+    Data,
+    Heap,
+}
+
+
+/// A contiguous range of the virtual address space backed by host memory.
+struct Region {
+    kind: RegionKind,
+    /// Host address of the first byte of the region.
+    host: *mut u8,
+    size: usize,
+    align: usize,
+    writable: bool,
+    /// Owned storage of heap blocks. Other regions are backed by memory owned by the VM or the program.
+    _block: Option<Box<[u8]>>,
+}
+
+
+// Leave the first page unmapped so that null and small addresses are always invalid.
+const FIRST_REGION_BASE: Address = 0x1000;
+
+// Unmapped space between regions, so that small overflows past the end of a region are caught.
+const REGION_GAP: Address = 0x1000;
+
+
+/// The memory accessible to a program: host heap blocks in the host memory model, or the mapped regions in the virtual memory model.
+pub struct Memory {
+
+    model: MemoryModel,
+    /// Heap blocks allocated through the host allocator in the host memory model.
+    heap: Heap,
+    /// Mapped regions by virtual base address, in the virtual memory model.
+    regions: BTreeMap<Address, Region>,
+    /// Base address of the next mapped region. Addresses are never reused, so dangling pointers keep pointing to unmapped memory.
+    next_base: Address,
+    /// Base addresses of the heap blocks that were freed, to detect double frees in the virtual memory model.
+    freed: HashSet<Address>,
+    /// Total size of the live heap blocks in the virtual memory model.
+    heap_bytes: usize,
+
+}
+
+impl Memory {
+
+    pub fn new(model: MemoryModel) -> Self {
+        Self {
+            model,
+            heap: Heap::default(),
+            regions: BTreeMap::new(),
+            next_base: FIRST_REGION_BASE,
+            freed: HashSet::new(),
+            heap_bytes: 0,
+        }
+    }
+
+
+    /// Make `size` bytes of host memory at `host` accessible to the program and return the address the program sees them at.
+    /// In the host memory model, the address is the host address itself.
+    pub fn map(&mut self, kind: RegionKind, host: *mut u8, size: usize, writable: bool) -> Address {
+        match self.model {
+            MemoryModel::Host => host as Address,
+            MemoryModel::Virtual => self.map_region(Region { kind, host, size, align: 1, writable, _block: None })
+                .expect("The virtual address space should have room for the program regions, which are mapped before any heap block"),
+        }
+    }
+
+
+    /// Remove the region mapped at `base`. Does nothing in the host memory model.
+    pub fn unmap(&mut self, base: Address) {
+        self.regions.remove(&base);
+    }
+
+
+    /// Map the region at the next free address. Return `None` if the virtual address space is exhausted.
+    fn map_region(&mut self, region: Region) -> Option<Address> {
+        let base = self.next_base.checked_next_multiple_of(region.align)?;
+        self.next_base = base.checked_add(region.size)?.checked_add(REGION_GAP)?;
+        self.regions.insert(base, region);
+        Some(base)
+    }
+
+
+    /// Translate the program address of a `size`-byte access to a host pointer.
+    /// In the virtual memory model, the access must be entirely inside a mapped region, and the region must be writable if `write` is set.
+    pub fn translate(&self, address: Address, size: usize, write: bool) -> Result<*mut u8, FaultKind> {
+
+        if self.model == MemoryModel::Host {
+            return Ok(address as *mut u8);
+        }
+
+        let (base, region) = self.regions.range(..=address).next_back()
+            .ok_or(FaultKind::InvalidMemoryAccess(address))?;
+
+        let offset = address - base;
+        if offset.checked_add(size).is_none_or(|end| end > region.size) {
+            return Err(FaultKind::InvalidMemoryAccess(address));
+        }
+        if write && !region.writable {
+            return Err(FaultKind::ReadOnlyMemoryWrite(address));
+        }
+
+        unsafe {
+            Ok(region.host.add(offset))
+        }
+    }
+
+
+    /// Allocate a heap block of `size` bytes with the given alignment and return its address.
+    pub fn allocate(&mut self, size: usize, align: usize, zeroed: bool) -> Result<Address, ErrorCodes> {
+        match self.model {
+            MemoryModel::Host => self.heap.allocate(size, align, zeroed).map(|block| block as Address),
+            MemoryModel::Virtual => {
+                if !align.is_power_of_two() {
+                    return Err(ErrorCodes::OutOfMemory);
+                }
+                // Virtual heap blocks are always zeroed, since their storage is allocated by the VM.
+                // The size comes from the program, so a failed allocation is reported instead of aborting the host.
+                let mut block = Vec::new();
+                block.try_reserve_exact(size).map_err(|_| ErrorCodes::OutOfMemory)?;
+                block.resize(size, 0u8);
+                let mut block = block.into_boxed_slice();
+                let host = block.as_mut_ptr();
+                let base = self.map_region(Region { kind: RegionKind::Heap, host, size, align, writable: true, _block: Some(block) })
+                    .ok_or(ErrorCodes::OutOfMemory)?;
+                self.heap_bytes += size;
+                Ok(base)
+            }
+        }
+    }
+
+
+    /// Resize the heap block at `address` to `new_size` bytes and return its new address.
+    /// A null address is equivalent to a new allocation.
+    pub fn reallocate(&mut self, address: Address, new_size: usize) -> Result<Address, ErrorCodes> {
+        match self.model {
+            MemoryModel::Host => self.heap.reallocate(address as *mut u8, new_size).map(|block| block as Address),
+            MemoryModel::Virtual => {
+
+                if address == 0 {
+                    return self.allocate(new_size, 1, false);
+                }
+
+                let (size, align) = self.heap_block(address).map(|region| (region.size, region.align))?;
+                let new_address = self.allocate(new_size, align, false)?;
+
+                let old_block = self.regions.remove(&address).unwrap();
+                let new_block = &self.regions[&new_address];
+                unsafe {
+                    new_block.host.copy_from_nonoverlapping(old_block.host, size.min(new_size));
+                }
+
+                self.freed.insert(address);
+                self.heap_bytes -= size;
+                Ok(new_address)
+            }
+        }
+    }
+
+
+    /// Deallocate the heap block at `address`. Freeing a null address does nothing.
+    pub fn free(&mut self, address: Address) -> Result<(), ErrorCodes> {
+        match self.model {
+            MemoryModel::Host => self.heap.free(address as *mut u8),
+            MemoryModel::Virtual => {
+
+                if address == 0 {
+                    return Ok(());
+                }
+
+                let size = self.heap_block(address)?.size;
+                self.regions.remove(&address);
+                self.freed.insert(address);
+                self.heap_bytes -= size;
+                Ok(())
+            }
+        }
+    }
+
+
+    /// Return the heap block that starts at `address`.
+    fn heap_block(&self, address: Address) -> Result<&Region, ErrorCodes> {
+        match self.regions.get(&address) {
+            Some(region) if region.kind == RegionKind::Heap => Ok(region),
+            _ if self.freed.contains(&address) => Err(ErrorCodes::DoubleFree),
+            _ => Err(ErrorCodes::InvalidPointer)
+        }
+    }
+
+
+    /// Return the size of the live heap block at `address`.
+    pub fn block_size(&self, address: Address) -> Option<usize> {
+        match self.model {
+            MemoryModel::Host => self.heap.block_size(address as *mut u8),
+            MemoryModel::Virtual => self.heap_block(address).ok().map(|region| region.size),
+        }
+    }
+
+
+    /// Total size of the live heap blocks.
+    pub fn heap_bytes(&self) -> usize {
+        match self.model {
+            MemoryModel::Host => self.heap.live_bytes(),
+            MemoryModel::Virtual => self.heap_bytes,
+        }
+    }
+
+
+    /// Return the heap blocks that are still allocated, sorted by address.
+    pub fn leaked_blocks(&self) -> Vec<(Address, Layout)> {
+        match self.model {
+            MemoryModel::Host => self.heap.leaked_blocks(),
+            MemoryModel::Virtual => self.regions.iter()
+                .filter(|(_, region)| region.kind == RegionKind::Heap)
+                .map(|(&base, region)| (base, Layout::from_size_align(region.size, region.align).unwrap()))
+                .collect()
+        }
+    }
+
+}
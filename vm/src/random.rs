@@ -0,0 +1,32 @@
+use std::hash::{BuildHasher, RandomState};
+
+
+/// Pseudo-random number generator of the random interrupt, using the SplitMix64 algorithm.
+/// It's fast and every seed is valid, but it's not suitable for cryptography.
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+
+    /// Create a generator with a seed that differs between runs of the host process.
+    pub fn from_entropy() -> Self {
+        // The standard library seeds the hash maps with random keys from the OS
+        Self::new(RandomState::new().hash_one(0u64))
+    }
+
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+}
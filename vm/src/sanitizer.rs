@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+
+use vmlib::Address;
+
+use crate::fault::FaultKind;
+
+
+/// Number of bytes allocated past the end of every heap block in sanitizer mode.
+/// Accesses to these bytes are reported as heap buffer overflows.
+pub const REDZONE_SIZE: usize = 32;
+
+
+/// Shadow metadata of the heap blocks allocated by the program, used to detect invalid heap accesses.
+/// Initialization of the operation stack is tracked by the stack itself.
+#[derive(Default)]
+pub struct Sanitizer {
+
+    /// Live blocks and the size requested by the program, by address. The redzone follows the requested size.
+    blocks: BTreeMap<Address, usize>,
+    /// Blocks that were freed and whose memory was not handed out again by the allocator, by address.
+    freed: BTreeMap<Address, usize>,
+
+}
+
+impl Sanitizer {
+
+    /// Record a new block of `size` bytes, followed by its redzone.
+    pub fn allocated(&mut self, block: Address, size: usize) {
+
+        // The allocator may reuse the memory of freed blocks
+        let end = block + size + REDZONE_SIZE;
+        let reused: Vec<Address> = self.freed.range(..end)
+            .filter(|&(&base, &freed_size)| base + freed_size + REDZONE_SIZE > block)
+            .map(|(&base, _)| base)
+            .collect();
+        for base in reused {
+            self.freed.remove(&base);
+        }
+
+        self.blocks.insert(block, size);
+    }
+
+
+    /// Record that the block at `block` was freed. Does nothing if the block is not live.
+    pub fn freed(&mut self, block: Address) {
+        if let Some(size) = self.blocks.remove(&block) {
+            self.freed.insert(block, size);
+        }
+    }
+
+
+    /// Return the size requested by the program for the live block at `block`.
+    pub fn block_size(&self, block: Address) -> Option<usize> {
+        self.blocks.get(&block).copied()
+    }
+
+
+    /// Check a `size`-byte access at `address` against the heap blocks.
+    /// Accesses outside of the heap blocks and their redzones are not checked.
+    pub fn check_access(&self, address: Address, size: usize) -> Result<(), FaultKind> {
+
+        let closest_block = |blocks: &BTreeMap<Address, usize>| {
+            blocks.range(..=address)
+                .next_back()
+                .map(|(&base, &size)| (base, base + size))
+                .filter(|&(_, end)| address < end + REDZONE_SIZE)
+        };
+
+        if let Some((_, end)) = closest_block(&self.blocks) {
+            return if address.checked_add(size).is_none_or(|access_end| access_end > end) {
+                Err(FaultKind::HeapBufferOverflow(address))
+            } else {
+                Ok(())
+            };
+        }
+
+        if closest_block(&self.freed).is_some() {
+            return Err(FaultKind::UseAfterFree(address));
+        }
+
+        Ok(())
+    }
+
+}
@@ -0,0 +1,74 @@
+//! Tests for the program argument and environment interrupts.
+
+mod common;
+
+use common::{run, Program};
+use vm::{ExitStatus, VM};
+use vmlib::{ErrorCodes, Interrupts};
+
+
+#[test]
+fn read_arguments() {
+    let mut program = Program::new();
+    let buf = program.data(&[0; 16]);
+    program.interrupt(Interrupts::ArgCount).print8();
+    for index in 0..2 {
+        program.push8(index).data_address(buf).push8(16).interrupt(Interrupts::Arg).print_buffer(buf);
+    }
+    program.exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder().args(["first", "second"]));
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "2firstsecond");
+}
+
+
+#[test]
+fn argument_out_of_range() {
+    let mut program = Program::new();
+    let buf = program.data(&[0; 16]);
+    program.push8(1).data_address(buf).push8(16).interrupt(Interrupts::Arg).print8().exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder().args(["only"]));
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::InvalidArgument)));
+    assert_eq!(output, "0");
+}
+
+
+#[test]
+fn argument_overflow() {
+    let mut program = Program::new();
+    let buf = program.data(&[0; 4]);
+    // The length of an argument that doesn't fit is returned, so that the program can retry with a larger buffer
+    program.push8(0).data_address(buf).push8(4).interrupt(Interrupts::Arg).print8().exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder().args(["too long"]));
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::Overflow)));
+    assert_eq!(output, "8");
+}
+
+
+#[test]
+fn read_environment() {
+    let mut program = Program::new();
+    let buf = program.data(&[0; 16]);
+    program.string("GREETING").data_address(buf).push8(16).interrupt(Interrupts::GetEnv).print_buffer(buf);
+    program.string("MISSING").data_address(buf).push8(16).interrupt(Interrupts::GetEnv).print8();
+    program.exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder().env_var("GREETING", "hello"));
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::VariableNotFound)));
+    assert_eq!(output, "hello0");
+}
+
+
+#[test]
+fn environment_not_inherited() {
+    let mut program = Program::new();
+    let buf = program.data(&[0; 64]);
+    program.string("PATH").data_address(buf).push8(64).interrupt(Interrupts::GetEnv).exit_with_error_code();
+
+    // Embedders only pass the variables they choose
+    let (result, _) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::VariableNotFound)));
+}
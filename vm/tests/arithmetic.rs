@@ -0,0 +1,94 @@
+//! Tests for the integer division, checked and saturating arithmetic instructions.
+
+mod common;
+
+use common::{run, Program};
+use vm::{ExitStatus, VM};
+use vmlib::{ByteCodes, ErrorCodes, Interrupts};
+
+
+/// Print the result on top of the stack with `print`, then the error code it set, and clear the error code.
+fn print_result(program: &mut Program, print: Interrupts) {
+    program.interrupt(print).print_char(':')
+        .instruction(ByteCodes::ReadError).interrupt(Interrupts::Print4).print_char(' ')
+        .instruction(ByteCodes::SetErrorConst).operand(&(ErrorCodes::NoError as i32).to_le_bytes());
+}
+
+
+#[test]
+fn unsigned_division() {
+    let mut program = Program::new();
+    // As a signed integer, 0xf0 would be -16
+    program.push1(0xf0).push1(3).instruction(ByteCodes::DivUint1);
+    print_result(&mut program, Interrupts::Print1);
+    program.push8(u64::MAX).push8(10).instruction(ByteCodes::ModUint8);
+    print_result(&mut program, Interrupts::Print8);
+    program.exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "80:0 5:0 ");
+}
+
+
+#[test]
+fn division_by_zero() {
+    let mut program = Program::new();
+    program.push4(7).push4(0).instruction(ByteCodes::DivInt4);
+    print_result(&mut program, Interrupts::Print4);
+    program.push8(7).push8(0).instruction(ByteCodes::ModInt8);
+    print_result(&mut program, Interrupts::Print8);
+    program.push4(7).push4(0).instruction(ByteCodes::DivUint4);
+    print_result(&mut program, Interrupts::Print4);
+    program.exit_with_error_code();
+
+    let division_by_zero = ErrorCodes::DivisionByZero as i32;
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, format!("0:{division_by_zero} 0:{division_by_zero} 0:{division_by_zero} "));
+}
+
+
+#[test]
+fn checked_arithmetic() {
+    let mut program = Program::new();
+    program.push1(100).push1(27).instruction(ByteCodes::CheckedAddInt1);
+    print_result(&mut program, Interrupts::Print1);
+    // The wrapped result is kept when the operation overflows
+    program.push1(100).push1(28).instruction(ByteCodes::CheckedAddInt1);
+    print_result(&mut program, Interrupts::Print1);
+    program.push2(1).push2(2).instruction(ByteCodes::CheckedSubUint2);
+    print_result(&mut program, Interrupts::Print2);
+    program.push4(1 << 16).push4(1 << 16).instruction(ByteCodes::CheckedMulUint4);
+    print_result(&mut program, Interrupts::Print4);
+    program.push8(i64::MIN as u64).push8(-1i64 as u64).instruction(ByteCodes::CheckedMulInt8);
+    print_result(&mut program, Interrupts::Print8);
+    program.exit_with_error_code();
+
+    let overflow = ErrorCodes::Overflow as i32;
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, format!("127:0 128:{overflow} 65535:{overflow} 0:{overflow} {}:{overflow} ", i64::MIN as u64));
+}
+
+
+#[test]
+fn saturating_arithmetic() {
+    let mut program = Program::new();
+    program.push1(100).push1(28).instruction(ByteCodes::SaturatingAddInt1);
+    print_result(&mut program, Interrupts::Print1);
+    program.push1(-100i8 as u8).push1(100).instruction(ByteCodes::SaturatingSubInt1);
+    print_result(&mut program, Interrupts::Print1);
+    program.push2(1).push2(2).instruction(ByteCodes::SaturatingSubUint2);
+    print_result(&mut program, Interrupts::Print2);
+    program.push4(1 << 16).push4(1 << 16).instruction(ByteCodes::SaturatingMulUint4);
+    print_result(&mut program, Interrupts::Print4);
+    program.push8(i64::MIN as u64).push8(-1i64 as u64).instruction(ByteCodes::SaturatingMulInt8);
+    print_result(&mut program, Interrupts::Print8);
+    program.exit_with_error_code();
+
+    // Saturating operations don't set the error code
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, format!("127:0 128:0 0:0 {}:0 {}:0 ", u32::MAX, i64::MAX));
+}
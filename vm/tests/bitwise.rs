@@ -0,0 +1,71 @@
+//! Tests for the bitwise, shift, rotate and byte swap instructions.
+
+mod common;
+
+use common::{run, Program};
+use vm::{ExitStatus, VM};
+use vmlib::{ByteCodes, ErrorCodes, Interrupts};
+
+
+#[test]
+fn shift_by_width_or_more() {
+    let mut program = Program::new();
+    program.push1(1).push1(8).instruction(ByteCodes::ShiftLeft1).print1().print_char(' ')
+        .push2(0x8000).push1(16).instruction(ByteCodes::ShiftRight2).interrupt(Interrupts::Print2).print_char(' ')
+        .push8(1).push1(200).instruction(ByteCodes::ShiftLeft8).print8().print_char(' ')
+        // Arithmetic shifts fill the value with its sign bit
+        .push4(-8i32 as u32).push1(32).instruction(ByteCodes::ShiftRightArith4).interrupt(Interrupts::PrintInt4).print_char(' ')
+        .push1(0x40).push1(9).instruction(ByteCodes::ShiftRightArith1).print1()
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "0 0 0 -1 0");
+}
+
+
+#[test]
+fn logic_operations() {
+    let mut program = Program::new();
+    program.push1(0b1100).push1(0b1010).instruction(ByteCodes::BitAnd1).print1().print_char(' ')
+        .push2(0b1100).push2(0b1010).instruction(ByteCodes::BitOr2).interrupt(Interrupts::Print2).print_char(' ')
+        .push4(0b1100).push4(0b1010).instruction(ByteCodes::BitXor4).print4().print_char(' ')
+        .push8(0xff00ff).instruction(ByteCodes::BitNot8).print8()
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, format!("{} {} {} {}", 0b1000, 0b1110, 0b110, !0xff00ffu64));
+}
+
+
+#[test]
+fn shifts_and_rotations() {
+    let mut program = Program::new();
+    program.push4(0x0f).push1(4).instruction(ByteCodes::ShiftLeft4).print4().print_char(' ')
+        .push2(0xf000).push1(4).instruction(ByteCodes::ShiftRight2).interrupt(Interrupts::Print2).print_char(' ')
+        .push2(0xf000).push1(4).instruction(ByteCodes::ShiftRightArith2).interrupt(Interrupts::Print2).print_char(' ')
+        .push1(0x81).push1(1).instruction(ByteCodes::RotateLeft1).print1().print_char(' ')
+        .push8(1).push1(1).instruction(ByteCodes::RotateRight8).print8().print_char(' ')
+        // Rotations use the count modulo the width
+        .push4(0x12345678).push1(36).instruction(ByteCodes::RotateLeft4).print4()
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, format!("{} {} {} {} {} {}", 0xf0, 0xf00, 0xff00, 0x03, 1u64 << 63, 0x23456781));
+}
+
+
+#[test]
+fn byte_swap() {
+    let mut program = Program::new();
+    program.push2(0x1234).instruction(ByteCodes::ByteSwap2).interrupt(Interrupts::Print2).print_char(' ')
+        .push4(0x12345678).instruction(ByteCodes::ByteSwap4).print4().print_char(' ')
+        .push8(0x0102030405060708).instruction(ByteCodes::ByteSwap8).print8()
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, format!("{} {} {}", 0x3412, 0x78563412, 0x0807060504030201u64));
+}
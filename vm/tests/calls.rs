@@ -0,0 +1,100 @@
+//! Tests for the call stack instructions.
+
+mod common;
+
+use common::{run, run_fault, Program};
+use vm::{ExitStatus, FaultKind, VM};
+use vmlib::{ByteCodes, ErrorCodes, VirtualAddress, INSTRUCTION_SIZE};
+
+
+/// Set the address operand of the instruction at `instruction`, once the address it refers to is known.
+fn patch_address(program: &mut Program, instruction: usize, address: usize) {
+    let operand = instruction + INSTRUCTION_SIZE;
+    program.code[operand..operand + 8].copy_from_slice(&address.to_le_bytes());
+}
+
+
+#[test]
+fn call_and_return() {
+    let mut program = Program::new();
+    let call = program.position();
+    program.address_instruction(ByteCodes::Call, 0).print_char('b');
+    let call_indirect = program.position();
+    program.push8(0).instruction(ByteCodes::CallIndirect).print_char('c')
+        .exit(ErrorCodes::NoError);
+    let function = program.position();
+    program.print_char('a').instruction(ByteCodes::Return);
+
+    patch_address(&mut program, call, function);
+    patch_address(&mut program, call_indirect, function);
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "abac");
+}
+
+
+#[test]
+fn tail_call_returns_to_the_caller() {
+    let mut program = Program::new();
+    let call = program.position();
+    program.address_instruction(ByteCodes::Call, 0).print_char('c')
+        .exit(ErrorCodes::NoError);
+    let function = program.position();
+    program.address_instruction(ByteCodes::TailCall, 0);
+    let tail_function = program.position();
+    program.print_char('t').instruction(ByteCodes::Return);
+
+    patch_address(&mut program, call, function);
+    patch_address(&mut program, function, tail_function);
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "tc");
+}
+
+
+#[test]
+fn backtrace_lists_call_sites() {
+    let mut program = Program::new();
+    let outer_call = program.position();
+    program.address_instruction(ByteCodes::Call, 0);
+    let inner_call = program.position();
+    program.address_instruction(ByteCodes::Call, 0);
+    let inner = program.position();
+    program.pop8();
+
+    patch_address(&mut program, outer_call, inner_call);
+    patch_address(&mut program, inner_call, inner);
+
+    let fault = run_fault(&program, VM::builder());
+    assert_eq!(fault.kind, FaultKind::StackUnderflow);
+    let addresses: Vec<VirtualAddress> = fault.backtrace.0.iter().map(|frame| frame.address).collect();
+    assert_eq!(addresses, [VirtualAddress(inner), VirtualAddress(inner_call), VirtualAddress(outer_call)]);
+}
+
+
+#[test]
+fn call_stack_overflow() {
+    let mut program = Program::new();
+    // Call the same address forever
+    program.address_instruction(ByteCodes::Call, 0);
+
+    let fault = run_fault(&program, VM::builder().call_stack_depth(4));
+    assert_eq!(fault.kind, FaultKind::CallStackOverflow);
+    assert_eq!(fault.pc, VirtualAddress(0));
+    assert_eq!(fault.backtrace.0.len(), 5);
+}
+
+
+#[test]
+fn call_stack_underflow() {
+    let mut program = Program::new();
+    program.push1(0).push1(0);
+    let ret = program.position();
+    program.instruction(ByteCodes::Return);
+
+    let fault = run_fault(&program, VM::builder());
+    assert_eq!(fault.kind, FaultKind::CallStackUnderflow);
+    assert_eq!(fault.pc, VirtualAddress(ret));
+}
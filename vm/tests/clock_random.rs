@@ -0,0 +1,103 @@
+//! Tests for the clock, sleep and random number interrupts.
+
+mod common;
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use common::{build, Program};
+use vm::{ExitStatus, FaultKind, Limit, Limits, VmBuilder, VM};
+use vmlib::{ByteCodes, ErrorCodes, Interrupts};
+
+
+/// Parse the numbers printed one per line.
+fn numbers(output: &str) -> Vec<u64> {
+    output.lines().map(|line| line.parse().unwrap()).collect()
+}
+
+
+/// Run the program on a VM configured by `builder` and return its exit status and the numbers it printed.
+fn run(program: &Program, builder: VmBuilder) -> (Result<ExitStatus, FaultKind>, Vec<u64>) {
+    let (result, output) = common::run(program, builder);
+    (result, numbers(&output))
+}
+
+
+fn random_program(count: usize) -> Program {
+    let mut program = Program::new();
+    for _ in 0..count {
+        program.interrupt(Interrupts::Random).print8().print_char('\n');
+    }
+    program.exit(ErrorCodes::NoError);
+    program
+}
+
+
+#[test]
+fn seeded_random_is_reproducible() {
+    let program = random_program(4);
+
+    let (result, first) = run(&program, VM::builder().seed(42));
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    let (_, second) = run(&program, VM::builder().seed(42));
+    let (_, other) = run(&program, VM::builder().seed(43));
+
+    assert_eq!(first, second);
+    assert_ne!(first, other);
+    assert!(first.windows(2).all(|pair| pair[0] != pair[1]));
+}
+
+
+#[test]
+fn random_state_carries_over_between_runs() {
+    let program = random_program(2);
+
+    let (mut vm, output) = build(VM::builder().seed(7));
+    vm.run(&program.binary()).unwrap();
+    vm.run(&program.binary()).unwrap();
+
+    let (_, expected) = run(&random_program(4), VM::builder().seed(7));
+    assert_eq!(numbers(&output.to_string_lossy()), expected);
+}
+
+
+#[test]
+fn sleep_advances_monotonic_time() {
+    let mut program = Program::new();
+    program.interrupt(Interrupts::MonotonicTime)
+        .push8(20_000_000).interrupt(Interrupts::Sleep)
+        .interrupt(Interrupts::MonotonicTime)
+        .instruction(ByteCodes::Swap8)
+        .instruction(ByteCodes::SubInt8)
+        .print8().print_char('\n')
+        .exit(ErrorCodes::NoError);
+
+    let (result, numbers) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert!(numbers[0] >= 20_000_000, "slept for {} ns", numbers[0]);
+}
+
+
+#[test]
+fn sleep_past_timeout() {
+    let mut program = Program::new();
+    program.push8(60_000_000_000).interrupt(Interrupts::Sleep).exit(ErrorCodes::NoError);
+
+    // The sleep ends at the deadline instead of blocking for a minute
+    let limits = Limits { timeout: Some(Duration::from_millis(50)), ..Limits::default() };
+    let (result, _) = run(&program, VM::builder().limits(limits));
+    assert_eq!(result, Err(FaultKind::LimitExceeded(Limit::Time)));
+}
+
+
+#[test]
+fn unix_time() {
+    let mut program = Program::new();
+    program.interrupt(Interrupts::UnixTime).print8().print_char('\n').exit(ErrorCodes::NoError);
+
+    let before = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos() as u64;
+    let (result, numbers) = run(&program, VM::builder());
+    let after = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos() as u64;
+
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert!((before..=after).contains(&numbers[0]));
+}
@@ -0,0 +1,186 @@
+//! Hand-assembled programs shared by the integration tests.
+//! Each test file uses a different subset of the builder, so unused helpers are allowed.
+#![allow(dead_code)]
+
+use vm::{ExitStatus, FaultKind, SharedBuffer, VmBuilder, VmFault, VM};
+use vmlib::metadata::{ProgramMetadata, Symbol};
+use vmlib::{ByteCodes, ErrorCodes, Interrupts, VirtualAddress};
+
+
+/// Hand-assembled program, executed from its first byte.
+/// Its data segment is part of the program metadata with its exports and host functions.
+pub struct Program {
+    pub code: Vec<u8>,
+    pub metadata: ProgramMetadata,
+}
+
+impl Program {
+
+    pub fn new() -> Self {
+        Self { code: Vec::new(), metadata: ProgramMetadata::default() }
+    }
+
+
+    /// Address of the next instruction.
+    pub fn position(&self) -> usize {
+        self.code.len()
+    }
+
+
+    pub fn instruction(&mut self, instruction: ByteCodes) -> &mut Self {
+        self.code.extend_from_slice(&instruction.to_le_bytes());
+        self
+    }
+
+
+    /// Append raw operand bytes to the last instruction.
+    pub fn operand(&mut self, bytes: &[u8]) -> &mut Self {
+        self.code.extend_from_slice(bytes);
+        self
+    }
+
+
+    /// Append an instruction that takes an address or a size as its operand.
+    pub fn address_instruction(&mut self, instruction: ByteCodes, address: usize) -> &mut Self {
+        self.instruction(instruction).operand(&address.to_le_bytes())
+    }
+
+
+    pub fn push1(&mut self, value: u8) -> &mut Self {
+        self.instruction(ByteCodes::LoadConst1).operand(&[value])
+    }
+
+
+    pub fn push2(&mut self, value: u16) -> &mut Self {
+        self.instruction(ByteCodes::LoadConst2).operand(&value.to_le_bytes())
+    }
+
+
+    pub fn push4(&mut self, value: u32) -> &mut Self {
+        self.instruction(ByteCodes::LoadConst4).operand(&value.to_le_bytes())
+    }
+
+
+    pub fn push8(&mut self, value: u64) -> &mut Self {
+        self.instruction(ByteCodes::LoadConst8).operand(&value.to_le_bytes())
+    }
+
+
+    pub fn pop8(&mut self) -> &mut Self {
+        self.address_instruction(ByteCodes::PopConst, 8)
+    }
+
+
+    pub fn interrupt(&mut self, interrupt: Interrupts) -> &mut Self {
+        self.instruction(ByteCodes::IntrConst).operand(&[interrupt as u8])
+    }
+
+
+    pub fn print1(&mut self) -> &mut Self {
+        self.interrupt(Interrupts::Print1)
+    }
+
+
+    pub fn print4(&mut self) -> &mut Self {
+        self.interrupt(Interrupts::Print4)
+    }
+
+
+    pub fn print8(&mut self) -> &mut Self {
+        self.interrupt(Interrupts::Print8)
+    }
+
+
+    pub fn print_char(&mut self, c: char) -> &mut Self {
+        self.push1(c as u8).interrupt(Interrupts::PrintChar)
+    }
+
+
+    /// Reserve `bytes` in the data segment and return their offset.
+    pub fn data(&mut self, bytes: &[u8]) -> usize {
+        self.metadata.data.extend_from_slice(bytes);
+        self.metadata.data.len() - bytes.len()
+    }
+
+
+    /// Push the address of the data segment byte at `offset`.
+    pub fn data_address(&mut self, offset: usize) -> &mut Self {
+        self.address_instruction(ByteCodes::VirtualDataConstToReal, offset)
+    }
+
+
+    /// Push the address and the length of a string stored in the data segment.
+    pub fn string(&mut self, string: &str) -> &mut Self {
+        let offset = self.data(string.as_bytes());
+        self.data_address(offset).push8(string.len() as u64)
+    }
+
+
+    /// Print the string stored in the data segment at `offset`, whose length is on top of the stack.
+    pub fn print_buffer(&mut self, offset: usize) -> &mut Self {
+        self.data_address(offset).instruction(ByteCodes::Swap8).interrupt(Interrupts::PrintString)
+    }
+
+
+    /// Declare a host function like the `hostfn` directive and return its number.
+    pub fn declare_host_function(&mut self, name: &str) -> u32 {
+        self.metadata.host_functions.push(name.to_string());
+        self.metadata.host_functions.len() as u32 - 1
+    }
+
+
+    pub fn host_call(&mut self, id: u32) -> &mut Self {
+        self.instruction(ByteCodes::HostCall).operand(&id.to_le_bytes())
+    }
+
+
+    /// Export the next instruction under `name`, like the `export` directive.
+    pub fn export(&mut self, name: &str) -> &mut Self {
+        self.metadata.exports.push(Symbol { name: name.to_string(), address: VirtualAddress(self.code.len()) });
+        self
+    }
+
+
+    pub fn exit(&mut self, code: ErrorCodes) -> &mut Self {
+        self.push4(code as i32 as u32).instruction(ByteCodes::Exit)
+    }
+
+
+    /// Exit with the current VM error code.
+    pub fn exit_with_error_code(&mut self) -> &mut Self {
+        self.instruction(ByteCodes::ReadError).instruction(ByteCodes::Exit)
+    }
+
+
+    pub fn binary(&self) -> Vec<u8> {
+        let mut binary = self.code.clone();
+        self.metadata.append_to(&mut binary);
+        binary
+    }
+
+}
+
+
+/// Build a VM configured by `builder` whose console output is captured.
+pub fn build(builder: VmBuilder) -> (VM, SharedBuffer) {
+    let output = SharedBuffer::new();
+    (builder.output(output.clone()).build(), output)
+}
+
+
+/// Run the program on a VM configured by `builder` and return its exit status and output.
+pub fn run(program: &Program, builder: VmBuilder) -> (Result<ExitStatus, FaultKind>, String) {
+    let (mut vm, output) = build(builder);
+    let result = vm.run(&program.binary()).map_err(|fault| fault.kind);
+    (result, output.to_string_lossy())
+}
+
+
+/// Run the program on a VM configured by `builder` and return the fault that stopped it.
+pub fn run_fault(program: &Program, builder: VmBuilder) -> VmFault {
+    let (mut vm, _) = build(builder);
+    match vm.run(&program.binary()) {
+        Ok(status) => panic!("the program exited with {status:?} instead of faulting"),
+        Err(fault) => fault
+    }
+}
@@ -0,0 +1,61 @@
+//! Tests for the sized comparison and select instructions.
+
+mod common;
+
+use common::{run, Program};
+use vm::{ExitStatus, VM};
+use vmlib::{ByteCodes, ErrorCodes, Interrupts};
+
+
+#[test]
+fn signed_and_unsigned_comparisons() {
+    let mut program = Program::new();
+    // -1 is less than 1 as a signed integer, but greater as an unsigned one
+    program.push1(-1i8 as u8).push1(1).instruction(ByteCodes::LessInt1).print1()
+        .push1(-1i8 as u8).push1(1).instruction(ByteCodes::LessUint1).print1()
+        .push2(-5i16 as u16).push2(-5i16 as u16).instruction(ByteCodes::LessEqualInt2).print1()
+        .push4(7).push4(7).instruction(ByteCodes::GreaterInt4).print1()
+        .push4(u32::MAX).push4(7).instruction(ByteCodes::GreaterUint4).print1()
+        .push8(i64::MIN as u64).push8(0).instruction(ByteCodes::GreaterEqualInt8).print1()
+        .push8(i64::MIN as u64).push8(0).instruction(ByteCodes::GreaterEqualUint8).print1()
+        .push8(3).push8(3).instruction(ByteCodes::EqualInt8).print1()
+        .push2(3).push2(4).instruction(ByteCodes::NotEqualInt2).print1()
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "101010111");
+}
+
+
+#[test]
+fn float_comparisons() {
+    let mut program = Program::new();
+    program.push4((-0.0f32).to_bits()).push4(0.0f32.to_bits()).instruction(ByteCodes::EqualFloat4).print1()
+        .push8(1.5f64.to_bits()).push8(1.5f64.to_bits()).instruction(ByteCodes::LessEqualFloat8).print1()
+        .push8(2.5f64.to_bits()).push8(1.5f64.to_bits()).instruction(ByteCodes::GreaterFloat8).print1()
+        // Every ordered comparison with NaN is false
+        .push4(f32::NAN.to_bits()).push4(1.0f32.to_bits()).instruction(ByteCodes::GreaterEqualFloat4).print1()
+        .push4(f32::NAN.to_bits()).push4(1.0f32.to_bits()).instruction(ByteCodes::LessFloat4).print1()
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "11100");
+}
+
+
+#[test]
+fn select() {
+    let mut program = Program::new();
+    program.push1(10).push1(20).push1(1).instruction(ByteCodes::Select1).print1().print_char(' ')
+        .push2(10).push2(20).push1(0).instruction(ByteCodes::Select2).interrupt(Interrupts::Print2).print_char(' ')
+        // Any nonzero condition selects the first value
+        .push4(10).push4(20).push1(0x80).instruction(ByteCodes::Select4).print4().print_char(' ')
+        .push8(10).push8(20).push8(5).push8(3).instruction(ByteCodes::LessInt8).instruction(ByteCodes::Select8).print8()
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "10 20 10 20");
+}
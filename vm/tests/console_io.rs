@@ -0,0 +1,167 @@
+//! Tests for the console input and output of the embedding API.
+
+mod common;
+
+use std::io::Cursor;
+
+use common::Program;
+use vm::{ExecutionMode, ExitStatus, FaultKind, Limit, Limits, SharedBuffer, VM};
+use vmlib::{ByteCodes, ErrorCodes, Interrupts};
+
+
+/// Run the program with the given console input and return its exit status and output.
+fn run(program: &Program, input: &[u8], buffered: bool) -> (ExitStatus, String) {
+    let builder = VM::builder().input(Cursor::new(input.to_vec())).buffered(buffered);
+    let (result, output) = common::run(program, builder);
+    (result.unwrap(), output)
+}
+
+
+#[test]
+fn print_to_output() {
+    let mut program = Program::new();
+    program.push8(42).interrupt(Interrupts::Print8)
+        .push1(b'\n').interrupt(Interrupts::PrintChar)
+        .exit_with_error_code();
+
+    for buffered in [true, false] {
+        let (status, output) = run(&program, b"", buffered);
+        assert_eq!(status, ExitStatus::Exited(ErrorCodes::NoError));
+        assert_eq!(output, "42\n");
+    }
+}
+
+
+#[test]
+fn read_bytes_from_input() {
+    let mut program = Program::new();
+    program.push8(3).interrupt(Interrupts::ReadBytes)
+        .interrupt(Interrupts::PrintChar)
+        .interrupt(Interrupts::PrintChar)
+        .interrupt(Interrupts::PrintChar)
+        .exit_with_error_code();
+
+    let (status, output) = run(&program, b"abcd", true);
+    assert_eq!(status, ExitStatus::Exited(ErrorCodes::NoError));
+    assert_eq!(output, "abc");
+}
+
+
+#[test]
+fn read_past_end_of_input() {
+    let mut program = Program::new();
+    program.push8(3).interrupt(Interrupts::ReadBytes).exit_with_error_code();
+
+    let (status, _) = run(&program, b"ab", true);
+    assert_eq!(status, ExitStatus::Exited(ErrorCodes::UnexpectedEOF));
+}
+
+
+#[test]
+fn read_more_bytes_than_the_stack_holds() {
+    let mut program = Program::new();
+    program.push8(u64::MAX).interrupt(Interrupts::ReadBytes).exit_with_error_code();
+
+    for mode in [ExecutionMode::Checked, ExecutionMode::Unchecked] {
+        let builder = VM::builder().input(Cursor::new(b"abc".to_vec())).mode(mode);
+        let (result, _) = common::run(&program, builder);
+        assert_eq!(result, Err(FaultKind::StackOverflow), "{mode:?}");
+    }
+}
+
+
+#[test]
+fn read_all_input() {
+    let mut program = Program::new();
+    program.interrupt(Interrupts::ReadAll).interrupt(Interrupts::Print8).exit_with_error_code();
+
+    let (status, output) = run(&program, b"hello", true);
+    assert_eq!(status, ExitStatus::Exited(ErrorCodes::NoError));
+    assert_eq!(output, "5");
+}
+
+
+#[test]
+fn read_all_more_than_the_stack_holds() {
+    let mut program = Program::new();
+    program.interrupt(Interrupts::ReadAll).interrupt(Interrupts::Print8).exit_with_error_code();
+
+    for mode in [ExecutionMode::Checked, ExecutionMode::Unchecked] {
+        // The input and its 8-byte length fill the 16-byte stack exactly
+        let builder = VM::builder().input(Cursor::new(b"12345678".to_vec())).opstack_size(16).mode(mode);
+        let (result, output) = common::run(&program, builder);
+        assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)), "{mode:?}");
+        assert_eq!(output, "8", "{mode:?}");
+
+        let builder = VM::builder().input(Cursor::new(b"123456789".to_vec())).opstack_size(16).mode(mode);
+        let (result, _) = common::run(&program, builder);
+        assert_eq!(result, Err(FaultKind::StackOverflow), "{mode:?}");
+    }
+}
+
+
+#[test]
+fn flush_interrupt() {
+    let mut program = Program::new();
+    program.push1(b'x').interrupt(Interrupts::PrintChar)
+        .interrupt(Interrupts::Flush)
+        .exit_with_error_code();
+
+    let (status, output) = run(&program, b"", true);
+    assert_eq!(status, ExitStatus::Exited(ErrorCodes::NoError));
+    assert_eq!(output, "x");
+}
+
+
+#[test]
+fn output_flushed_on_fault() {
+    let mut program = Program::new();
+    program.push1(b'x').interrupt(Interrupts::PrintChar)
+        .instruction(ByteCodes::Exit);
+
+    let output = SharedBuffer::new();
+    let mut vm = VM::builder().output(output.clone()).build();
+
+    let fault = vm.run(&program.binary()).unwrap_err();
+    assert_eq!(fault.kind, FaultKind::StackUnderflow);
+    assert_eq!(output.to_string_lossy(), "x");
+}
+
+
+#[test]
+fn output_limit() {
+    let mut program = Program::new();
+    program.push8(1234).interrupt(Interrupts::Print8)
+        .push8(5678).interrupt(Interrupts::Print8)
+        .exit_with_error_code();
+
+    let output = SharedBuffer::new();
+    let mut vm = VM::builder()
+        .output(output.clone())
+        .limits(Limits { max_output_bytes: Some(6), ..Limits::default() })
+        .build();
+
+    let fault = vm.run(&program.binary()).unwrap_err();
+    assert_eq!(fault.kind, FaultKind::LimitExceeded(Limit::Output));
+    assert_eq!(output.to_string_lossy(), "1234");
+}
+
+
+#[test]
+fn print_invalid_utf8() {
+    let invalid = [b'a', 0xff, 0xc3, b'b'];
+    let mut program = Program::new();
+    // The static copy is stored in the code itself, behind a jump
+    program.address_instruction(ByteCodes::JumpConst, 10 + invalid.len()).operand(&invalid)
+        .address_instruction(ByteCodes::LoadConst8, 10).push8(invalid.len() as u64)
+        .interrupt(Interrupts::PrintStaticString);
+    let offset = program.data(&invalid);
+    program.push8(invalid.len() as u64).print_buffer(offset)
+        .exit_with_error_code();
+
+    let output = SharedBuffer::new();
+    let mut vm = VM::builder().output(output.clone()).build();
+
+    assert_eq!(vm.run(&program.binary()).unwrap(), ExitStatus::Exited(ErrorCodes::NoError));
+    assert_eq!(output.contents(), [invalid, invalid].concat());
+}
@@ -0,0 +1,176 @@
+//! Tests for the conversion instructions and the number and string conversion interrupts.
+
+mod common;
+
+use common::{run, Program};
+use vm::{ExitStatus, VM};
+use vmlib::{ByteCodes, ErrorCodes, Interrupts, SHORTEST_FLOAT_PRECISION};
+
+
+#[test]
+fn extend_and_truncate() {
+    let mut program = Program::new();
+    program.push1(0x80).instruction(ByteCodes::SignExtend1To8).print8().print_char(' ')
+        .push1(0x80).instruction(ByteCodes::ZeroExtend1To8).print8().print_char(' ')
+        .push2(0xfffe).instruction(ByteCodes::SignExtend2To4).print4().print_char(' ')
+        .push4(0xffff_fffe).instruction(ByteCodes::ZeroExtend4To8).print8().print_char(' ')
+        // Truncation keeps the low bytes
+        .push8(0x1234_5678_9abc_def0).instruction(ByteCodes::Truncate8To4).print4().print_char(' ')
+        .push4(0x1234_5678).instruction(ByteCodes::Truncate4To1).print1()
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, format!("{} 128 {} 4294967294 {} {}", -128i64 as u64, -2i32 as u32, 0x9abc_def0u32, 0x78));
+}
+
+
+#[test]
+fn int_to_float() {
+    let mut program = Program::new();
+    program.push1(-3i8 as u8).instruction(ByteCodes::Int1ToFloat8).print8().print_char(' ')
+        .push1(0xfd).instruction(ByteCodes::Uint1ToFloat4).print4().print_char(' ')
+        .push8(u64::MAX).instruction(ByteCodes::Uint8ToFloat8).print8().print_char(' ')
+        .push8(-1i64 as u64).instruction(ByteCodes::Int8ToFloat4).print4()
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, format!("{} {} {} {}", (-3f64).to_bits(), 253f32.to_bits(), (u64::MAX as f64).to_bits(), (-1f32).to_bits()));
+}
+
+
+#[test]
+fn float_to_int() {
+    let mut program = Program::new();
+    // Floats are truncated toward zero, and out-of-range values and NaN saturate like Rust casts
+    program.push8((-2.9f64).to_bits()).instruction(ByteCodes::Float8ToInt4).print4().print_char(' ')
+        .push4(300f32.to_bits()).instruction(ByteCodes::Float4ToInt1).print1().print_char(' ')
+        .push8((-1.5f64).to_bits()).instruction(ByteCodes::Float8ToUint2).interrupt(Interrupts::Print2).print_char(' ')
+        .push8(f64::INFINITY.to_bits()).instruction(ByteCodes::Float8ToUint8).print8().print_char(' ')
+        .push4(f32::NAN.to_bits()).instruction(ByteCodes::Float4ToInt8).print8()
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, format!("{} 127 0 {} 0", -2i32 as u32, u64::MAX));
+}
+
+
+#[test]
+fn float_precision() {
+    let mut program = Program::new();
+    program.push4(1.5f32.to_bits()).instruction(ByteCodes::Float4ToFloat8).print8().print_char(' ')
+        .push8(0.1f64.to_bits()).instruction(ByteCodes::Float8ToFloat4).print4()
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, format!("{} {}", 1.5f64.to_bits(), 0.1f32.to_bits()));
+}
+
+
+#[test]
+fn format_integers() {
+    let mut program = Program::new();
+    let buf = program.data(&[0; 32]);
+    program.push1(-1i8 as u8).data_address(buf).push8(32).interrupt(Interrupts::FormatInt1).print_buffer(buf).print_char(' ')
+        .push1(0xff).data_address(buf).push8(32).interrupt(Interrupts::FormatUint1).print_buffer(buf).print_char(' ')
+        .push2(u16::MAX).data_address(buf).push8(32).interrupt(Interrupts::FormatUint2).print_buffer(buf).print_char(' ')
+        .push4(-70000i32 as u32).data_address(buf).push8(32).interrupt(Interrupts::FormatInt4).print_buffer(buf).print_char(' ')
+        .push8(i64::MIN as u64).data_address(buf).push8(32).interrupt(Interrupts::FormatInt8).print_buffer(buf).print_char(' ')
+        .push8(u64::MAX).data_address(buf).push8(32).interrupt(Interrupts::FormatUint8).print_buffer(buf)
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "-1 255 65535 -70000 -9223372036854775808 18446744073709551615");
+}
+
+
+#[test]
+fn format_floats() {
+    let mut program = Program::new();
+    let buf = program.data(&[0; 32]);
+    program.push8(1.5f64.to_bits()).push1(3).data_address(buf).push8(32).interrupt(Interrupts::FormatFloat8).print_buffer(buf).print_char(' ')
+        .push8(1e21f64.to_bits()).push1(SHORTEST_FLOAT_PRECISION).data_address(buf).push8(32).interrupt(Interrupts::FormatFloat8).print_buffer(buf).print_char(' ')
+        .push4(0.1f32.to_bits()).push1(SHORTEST_FLOAT_PRECISION).data_address(buf).push8(32).interrupt(Interrupts::FormatFloat4).print_buffer(buf).print_char(' ')
+        .push4((-0.25f32).to_bits()).push1(1).data_address(buf).push8(32).interrupt(Interrupts::FormatFloat4).print_buffer(buf)
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "1.500 1000000000000000000000 0.1 -0.2");
+}
+
+
+#[test]
+fn format_into_small_buffer() {
+    let mut program = Program::new();
+    let buf = program.data(b"abc");
+    // The length of the text is pushed so that the program can retry with a larger buffer
+    program.push8(12345).data_address(buf).push8(3).interrupt(Interrupts::FormatUint8).interrupt(Interrupts::Print8).print_char(' ')
+        .push8(3).print_buffer(buf)
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::Overflow)));
+    assert_eq!(output, "5 abc");
+}
+
+
+#[test]
+fn parse_integers() {
+    let mut program = Program::new();
+    program.string("-128").interrupt(Interrupts::ParseInt1).interrupt(Interrupts::PrintInt1).print_char(' ')
+        .string("255").interrupt(Interrupts::ParseUint1).interrupt(Interrupts::Print1).print_char(' ')
+        // Surrounding whitespace, such as the newline of a line read from the console, is ignored
+        .string(" 65535\n").interrupt(Interrupts::ParseUint2).interrupt(Interrupts::Print2).print_char(' ')
+        .string("+4000000000").interrupt(Interrupts::ParseUint4).interrupt(Interrupts::Print4).print_char(' ')
+        .string("-9223372036854775808").interrupt(Interrupts::ParseInt8).interrupt(Interrupts::PrintInt8)
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "-128 255 65535 4000000000 -9223372036854775808");
+}
+
+
+#[test]
+fn parse_floats() {
+    let mut program = Program::new();
+    program.string("2.5e3").interrupt(Interrupts::ParseFloat8).push1(SHORTEST_FLOAT_PRECISION).interrupt(Interrupts::PrintFloat8).print_char(' ')
+        .string("-0.1\n").interrupt(Interrupts::ParseFloat4).push1(SHORTEST_FLOAT_PRECISION).interrupt(Interrupts::PrintFloat4).print_char(' ')
+        .string("inf").interrupt(Interrupts::ParseFloat8).push1(SHORTEST_FLOAT_PRECISION).interrupt(Interrupts::PrintFloat8)
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "2500 -0.1 inf");
+}
+
+
+#[test]
+fn parse_errors() {
+    let cases = [
+        (Interrupts::ParseInt1, Interrupts::Print1, "128", ErrorCodes::Overflow),
+        (Interrupts::ParseInt2, Interrupts::Print2, "-32769", ErrorCodes::Overflow),
+        (Interrupts::ParseUint8, Interrupts::Print8, "18446744073709551616", ErrorCodes::Overflow),
+        (Interrupts::ParseUint1, Interrupts::Print1, "-1", ErrorCodes::InvalidArgument),
+        (Interrupts::ParseInt4, Interrupts::Print4, "12a", ErrorCodes::InvalidArgument),
+        (Interrupts::ParseUint8, Interrupts::Print8, "", ErrorCodes::InvalidArgument),
+        (Interrupts::ParseInt8, Interrupts::Print8, "0x10", ErrorCodes::InvalidArgument),
+        (Interrupts::ParseFloat4, Interrupts::Print4, "1.5.2", ErrorCodes::InvalidArgument),
+        (Interrupts::ParseFloat8, Interrupts::Print8, "one", ErrorCodes::InvalidArgument),
+    ];
+
+    for (parse, print, text, error_code) in cases {
+        // Malformed input is parsed as zero, leaving the value below it on the stack
+        let mut program = Program::new();
+        program.push8(1234).string(text).interrupt(parse).interrupt(print).print_char(' ').interrupt(Interrupts::Print8).exit_with_error_code();
+
+        let (result, output) = run(&program, VM::builder());
+        assert_eq!(result, Ok(ExitStatus::Exited(error_code)), "{parse:?} {text:?}");
+        assert_eq!(output, "0 1234", "{parse:?} {text:?}");
+    }
+}
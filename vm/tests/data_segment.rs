@@ -0,0 +1,79 @@
+//! Tests for the writable data segment and its instructions.
+
+mod common;
+
+use common::{build, run, run_fault, Program};
+use vm::{ExitStatus, FaultKind, VM};
+use vmlib::{ByteCodes, ErrorCodes, VirtualAddress};
+
+
+/// Program with an initialized 8-byte counter set to 5, followed by 8 zero-initialized bytes.
+fn program_with_counter() -> Program {
+    let mut program = Program::new();
+    program.data(&5u64.to_le_bytes());
+    program.metadata.zeroed_data_size = 8;
+    program
+}
+
+
+#[test]
+fn load_and_store() {
+    let mut program = program_with_counter();
+    program.address_instruction(ByteCodes::LoadData8, 0).print8().print_char(' ')
+        .address_instruction(ByteCodes::LoadData8, 8).print8().print_char(' ')
+        .push8(42).address_instruction(ByteCodes::StoreData8, 8)
+        .address_instruction(ByteCodes::LoadData8, 8).print8().print_char(' ')
+        // Host pointers to the data segment work with the regular memory instructions
+        .push8(7).data_address(0).instruction(ByteCodes::Store8)
+        .push8(0).instruction(ByteCodes::VirtualDataToReal).instruction(ByteCodes::Load8).print8().print_char(' ')
+        .push4(0x01020304).address_instruction(ByteCodes::StoreData4, 12)
+        .address_instruction(ByteCodes::LoadData1, 12).print1()
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "5 0 42 7 4");
+}
+
+
+#[test]
+fn data_reset_between_runs() {
+    let mut program = program_with_counter();
+    program.address_instruction(ByteCodes::LoadData8, 0).push8(1).instruction(ByteCodes::AddInt8)
+        .address_instruction(ByteCodes::StoreData8, 0)
+        .address_instruction(ByteCodes::LoadData8, 0).print8()
+        .exit_with_error_code();
+
+    let (mut vm, output) = build(VM::builder());
+    for _ in 0..2 {
+        assert_eq!(vm.run(&program.binary()).unwrap(), ExitStatus::Exited(ErrorCodes::NoError));
+    }
+    assert_eq!(output.to_string_lossy(), "66");
+}
+
+
+#[test]
+fn load_past_end_of_data() {
+    let mut program = program_with_counter();
+    program.push1(0);
+    let load = program.position();
+    // The last 8-byte value starts at 8
+    program.address_instruction(ByteCodes::LoadData8, 9);
+
+    let fault = run_fault(&program, VM::builder());
+    assert_eq!(fault.kind, FaultKind::DataOutOfRange);
+    assert_eq!(fault.pc, VirtualAddress(load));
+}
+
+
+#[test]
+fn store_past_end_of_data() {
+    let mut program = program_with_counter();
+    program.push1(1);
+    let store = program.position();
+    program.address_instruction(ByteCodes::StoreData1, 16);
+
+    let fault = run_fault(&program, VM::builder());
+    assert_eq!(fault.kind, FaultKind::DataOutOfRange);
+    assert_eq!(fault.pc, VirtualAddress(store));
+}
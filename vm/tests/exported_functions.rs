@@ -0,0 +1,115 @@
+//! Tests for calling the functions exported by a program from the embedding API.
+
+mod common;
+
+use common::Program;
+use vm::{ExitStatus, FaultKind, VM};
+use vmlib::{ByteCodes, ErrorCodes};
+
+
+/// Program that starts with an entry point that exits successfully, followed by the exported functions.
+fn exporting_program() -> Program {
+    let mut program = Program::new();
+    program.exit(ErrorCodes::NoError);
+    program
+}
+
+
+fn result8(bytes: Vec<u8>) -> u64 {
+    u64::from_le_bytes(bytes.try_into().unwrap())
+}
+
+
+#[test]
+fn call_with_arguments() {
+    let mut program = exporting_program();
+    program.export("sub").instruction(ByteCodes::SubInt8).instruction(ByteCodes::Return);
+
+    let mut vm = VM::builder().build();
+    vm.load(&program.binary()).unwrap();
+
+    // The last argument is on top of the stack
+    let result = vm.call("sub", &[&50u64.to_le_bytes(), &8u64.to_le_bytes()]).unwrap();
+    assert_eq!(result8(result), 42);
+}
+
+
+#[test]
+fn data_persists_across_calls() {
+    let mut program = exporting_program();
+    program.metadata.data = vec![0; 8];
+    program.export("next")
+        .address_instruction(ByteCodes::LoadData8, 0)
+        .push8(1)
+        .instruction(ByteCodes::AddInt8)
+        .instruction(ByteCodes::Duplicate8)
+        .address_instruction(ByteCodes::StoreData8, 0)
+        .instruction(ByteCodes::Return);
+
+    let mut vm = VM::builder().build();
+    vm.load(&program.binary()).unwrap();
+
+    assert_eq!(result8(vm.call("next", &[]).unwrap()), 1);
+    assert_eq!(result8(vm.call("next", &[]).unwrap()), 2);
+}
+
+
+#[test]
+fn call_after_run() {
+    let mut program = exporting_program();
+    program.export("answer").push8(42).instruction(ByteCodes::Return);
+
+    let mut vm = VM::builder().build();
+    assert_eq!(vm.run(&program.binary()).unwrap(), ExitStatus::Exited(ErrorCodes::NoError));
+    assert_eq!(result8(vm.call("answer", &[]).unwrap()), 42);
+}
+
+
+#[test]
+fn unknown_export() {
+    let mut vm = VM::builder().build();
+    assert_eq!(vm.call("missing", &[]).unwrap_err().kind, FaultKind::UnknownExport);
+
+    let mut program = exporting_program();
+    program.export("answer").push8(42).instruction(ByteCodes::Return);
+    vm.load(&program.binary()).unwrap();
+
+    assert_eq!(vm.call("missing", &[]).unwrap_err().kind, FaultKind::UnknownExport);
+}
+
+
+#[test]
+fn exit_during_call() {
+    let mut program = exporting_program();
+    program.export("abort").push4(ErrorCodes::GenericError as u32).instruction(ByteCodes::Exit);
+
+    let mut vm = VM::builder().build();
+    vm.load(&program.binary()).unwrap();
+
+    assert_eq!(vm.call("abort", &[]).unwrap_err().kind, FaultKind::ExitDuringCall(ErrorCodes::GenericError));
+}
+
+
+#[test]
+fn missing_return() {
+    let mut program = exporting_program();
+    program.export("answer").push8(42);
+
+    let mut vm = VM::builder().build();
+    vm.load(&program.binary()).unwrap();
+
+    assert_eq!(vm.call("answer", &[]).unwrap_err().kind, FaultKind::MissingReturn);
+}
+
+
+#[test]
+fn arguments_not_consumed() {
+    let mut program = exporting_program();
+    program.export("nop").instruction(ByteCodes::Return);
+
+    let mut vm = VM::builder().build();
+    vm.load(&program.binary()).unwrap();
+
+    // The arguments that the function doesn't consume are returned as results
+    assert_eq!(result8(vm.call("nop", &[&7u64.to_le_bytes()]).unwrap()), 7);
+}
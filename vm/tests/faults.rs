@@ -0,0 +1,113 @@
+//! Tests for the faults reported by the checked execution mode.
+
+mod common;
+
+use common::{run_fault, Program};
+use vm::{FaultKind, VM};
+use vmlib::{ByteCodes, VirtualAddress};
+
+
+#[test]
+fn jump_to_end_of_address_space() {
+    let mut program = Program::new();
+    program.address_instruction(ByteCodes::JumpConst, usize::MAX);
+
+    let fault = run_fault(&program, VM::builder());
+    assert_eq!(fault.kind, FaultKind::ProgramCounterOutOfRange);
+    assert_eq!(fault.pc, VirtualAddress(usize::MAX));
+    assert_eq!(fault.opcode, None);
+}
+
+
+#[test]
+fn jump_past_end_of_program() {
+    let mut program = Program::new();
+    program.address_instruction(ByteCodes::JumpConst, 1000);
+
+    let fault = run_fault(&program, VM::builder());
+    assert_eq!(fault.kind, FaultKind::ProgramCounterOutOfRange);
+    assert_eq!(fault.pc, VirtualAddress(1000));
+    assert_eq!(fault.opcode, None);
+}
+
+
+#[test]
+fn stack_overflow() {
+    let mut program = Program::new();
+    program.push8(1).push8(2);
+    let push = program.position();
+    program.push8(3);
+
+    let fault = run_fault(&program, VM::builder().opstack_size(16));
+    assert_eq!(fault.kind, FaultKind::StackOverflow);
+    assert_eq!(fault.pc, VirtualAddress(push));
+    assert_eq!(fault.opcode, Some(ByteCodes::LoadConst8 as u16));
+}
+
+
+#[test]
+fn stack_underflow() {
+    let mut program = Program::new();
+    program.push4(1);
+    let pop = program.position();
+    program.pop8();
+
+    let fault = run_fault(&program, VM::builder());
+    assert_eq!(fault.kind, FaultKind::StackUnderflow);
+    assert_eq!(fault.pc, VirtualAddress(pop));
+    assert_eq!(fault.opcode, Some(ByteCodes::PopConst as u16));
+}
+
+
+#[test]
+fn truncated_operand() {
+    let mut program = Program::new();
+    program.push1(0);
+    let load = program.position();
+    // Only 3 of the 8 operand bytes are inside the program
+    program.instruction(ByteCodes::LoadConst8).operand(&[1, 2, 3]);
+
+    let fault = run_fault(&program, VM::builder());
+    assert_eq!(fault.kind, FaultKind::TruncatedOperand);
+    assert_eq!(fault.pc, VirtualAddress(load));
+    assert_eq!(fault.opcode, Some(ByteCodes::LoadConst8 as u16));
+}
+
+
+#[test]
+fn unknown_opcode() {
+    let mut program = Program::new();
+    program.push1(0).operand(&[0xff, 0xff]).push1(0);
+
+    let fault = run_fault(&program, VM::builder());
+    assert_eq!(fault.kind, FaultKind::UnknownOpcode);
+    assert_eq!(fault.pc, VirtualAddress(3));
+    assert_eq!(fault.opcode, Some(0xffff));
+}
+
+
+#[test]
+fn truncated_opcode() {
+    let mut program = Program::new();
+    // The last instruction code is missing its second byte
+    program.push2(0).push1(0).operand(&[ByteCodes::LoadConst1.to_le_bytes()[0]]);
+
+    let fault = run_fault(&program, VM::builder());
+    assert_eq!(fault.kind, FaultKind::UnknownOpcode);
+    assert_eq!(fault.pc, VirtualAddress(7));
+    assert_eq!(fault.opcode, None);
+}
+
+
+#[test]
+fn unknown_interrupt() {
+    let mut program = Program::new();
+    program.push1(0).push1(0);
+    let interrupt = program.position();
+    program.instruction(ByteCodes::IntrConst).operand(&[u8::MAX]);
+
+    let fault = run_fault(&program, VM::builder());
+    assert_eq!(fault.kind, FaultKind::UnknownInterrupt(u8::MAX));
+    assert_eq!(fault.pc, VirtualAddress(interrupt));
+    assert_eq!(fault.opcode, Some(ByteCodes::IntrConst as u16));
+}
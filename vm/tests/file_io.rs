@@ -0,0 +1,153 @@
+//! Tests for the file system interrupts.
+
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use common::{run, Program};
+use vm::{ExitStatus, MemoryModel, VM};
+use vmlib::files::{FILE_KIND_DIRECTORY, FILE_KIND_FILE, OPEN_CREATE, OPEN_EXCLUSIVE, OPEN_READ, OPEN_TRUNCATE, OPEN_WRITE, SEEK_START};
+use vmlib::{ByteCodes, ErrorCodes, Interrupts};
+
+
+/// Create an empty scratch directory for the test `name`.
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("stackvm-file-io-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+
+fn path_str(path: PathBuf) -> String {
+    path.to_str().unwrap().to_string()
+}
+
+
+#[test]
+fn write_and_read_back() {
+    let dir = scratch_dir("write-read");
+    let path = path_str(dir.join("file.txt"));
+
+    for memory in [MemoryModel::Host, MemoryModel::Virtual] {
+        let mut program = Program::new();
+        let text = program.data(b"hello file");
+        let buf = program.data(&[0; 8]);
+
+        // Write the text, keeping the descriptor on the stack
+        program.string(&path).push1(OPEN_CREATE | OPEN_TRUNCATE | OPEN_WRITE | OPEN_READ).interrupt(Interrupts::Open)
+            .instruction(ByteCodes::Duplicate8)
+            .data_address(text).push8(10).interrupt(Interrupts::Write).print8().print_char(' ');
+
+        // Seek to the second word and read it
+        program.instruction(ByteCodes::Duplicate8).push8(6).push1(SEEK_START).interrupt(Interrupts::Seek).print8().print_char(' ');
+        program.instruction(ByteCodes::Duplicate8).data_address(buf).push8(8).interrupt(Interrupts::Read).print8().print_char(' ');
+
+        // Reading again hits the end of the file, and the error code stays set until the exit
+        program.instruction(ByteCodes::Duplicate8).data_address(buf).push8(8).interrupt(Interrupts::Read).print8().print_char(':');
+        program.instruction(ByteCodes::ReadError).print4().print_char(' ');
+
+        program.interrupt(Interrupts::Close).address_instruction(ByteCodes::LoadData8, buf).print8().exit_with_error_code();
+
+        let (result, output) = run(&program, VM::builder().memory_model(memory));
+        assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::EOF)));
+        assert_eq!(output, format!("10 6 4 0:{} {}", ErrorCodes::EOF as i32, u64::from_le_bytes(*b"file\0\0\0\0")));
+        assert_eq!(fs::read(&path).unwrap(), b"hello file");
+    }
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+
+#[test]
+fn open_errors() {
+    let dir = scratch_dir("open-errors");
+    fs::write(dir.join("existing"), b"").unwrap();
+
+    let cases = [
+        ("missing", OPEN_READ, ErrorCodes::FileNotFound),
+        ("existing", OPEN_WRITE | OPEN_CREATE | OPEN_EXCLUSIVE, ErrorCodes::FileExists),
+        ("existing", 0x80, ErrorCodes::InvalidArgument),
+    ];
+
+    for (name, flags, error_code) in cases {
+        let mut program = Program::new();
+        program.string(&path_str(dir.join(name))).push1(flags).interrupt(Interrupts::Open).print8().exit_with_error_code();
+
+        let (result, output) = run(&program, VM::builder());
+        assert_eq!(result, Ok(ExitStatus::Exited(error_code)));
+        assert_eq!(output, "0");
+    }
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+
+#[test]
+fn invalid_descriptor() {
+    let mut program = Program::new();
+    let buf = program.data(&[0; 8]);
+    program.push8(42).data_address(buf).push8(8).interrupt(Interrupts::Read).print8().exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::InvalidDescriptor)));
+    assert_eq!(output, "0");
+
+    let mut program = Program::new();
+    program.push8(42).interrupt(Interrupts::Close).exit_with_error_code();
+
+    let (result, _) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::InvalidDescriptor)));
+}
+
+
+#[test]
+fn stat_and_unlink() {
+    let dir = scratch_dir("stat-unlink");
+    let path = path_str(dir.join("file.bin"));
+    fs::write(&path, [0; 123]).unwrap();
+
+    let mut program = Program::new();
+    // Print the kind and the size, dropping the modification time
+    program.string(&path).interrupt(Interrupts::Stat).print1().print_char(' ')
+        .pop8()
+        .print8().print_char(' ');
+    program.string(&path_str(dir.clone())).interrupt(Interrupts::Stat).print1().print_char(' ');
+    program.string(&path).interrupt(Interrupts::Unlink);
+    program.string(&path).interrupt(Interrupts::Stat).print1().exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::FileNotFound)));
+    assert_eq!(output, format!("{FILE_KIND_FILE} 123 {FILE_KIND_DIRECTORY} 0"));
+    assert!(!dir.join("file.bin").exists());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+
+#[test]
+fn list_directory() {
+    let dir = scratch_dir("list");
+    fs::write(dir.join("long_name"), b"").unwrap();
+
+    let mut program = Program::new();
+    let name = program.data(&[0; 16]);
+    let small = program.data(&[0; 4]);
+
+    program.string(&path_str(dir.clone())).interrupt(Interrupts::OpenDir);
+    // The entry doesn't fit in the small buffer, so it's read again with the large one
+    program.instruction(ByteCodes::Duplicate8).data_address(small).push8(4).interrupt(Interrupts::ReadDir)
+        .instruction(ByteCodes::ReadError).print4().print_char(':').print8().print_char(' ');
+    program.instruction(ByteCodes::SetErrorConst).operand(&(ErrorCodes::NoError as i32).to_le_bytes());
+    program.instruction(ByteCodes::Duplicate8).data_address(name).push8(16).interrupt(Interrupts::ReadDir).print8().print_char(' ');
+    // There are no more entries
+    program.instruction(ByteCodes::Duplicate8).data_address(name).push8(16).interrupt(Interrupts::ReadDir).print8().print_char(' ');
+    program.interrupt(Interrupts::Close).address_instruction(ByteCodes::LoadData8, name).print8().exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::EOF)));
+    assert_eq!(output, format!("{}:9 9 0 {}", ErrorCodes::Overflow as i32, u64::from_le_bytes(*b"long_nam")));
+
+    fs::remove_dir_all(dir).unwrap();
+}
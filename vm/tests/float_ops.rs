@@ -0,0 +1,126 @@
+//! Regression tests for the floating point instructions.
+
+mod common;
+
+use common::{run, Program};
+use vm::{ExitStatus, VM};
+use vmlib::{ByteCodes, ErrorCodes, Interrupts};
+
+
+/// Execute a single instruction on the pushed operands, print the raw result with `print` and return what was printed.
+fn run_instruction(program: &mut Program, instruction: ByteCodes, print: Interrupts) -> u64 {
+    program.instruction(instruction).interrupt(print).exit(ErrorCodes::NoError);
+
+    let (result, output) = run(program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    output.parse().unwrap()
+}
+
+
+fn run_float4(instruction: ByteCodes, operands: &[f32]) -> f32 {
+    let mut program = Program::new();
+    for &operand in operands {
+        program.push4(operand.to_bits());
+    }
+    f32::from_bits(run_instruction(&mut program, instruction, Interrupts::Print4) as u32)
+}
+
+
+fn run_float8(instruction: ByteCodes, operands: &[f64]) -> f64 {
+    let mut program = Program::new();
+    for &operand in operands {
+        program.push8(operand.to_bits());
+    }
+    f64::from_bits(run_instruction(&mut program, instruction, Interrupts::Print8))
+}
+
+
+fn run_compare4(instruction: ByteCodes, a: f32, b: f32) -> bool {
+    let mut program = Program::new();
+    program.push4(a.to_bits()).push4(b.to_bits());
+    run_instruction(&mut program, instruction, Interrupts::Print1) != 0
+}
+
+
+fn run_compare8(instruction: ByteCodes, a: f64, b: f64) -> bool {
+    let mut program = Program::new();
+    program.push8(a.to_bits()).push8(b.to_bits());
+    run_instruction(&mut program, instruction, Interrupts::Print1) != 0
+}
+
+
+/// Declare a test that checks the result of a float instruction bit for bit.
+macro_rules! float_test {
+    ($test:ident, $instruction:ident, f32, [$($operand:expr),+], $expected:expr) => {
+        #[test]
+        fn $test() {
+            let result = run_float4(ByteCodes::$instruction, &[$($operand),+]);
+            assert_eq!(result.to_bits(), ($expected as f32).to_bits(), "got {result}");
+        }
+    };
+    ($test:ident, $instruction:ident, f64, [$($operand:expr),+], $expected:expr) => {
+        #[test]
+        fn $test() {
+            let result = run_float8(ByteCodes::$instruction, &[$($operand),+]);
+            assert_eq!(result.to_bits(), ($expected as f64).to_bits(), "got {result}");
+        }
+    };
+}
+
+
+float_test!(add_float4, AddFloat4, f32, [1.5, 2.25], 3.75);
+float_test!(add_float8, AddFloat8, f64, [1.5, 2.25], 3.75);
+float_test!(sub_float4, SubFloat4, f32, [1.5, 2.25], -0.75);
+float_test!(sub_float8, SubFloat8, f64, [1.5, 2.25], -0.75);
+float_test!(mul_float4, MulFloat4, f32, [1.5, -2.25], -3.375);
+float_test!(mul_float8, MulFloat8, f64, [1.5, -2.25], -3.375);
+float_test!(div_float4, DivFloat4, f32, [7.5, 2.5], 3.0);
+float_test!(div_float8, DivFloat8, f64, [7.5, 2.5], 3.0);
+float_test!(mod_float4, ModFloat4, f32, [7.5, 2.0], 1.5);
+float_test!(mod_float8, ModFloat8, f64, [7.5, 2.0], 1.5);
+
+float_test!(neg_float4, NegFloat4, f32, [1.5], -1.5);
+float_test!(neg_float8, NegFloat8, f64, [1.5], -1.5);
+float_test!(abs_float4, AbsFloat4, f32, [-1.5], 1.5);
+float_test!(abs_float8, AbsFloat8, f64, [-1.5], 1.5);
+float_test!(sqrt_float4, SqrtFloat4, f32, [2.0], 2f32.sqrt());
+float_test!(sqrt_float8, SqrtFloat8, f64, [2.0], 2f64.sqrt());
+float_test!(floor_float4, FloorFloat4, f32, [-1.5], -2.0);
+float_test!(floor_float8, FloorFloat8, f64, [-1.5], -2.0);
+float_test!(ceil_float4, CeilFloat4, f32, [-1.5], -1.0);
+float_test!(ceil_float8, CeilFloat8, f64, [-1.5], -1.0);
+float_test!(min_float4, MinFloat4, f32, [-1.5, 2.0], -1.5);
+float_test!(min_float8, MinFloat8, f64, [-1.5, 2.0], -1.5);
+float_test!(max_float4, MaxFloat4, f32, [-1.5, 2.0], 2.0);
+float_test!(max_float8, MaxFloat8, f64, [-1.5, 2.0], 2.0);
+float_test!(sin_float4, SinFloat4, f32, [0.5], 0.5f32.sin());
+float_test!(sin_float8, SinFloat8, f64, [0.5], 0.5f64.sin());
+float_test!(cos_float4, CosFloat4, f32, [0.5], 0.5f32.cos());
+float_test!(cos_float8, CosFloat8, f64, [0.5], 0.5f64.cos());
+float_test!(exp_float4, ExpFloat4, f32, [1.5], 1.5f32.exp());
+float_test!(exp_float8, ExpFloat8, f64, [1.5], 1.5f64.exp());
+float_test!(log_float4, LogFloat4, f32, [10.0], 10f32.ln());
+float_test!(log_float8, LogFloat8, f64, [10.0], 10f64.ln());
+float_test!(pow_float4, PowFloat4, f32, [2.0, 0.5], 2f32.powf(0.5));
+float_test!(pow_float8, PowFloat8, f64, [2.0, 0.5], 2f64.powf(0.5));
+
+
+#[test]
+fn less_float4() {
+    assert!(run_compare4(ByteCodes::LessFloat4, -2.5, 1.5));
+    assert!(!run_compare4(ByteCodes::LessFloat4, 1.5, -2.5));
+}
+
+
+#[test]
+fn less_float8() {
+    assert!(run_compare8(ByteCodes::LessFloat8, -2.5, 1.5));
+    assert!(!run_compare8(ByteCodes::LessFloat8, 1.5, -2.5));
+}
+
+
+#[test]
+fn equal_float_nan() {
+    assert!(!run_compare4(ByteCodes::EqualFloat4, f32::NAN, f32::NAN));
+    assert!(run_compare8(ByteCodes::NotEqualFloat8, f64::NAN, f64::NAN));
+}
@@ -0,0 +1,132 @@
+//! Tests for the formatted printing interrupts.
+
+mod common;
+
+use common::{run, Program};
+use vm::{ExitStatus, VM};
+use vmlib::{ErrorCodes, Interrupts, SHORTEST_FLOAT_PRECISION};
+
+
+#[test]
+fn signed_integers() {
+    let mut program = Program::new();
+    program.push1(-1i8 as u8).interrupt(Interrupts::PrintInt1).print_char(' ')
+        .push2(-300i16 as u16).interrupt(Interrupts::PrintInt2).print_char(' ')
+        .push4(i32::MIN as u32).interrupt(Interrupts::PrintInt4).print_char(' ')
+        .push8(-5i64 as u64).interrupt(Interrupts::PrintInt8).print_char(' ')
+        .push8(7).interrupt(Interrupts::PrintInt8)
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "-1 -300 -2147483648 -5 7");
+}
+
+
+#[test]
+fn hex_and_binary() {
+    let mut program = Program::new();
+    program.push1(0xff).interrupt(Interrupts::PrintHex1).print_char(' ')
+        .push2(0xbeef).interrupt(Interrupts::PrintHex2).print_char(' ')
+        .push4(0).interrupt(Interrupts::PrintHex4).print_char(' ')
+        .push8(u64::MAX).interrupt(Interrupts::PrintHex8).print_char(' ')
+        .push1(5).interrupt(Interrupts::PrintBinary1).print_char(' ')
+        .push8(1 << 40).interrupt(Interrupts::PrintBinary8)
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, format!("ff beef 0 ffffffffffffffff 101 1{}", "0".repeat(40)));
+}
+
+
+#[test]
+fn floats() {
+    let mut program = Program::new();
+    program.push8(1.23456f64.to_bits()).push1(2).interrupt(Interrupts::PrintFloat8).print_char(' ')
+        .push8(0.1f64.to_bits()).push1(SHORTEST_FLOAT_PRECISION).interrupt(Interrupts::PrintFloat8).print_char(' ')
+        .push4(0.1f32.to_bits()).push1(SHORTEST_FLOAT_PRECISION).interrupt(Interrupts::PrintFloat4).print_char(' ')
+        .push4((-2.5f32).to_bits()).push1(0).interrupt(Interrupts::PrintFloat4).print_char(' ')
+        .push8(f64::NAN.to_bits()).push1(3).interrupt(Interrupts::PrintFloat8)
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "1.23 0.1 0.1 -2 NaN");
+}
+
+
+#[test]
+fn code_points() {
+    let mut program = Program::new();
+    program.push4('é' as u32).interrupt(Interrupts::PrintCodePoint)
+        .push4('😀' as u32).interrupt(Interrupts::PrintCodePoint)
+        // Surrogates are not characters
+        .push4(0xd800).interrupt(Interrupts::PrintCodePoint)
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::InvalidArgument)));
+    assert_eq!(output, "é😀");
+}
+
+
+#[test]
+fn format_string() {
+    let mut program = Program::new();
+    // The value below the arguments must be left on the stack
+    program.push8(1234)
+        .push4(-42i32 as u32)
+        .push2(-7i16 as u16)
+        .push1(9)
+        .push8(0xdeadbeef)
+        .push2(0xab)
+        .push8(2f64.to_bits())
+        .push4('é' as u32)
+        .string("stack")
+        .string("vm")
+        .string("[%i4] [%05i2] [%-4u1] [%x8] [%X2] [%.3f8] [%c] [%6s] [%-4s] 100%%")
+        .interrupt(Interrupts::PrintFormat)
+        .print_char(' ')
+        .interrupt(Interrupts::Print8)
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "[-42] [-0007] [9   ] [deadbeef] [AB] [2.000] [é] [ stack] [vm  ] 100% 1234");
+}
+
+
+#[test]
+fn invalid_format_string() {
+    for format in ["%q4", "%u3", "%.2u8", "%f2", "trailing %", "%99999u8"] {
+        let mut program = Program::new();
+        // The stack is left untouched, since the arguments of the format string are unknown
+        program.push8(1234).string(format).interrupt(Interrupts::PrintFormat)
+            .interrupt(Interrupts::Print8).print_char(' ')
+            .pop8().interrupt(Interrupts::Print8)
+            .exit_with_error_code();
+
+        let (result, output) = run(&program, VM::builder());
+        assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::InvalidArgument)), "{format}");
+        assert_eq!(output, format!("{} 1234", format.len()), "{format}");
+    }
+}
+
+
+#[test]
+fn invalid_format_argument() {
+    let mut program = Program::new();
+    // The format string and all its arguments are popped, even those after the invalid code point
+    program.push8(1234)
+        .push4(0xd800)
+        .push8(5678)
+        .string("%c %u8")
+        .interrupt(Interrupts::PrintFormat)
+        .interrupt(Interrupts::Print8)
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::InvalidArgument)));
+    assert_eq!(output, "1234");
+}
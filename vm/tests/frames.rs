@@ -0,0 +1,65 @@
+//! Tests for the stack frame instructions.
+
+mod common;
+
+use common::{run, run_fault, Program};
+use vm::{ExitStatus, FaultKind, VM};
+use vmlib::{ByteCodes, ErrorCodes, VirtualAddress, INSTRUCTION_SIZE};
+
+
+#[test]
+fn locals_and_arguments() {
+    let mut program = Program::new();
+    // Call a function that returns the difference of its arguments in the first one
+    program.push8(10).push8(3);
+    let call = program.position();
+    program.address_instruction(ByteCodes::Call, 0)
+        .print8().print_char(' ').print8()
+        .exit(ErrorCodes::NoError);
+    let function = program.position();
+    program.address_instruction(ByteCodes::Enter, 16)
+        .address_instruction(ByteCodes::LoadArg8, 8)
+        .address_instruction(ByteCodes::LoadArg8, 0)
+        .instruction(ByteCodes::SubInt8)
+        .address_instruction(ByteCodes::StoreLocal8, 8)
+        // The frame pointer of a nested frame is restored by `leave`
+        .address_instruction(ByteCodes::Enter, 8).push8(99).address_instruction(ByteCodes::StoreLocal8, 0).instruction(ByteCodes::Leave)
+        .address_instruction(ByteCodes::LoadLocal8, 8)
+        .address_instruction(ByteCodes::StoreArg8, 8)
+        .instruction(ByteCodes::Leave)
+        .instruction(ByteCodes::Return);
+
+    let operand = call + INSTRUCTION_SIZE;
+    program.code[operand..operand + 8].copy_from_slice(&function.to_le_bytes());
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "3 7");
+}
+
+
+#[test]
+fn local_past_the_frame() {
+    let mut program = Program::new();
+    program.address_instruction(ByteCodes::Enter, 8);
+    let load = program.position();
+    program.address_instruction(ByteCodes::LoadLocal8, 8);
+
+    let fault = run_fault(&program, VM::builder());
+    assert_eq!(fault.kind, FaultKind::FrameOutOfRange);
+    assert_eq!(fault.pc, VirtualAddress(load));
+}
+
+
+#[test]
+fn argument_below_the_stack() {
+    let mut program = Program::new();
+    program.push8(1);
+    let load = program.position();
+    // There is no frame, so the frame pointer is at the bottom of the stack
+    program.address_instruction(ByteCodes::LoadArg8, 0);
+
+    let fault = run_fault(&program, VM::builder());
+    assert_eq!(fault.kind, FaultKind::FrameOutOfRange);
+    assert_eq!(fault.pc, VirtualAddress(load));
+}
@@ -0,0 +1,150 @@
+//! Tests for the host functions of the embedding API.
+
+mod common;
+
+use common::{run, Program};
+use vm::{ExecutionMode, ExitStatus, FaultKind, MemoryModel, VM};
+use vmlib::{ByteCodes, ErrorCodes};
+
+
+#[test]
+fn call_by_name() {
+    let mut program = Program::new();
+    let add = program.declare_host_function("add");
+    program.push8(40).push8(2).host_call(add).print8().exit_with_error_code();
+
+    let builder = VM::builder().host_function("add", |ctx| {
+        let b = ctx.pop_8()?;
+        let a = ctx.pop_8()?;
+        ctx.push_8(a + b)
+    });
+
+    let (result, output) = run(&program, builder);
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "42");
+}
+
+
+#[test]
+fn call_by_number() {
+    let mut program = Program::new();
+    program.host_call(7).print8().exit_with_error_code();
+
+    let builder = VM::builder().host_function_id(7, |ctx| ctx.push_8(7));
+
+    let (result, output) = run(&program, builder);
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "7");
+}
+
+
+#[test]
+fn declared_numbers_resolve_by_name() {
+    let mut program = Program::new();
+    let missing = program.declare_host_function("missing");
+    program.host_call(missing).exit_with_error_code();
+
+    // A number declared by the program never falls back to a function registered under the same number
+    let builder = VM::builder().host_function_id(missing, |_| Ok(()));
+
+    let (result, _) = run(&program, builder);
+    assert_eq!(result, Err(FaultKind::UnknownHostFunction(missing)));
+}
+
+
+#[test]
+fn unknown_host_function() {
+    let mut program = Program::new();
+    program.host_call(3).exit_with_error_code();
+
+    let (result, _) = run(&program, VM::builder());
+    assert_eq!(result, Err(FaultKind::UnknownHostFunction(3)));
+}
+
+
+#[test]
+fn state_persists_across_calls() {
+    let mut program = Program::new();
+    let next = program.declare_host_function("next");
+    program.host_call(next).print8().host_call(next).print8().exit_with_error_code();
+
+    let mut counter = 0;
+    let builder = VM::builder().host_function("next", move |ctx| {
+        counter += 1;
+        ctx.push_8(counter)
+    });
+
+    let (result, output) = run(&program, builder);
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "12");
+}
+
+
+#[test]
+fn memory_access() {
+    let mut program = Program::new();
+    let fill = program.declare_host_function("fill");
+    // Allocate a block, let the host function fill it, then load its content
+    program.push8(8).instruction(ByteCodes::Malloc)
+        .instruction(ByteCodes::Duplicate8)
+        .host_call(fill)
+        .instruction(ByteCodes::Load8)
+        .print8()
+        .exit_with_error_code();
+
+    for memory in [MemoryModel::Host, MemoryModel::Virtual] {
+        let builder = VM::builder().memory_model(memory).host_function("fill", |ctx| {
+            let address = ctx.pop_8()?;
+            ctx.write_memory(address, &1234u64.to_le_bytes())
+        });
+
+        let (result, output) = run(&program, builder);
+        assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+        assert_eq!(output, "1234");
+    }
+}
+
+
+#[test]
+fn invalid_memory_access() {
+    let mut program = Program::new();
+    let peek = program.declare_host_function("peek");
+    program.push8(0).host_call(peek).exit_with_error_code();
+
+    let builder = VM::builder().memory_model(MemoryModel::Virtual).host_function("peek", |ctx| {
+        let address = ctx.pop_8()?;
+        ctx.read_memory(address, &mut [0; 8])
+    });
+
+    let (result, _) = run(&program, builder);
+    assert_eq!(result, Err(FaultKind::InvalidMemoryAccess(0)));
+}
+
+
+#[test]
+fn stack_checked_in_unchecked_mode() {
+    let mut program = Program::new();
+    let pop = program.declare_host_function("pop");
+    program.host_call(pop).exit_with_error_code();
+
+    let builder = VM::builder().mode(ExecutionMode::Unchecked).host_function("pop", |ctx| ctx.pop_8().map(|_| ()));
+
+    let (result, _) = run(&program, builder);
+    assert_eq!(result, Err(FaultKind::StackUnderflow));
+}
+
+
+#[test]
+fn error_code() {
+    let mut program = Program::new();
+    let fail = program.declare_host_function("fail");
+    program.host_call(fail).exit_with_error_code();
+
+    let builder = VM::builder().host_function("fail", |ctx| {
+        ctx.set_error_code(ErrorCodes::InvalidPointer);
+        Ok(())
+    });
+
+    let (result, _) = run(&program, builder);
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::InvalidPointer)));
+}
@@ -0,0 +1,80 @@
+//! Tests for the resource limits of a run.
+
+mod common;
+
+use std::time::Duration;
+
+use common::{run, run_fault, Program};
+use vm::{ExitStatus, FaultKind, Limit, Limits, VM};
+use vmlib::{ByteCodes, ErrorCodes, Interrupts, VirtualAddress};
+
+
+/// A program that jumps to itself forever.
+fn infinite_loop() -> Program {
+    let mut program = Program::new();
+    program.address_instruction(ByteCodes::JumpConst, 0);
+    program
+}
+
+
+#[test]
+fn instruction_limit() {
+    let limits = Limits { max_instructions: Some(1000), ..Limits::default() };
+
+    let fault = run_fault(&infinite_loop(), VM::builder().limits(limits));
+    assert_eq!(fault.kind, FaultKind::LimitExceeded(Limit::Instructions));
+    assert_eq!(fault.pc, VirtualAddress(0));
+}
+
+
+#[test]
+fn timeout() {
+    let limits = Limits { timeout: Some(Duration::from_millis(50)), ..Limits::default() };
+
+    let fault = run_fault(&infinite_loop(), VM::builder().limits(limits));
+    assert_eq!(fault.kind, FaultKind::LimitExceeded(Limit::Time));
+    assert_eq!(fault.pc, VirtualAddress(0));
+}
+
+
+#[test]
+fn heap_limit() {
+    let mut program = Program::new();
+    program.push8(40).instruction(ByteCodes::Malloc).pop8()
+        .push8(40).instruction(ByteCodes::Malloc).pop8()
+        .exit(ErrorCodes::NoError);
+    let limits = Limits { max_heap_bytes: Some(64), ..Limits::default() };
+
+    let fault = run_fault(&program, VM::builder().limits(limits));
+    assert_eq!(fault.kind, FaultKind::LimitExceeded(Limit::HeapBytes));
+    assert_eq!(fault.opcode, Some(ByteCodes::Malloc as u16));
+}
+
+
+#[test]
+fn heap_limit_counts_redzones() {
+    let mut program = Program::new();
+    program.push8(40).instruction(ByteCodes::Malloc).pop8().exit(ErrorCodes::NoError);
+    let limits = Limits { max_heap_bytes: Some(64), ..Limits::default() };
+
+    let (result, _) = run(&program, VM::builder().limits(limits));
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+
+    // The sanitizer follows the block with a redzone, which takes heap space too
+    let (result, _) = run(&program, VM::builder().limits(limits).sanitize(true));
+    assert_eq!(result, Err(FaultKind::LimitExceeded(Limit::HeapBytes)));
+}
+
+
+#[test]
+fn output_limit() {
+    let mut program = Program::new();
+    program.string("hello").interrupt(Interrupts::PrintString)
+        .string("world").interrupt(Interrupts::PrintString)
+        .exit(ErrorCodes::NoError);
+    let limits = Limits { max_output_bytes: Some(8), ..Limits::default() };
+
+    let (result, output) = run(&program, VM::builder().limits(limits));
+    assert_eq!(result, Err(FaultKind::LimitExceeded(Limit::Output)));
+    assert_eq!(output, "hello");
+}
@@ -0,0 +1,164 @@
+//! Tests for the heap instructions and the memory models.
+
+mod common;
+
+use std::alloc::Layout;
+
+use common::{build, run, run_fault, Program};
+use vm::{ExecutionMode, ExitStatus, FaultKind, MemoryModel, VM};
+use vmlib::{ByteCodes, ErrorCodes, VirtualAddress};
+
+
+#[test]
+fn allocation_too_large() {
+    for memory in [MemoryModel::Host, MemoryModel::Virtual] {
+        let mut program = Program::new();
+        program.push8(u64::MAX).instruction(ByteCodes::Malloc).print8()
+            .push8(u64::MAX).instruction(ByteCodes::Calloc).print8()
+            .exit_with_error_code();
+
+        let (result, output) = run(&program, VM::builder().memory_model(memory));
+        assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::OutOfMemory)), "{memory:?}");
+        assert_eq!(output, "00", "{memory:?}");
+    }
+}
+
+
+#[test]
+fn virtual_address_space_exhausted() {
+    let mut program = Program::new();
+    // The second block would start past the end of the address space
+    program.push8(1).push8(1 << 63).instruction(ByteCodes::MallocAligned).print8().print_char(' ')
+        .push8(1).push8(1 << 63).instruction(ByteCodes::MallocAligned).print8()
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder().memory_model(MemoryModel::Virtual));
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::OutOfMemory)));
+    assert_eq!(output, format!("{} 0", 1u64 << 63));
+}
+
+
+#[test]
+fn double_free() {
+    for memory in [MemoryModel::Host, MemoryModel::Virtual] {
+        let mut program = Program::new();
+        program.push8(8).instruction(ByteCodes::Malloc)
+            .instruction(ByteCodes::Duplicate8).instruction(ByteCodes::Free)
+            .instruction(ByteCodes::Free)
+            .exit_with_error_code();
+
+        let (result, _) = run(&program, VM::builder().memory_model(memory));
+        assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::DoubleFree)), "{memory:?}");
+    }
+}
+
+
+#[test]
+fn invalid_pointer() {
+    for memory in [MemoryModel::Host, MemoryModel::Virtual] {
+        // The pointer is inside a block, but it's not the address of a block
+        let mut free = Program::new();
+        free.push8(8).instruction(ByteCodes::Malloc).push8(1).instruction(ByteCodes::AddInt8)
+            .instruction(ByteCodes::Free)
+            .exit_with_error_code();
+
+        let (result, _) = run(&free, VM::builder().memory_model(memory));
+        assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::InvalidPointer)), "{memory:?}");
+
+        let mut realloc = Program::new();
+        realloc.push8(16).push8(0x1234).instruction(ByteCodes::Realloc).print8()
+            .exit_with_error_code();
+
+        let (result, output) = run(&realloc, VM::builder().memory_model(memory));
+        assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::InvalidPointer)), "{memory:?}");
+        assert_eq!(output, "0", "{memory:?}");
+    }
+}
+
+
+#[test]
+fn leaked_blocks() {
+    let mut program = Program::new();
+    program.push8(24).push8(16).instruction(ByteCodes::MallocAligned).print8()
+        .push8(8).instruction(ByteCodes::Malloc).instruction(ByteCodes::Free)
+        .exit_with_error_code();
+
+    for memory in [MemoryModel::Host, MemoryModel::Virtual] {
+        // The redzones added by the sanitizer are not reported
+        for sanitize in [false, true] {
+            let (mut vm, output) = build(VM::builder().memory_model(memory).sanitize(sanitize));
+
+            assert_eq!(vm.run(&program.binary()).unwrap(), ExitStatus::Exited(ErrorCodes::NoError));
+            let block: usize = output.to_string_lossy().parse().unwrap();
+            assert_eq!(vm.leaked_blocks(), [(block, Layout::from_size_align(24, 16).unwrap())], "{memory:?} {sanitize}");
+        }
+    }
+}
+
+
+#[test]
+fn virtual_pointers() {
+    let mut program = Program::new();
+    // Heap blocks, the top of the stack and the data segment are all reachable through their virtual addresses
+    let data = program.data(&3u64.to_le_bytes());
+    program.push8(8).instruction(ByteCodes::Malloc)
+        .push8(42).instruction(ByteCodes::Over8).instruction(ByteCodes::Store8)
+        .instruction(ByteCodes::Load8).print8().print_char(' ')
+        .push8(7).instruction(ByteCodes::LoadStackPointer).instruction(ByteCodes::Load8).print8().pop8().print_char(' ')
+        .data_address(data).instruction(ByteCodes::Load8).print8()
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder().memory_model(MemoryModel::Virtual));
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "42 7 3");
+}
+
+
+#[test]
+fn virtual_invalid_accesses() {
+    for mode in [ExecutionMode::Checked, ExecutionMode::Unchecked] {
+        let builder = || VM::builder().memory_model(MemoryModel::Virtual).mode(mode);
+
+        let mut unmapped = Program::new();
+        unmapped.push8(0).instruction(ByteCodes::Load8);
+        let fault = run_fault(&unmapped, builder());
+        assert_eq!(fault.kind, FaultKind::InvalidMemoryAccess(0), "{mode:?}");
+        assert_eq!(fault.pc, VirtualAddress(10), "{mode:?}");
+
+        // Print the address of an 8-byte block, then read the byte after it
+        let mut overflow = Program::new();
+        overflow.push8(8).instruction(ByteCodes::Malloc).instruction(ByteCodes::Duplicate8).print8()
+            .push8(8).instruction(ByteCodes::AddInt8).instruction(ByteCodes::Load1);
+        let (mut vm, output) = build(builder());
+        let fault = vm.run(&overflow.binary()).unwrap_err();
+        let block: usize = output.to_string_lossy().parse().unwrap();
+        assert_eq!(fault.kind, FaultKind::InvalidMemoryAccess(block + 8), "{mode:?}");
+
+        // Addresses of freed blocks are never reused
+        let mut use_after_free = Program::new();
+        use_after_free.push8(8).instruction(ByteCodes::Malloc).instruction(ByteCodes::Duplicate8).print8()
+            .instruction(ByteCodes::Duplicate8).instruction(ByteCodes::Free)
+            .push8(8).instruction(ByteCodes::Malloc).pop8()
+            .instruction(ByteCodes::Load8);
+        let (mut vm, output) = build(builder());
+        let fault = vm.run(&use_after_free.binary()).unwrap_err();
+        let block: usize = output.to_string_lossy().parse().unwrap();
+        assert_eq!(fault.kind, FaultKind::InvalidMemoryAccess(block), "{mode:?}");
+    }
+}
+
+
+#[test]
+fn virtual_program_space_is_read_only() {
+    let mut program = Program::new();
+    program.address_instruction(ByteCodes::VirtualConstToReal, 0).print8()
+        .push1(0).address_instruction(ByteCodes::VirtualConstToReal, 0);
+    let store = program.position();
+    program.instruction(ByteCodes::Store1);
+
+    let (mut vm, output) = build(VM::builder().memory_model(MemoryModel::Virtual));
+    let fault = vm.run(&program.binary()).unwrap_err();
+    let code: usize = output.to_string_lossy().parse().unwrap();
+    assert_eq!(fault.kind, FaultKind::ReadOnlyMemoryWrite(code));
+    assert_eq!(fault.pc, VirtualAddress(store));
+}
@@ -0,0 +1,124 @@
+//! Tests for the process interrupts.
+#![cfg(unix)]
+
+mod common;
+
+use common::{run, run_fault, Program};
+use vm::{ExitStatus, FaultKind, MemoryModel, VM};
+use vmlib::files::{SPAWN_PIPE_STDIN, SPAWN_PIPE_STDOUT};
+use vmlib::{ByteCodes, ErrorCodes, Interrupts, ADDRESS_SIZE};
+
+
+/// Spawn a process running `argv` with the given flags. The process, stdin and stdout descriptors are left on the stack.
+fn spawn<'a>(program: &'a mut Program, argv: &[&str], flags: u8) -> &'a mut Program {
+
+    // The addresses of the strings are only known at run time, so the program fills them in the array
+    let array = program.data(&vec![0; argv.len() * 2 * ADDRESS_SIZE]);
+    for (i, arg) in argv.iter().enumerate() {
+        let entry = array + i * 2 * ADDRESS_SIZE;
+        let string = program.data(arg.as_bytes());
+        program.metadata.data[entry + ADDRESS_SIZE..entry + 2 * ADDRESS_SIZE].copy_from_slice(&arg.len().to_le_bytes());
+        program.data_address(string).address_instruction(ByteCodes::StoreData8, entry);
+    }
+
+    program.data_address(array).push8(argv.len() as u64).push1(flags).interrupt(Interrupts::Spawn)
+}
+
+
+#[test]
+fn capture_output_and_exit_code() {
+    for memory in [MemoryModel::Host, MemoryModel::Virtual] {
+        let mut program = Program::new();
+        let buf = program.data(&[0; 16]);
+
+        // Read the output of the process, then wait for it to exit
+        spawn(&mut program, &["sh", "-c", "printf captured; exit 3"], SPAWN_PIPE_STDOUT)
+            .instruction(ByteCodes::Duplicate8).data_address(buf).push8(16).interrupt(Interrupts::Read)
+            .data_address(buf).instruction(ByteCodes::Swap8).interrupt(Interrupts::PrintString).print_char(' ')
+            .interrupt(Interrupts::Close)
+            .print8().print_char(' ')
+            .interrupt(Interrupts::Wait).print4()
+            .exit_with_error_code();
+
+        let (result, output) = run(&program, VM::builder().memory_model(memory));
+        assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+        // The standard input is not piped, so its descriptor is 0
+        assert_eq!(output, "captured 0 3");
+    }
+}
+
+
+#[test]
+fn pipe_through_process() {
+    let mut program = Program::new();
+    let input = program.data(b"abc");
+    let buf = program.data(&[0; 16]);
+
+    spawn(&mut program, &["tr", "a-z", "A-Z"], SPAWN_PIPE_STDIN | SPAWN_PIPE_STDOUT)
+        .instruction(ByteCodes::Swap8)
+        // Closing the standard input lets the process finish
+        .instruction(ByteCodes::Duplicate8).data_address(input).push8(3).interrupt(Interrupts::Write).pop8()
+        .interrupt(Interrupts::Close)
+        .instruction(ByteCodes::Duplicate8).data_address(buf).push8(16).interrupt(Interrupts::Read)
+        .data_address(buf).instruction(ByteCodes::Swap8).interrupt(Interrupts::PrintString)
+        .interrupt(Interrupts::Close)
+        .interrupt(Interrupts::Wait).print4()
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "ABC0");
+}
+
+
+#[test]
+fn spawn_errors() {
+    let cases: [(&[&str], u8, ErrorCodes); 3] = [
+        (&["stackvm-missing-command"], 0, ErrorCodes::FileNotFound),
+        (&[], 0, ErrorCodes::InvalidArgument),
+        (&["true"], 0x80, ErrorCodes::InvalidArgument),
+    ];
+
+    for (argv, flags, error_code) in cases {
+        let mut program = Program::new();
+        spawn(&mut program, argv, flags).print8().print8().print8().exit_with_error_code();
+
+        let (result, output) = run(&program, VM::builder());
+        assert_eq!(result, Ok(ExitStatus::Exited(error_code)));
+        assert_eq!(output, "000");
+    }
+}
+
+
+#[test]
+fn spawn_huge_argc() {
+    let mut program = Program::new();
+    // The array holds a single entry, so the next ones are read past the end of the data segment
+    let array = program.data(&[0; 2 * ADDRESS_SIZE]);
+    program.data_address(array).push8(u64::MAX / 2).push1(0).interrupt(Interrupts::Spawn);
+
+    let fault = run_fault(&program, VM::builder().memory_model(MemoryModel::Virtual));
+    assert!(matches!(fault.kind, FaultKind::InvalidMemoryAccess(_)), "{:?}", fault.kind);
+}
+
+
+#[test]
+fn spawn_denied() {
+    let mut program = Program::new();
+    spawn(&mut program, &["true"], 0).print8().print8().print8().exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder().allow_spawn(false));
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::PermissionDenied)));
+    assert_eq!(output, "000");
+}
+
+
+#[test]
+fn wait_invalid_descriptor() {
+    let mut program = Program::new();
+    // A pipe is not a process
+    spawn(&mut program, &["true"], SPAWN_PIPE_STDOUT).interrupt(Interrupts::Wait).exit_with_error_code();
+
+    let (result, _) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::InvalidDescriptor)));
+}
@@ -0,0 +1,67 @@
+//! Tests for the invalid accesses reported by the sanitizer.
+
+mod common;
+
+use common::{build, Program};
+use vm::{FaultKind, MemoryModel, VmFault, VM};
+use vmlib::{ByteCodes, VirtualAddress};
+
+
+/// Run the program with the sanitizer and return the fault that stopped it and what it printed.
+fn run_sanitized(program: &Program, memory: MemoryModel) -> (VmFault, String) {
+    let (mut vm, output) = build(VM::builder().sanitize(true).memory_model(memory));
+
+    let fault = vm.run(&program.binary()).expect_err("the program should fault");
+    (fault, output.to_string_lossy())
+}
+
+
+#[test]
+fn heap_buffer_overflow() {
+    let mut program = Program::new();
+    // Print the address of a 16-byte block, then read the byte right after it
+    program.push8(16).instruction(ByteCodes::Malloc)
+        .instruction(ByteCodes::Duplicate8).print8()
+        .push8(16).instruction(ByteCodes::AddInt8);
+    let load = program.position();
+    program.instruction(ByteCodes::Load1);
+
+    for memory in [MemoryModel::Host, MemoryModel::Virtual] {
+        let (fault, output) = run_sanitized(&program, memory);
+        let block: usize = output.parse().unwrap();
+        assert_eq!(fault.kind, FaultKind::HeapBufferOverflow(block + 16), "{memory:?}");
+        assert_eq!(fault.pc, VirtualAddress(load), "{memory:?}");
+    }
+}
+
+
+#[test]
+fn use_after_free() {
+    let mut program = Program::new();
+    program.push8(8).instruction(ByteCodes::Malloc)
+        .instruction(ByteCodes::Duplicate8).print8()
+        .instruction(ByteCodes::Duplicate8).instruction(ByteCodes::Free);
+    let load = program.position();
+    program.instruction(ByteCodes::Load8);
+
+    for memory in [MemoryModel::Host, MemoryModel::Virtual] {
+        let (fault, output) = run_sanitized(&program, memory);
+        let block: usize = output.parse().unwrap();
+        assert_eq!(fault.kind, FaultKind::UseAfterFree(block), "{memory:?}");
+        assert_eq!(fault.pc, VirtualAddress(load), "{memory:?}");
+    }
+}
+
+
+#[test]
+fn uninitialized_stack_read() {
+    let mut program = Program::new();
+    // The locals reserved by `enter` are not initialized
+    program.address_instruction(ByteCodes::Enter, 8);
+    let load = program.position();
+    program.address_instruction(ByteCodes::LoadLocal8, 0);
+
+    let (fault, _) = run_sanitized(&program, MemoryModel::Host);
+    assert_eq!(fault.kind, FaultKind::UninitializedStackRead);
+    assert_eq!(fault.pc, VirtualAddress(load));
+}
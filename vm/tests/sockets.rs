@@ -0,0 +1,158 @@
+//! Tests for the socket interrupts.
+
+mod common;
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream, UdpSocket};
+use std::thread;
+use std::time::Duration;
+
+use common::{run, Program};
+use vm::{ExitStatus, VM};
+use vmlib::{ByteCodes, ErrorCodes, Interrupts};
+
+
+/// Return a loopback address with a port that is not in use.
+fn free_address() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap().to_string()
+}
+
+
+#[test]
+fn tcp_client() {
+    let server = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = server.local_addr().unwrap().to_string();
+
+    let peer = thread::spawn(move || {
+        let (mut stream, _) = server.accept().unwrap();
+        let mut request = [0; 4];
+        stream.read_exact(&mut request).unwrap();
+        assert_eq!(&request, b"ping");
+        stream.write_all(b"pong").unwrap();
+    });
+
+    let mut program = Program::new();
+    let ping = program.data(b"ping");
+    let buf = program.data(&[0; 16]);
+    program.string(&address).interrupt(Interrupts::Connect)
+        .instruction(ByteCodes::Duplicate8).data_address(ping).push8(4).interrupt(Interrupts::Send).print8().print_char(' ')
+        .instruction(ByteCodes::Duplicate8).data_address(buf).push8(16).interrupt(Interrupts::Recv).print_buffer(buf).print_char(' ')
+        // The peer closed the connection
+        .instruction(ByteCodes::Duplicate8).data_address(buf).push8(16).interrupt(Interrupts::Recv).print8()
+        .interrupt(Interrupts::Close)
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    peer.join().unwrap();
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::EOF)));
+    assert_eq!(output, "4 pong 0");
+}
+
+
+#[test]
+fn tcp_server() {
+    let address = free_address();
+
+    let peer = {
+        let address = address.clone();
+        thread::spawn(move || {
+            // Retry until the program listens
+            let mut stream = loop {
+                match TcpStream::connect(&address) {
+                    Ok(stream) => break stream,
+                    Err(_) => thread::sleep(Duration::from_millis(10))
+                }
+            };
+            stream.write_all(b"echo").unwrap();
+            let mut response = Vec::new();
+            stream.read_to_end(&mut response).unwrap();
+            response
+        })
+    };
+
+    let mut program = Program::new();
+    let buf = program.data(&[0; 16]);
+    // Echo what the client sends with the file interrupts, which also work on streams
+    program.string(&address).interrupt(Interrupts::Listen)
+        .instruction(ByteCodes::Duplicate8).interrupt(Interrupts::Accept)
+        .instruction(ByteCodes::Duplicate8).instruction(ByteCodes::Duplicate8).data_address(buf).push8(4).interrupt(Interrupts::Read)
+        .data_address(buf).instruction(ByteCodes::Swap8).interrupt(Interrupts::Write).print8()
+        .interrupt(Interrupts::Close)
+        .interrupt(Interrupts::Close)
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "4");
+    assert_eq!(peer.join().unwrap(), b"echo");
+}
+
+
+#[test]
+fn udp_datagrams() {
+    let peer_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let peer_address = peer_socket.local_addr().unwrap().to_string();
+
+    let peer = thread::spawn(move || {
+        let mut buf = [0; 16];
+        let (length, sender) = peer_socket.recv_from(&mut buf).unwrap();
+        peer_socket.send_to(&buf[..length], sender).unwrap();
+        sender
+    });
+
+    let mut program = Program::new();
+    let hello = program.data(b"hello");
+    let buf = program.data(&[0; 16]);
+    let sender = program.data(&[0; 32]);
+    program.string("127.0.0.1:0").interrupt(Interrupts::Bind)
+        .instruction(ByteCodes::Duplicate8).interrupt(Interrupts::LocalPort).instruction(ByteCodes::ZeroExtend2To8).print8().print_char(' ');
+    program.instruction(ByteCodes::Duplicate8).data_address(hello).push8(5).string(&peer_address).interrupt(Interrupts::SendTo).print8().print_char(' ');
+    program.instruction(ByteCodes::Duplicate8).data_address(buf).push8(16).data_address(sender).push8(32).interrupt(Interrupts::RecvFrom)
+        .print_buffer(sender).print_char(' ')
+        .print_buffer(buf)
+        .interrupt(Interrupts::Close)
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    let port = peer.join().unwrap().port();
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, format!("{port} 5 {peer_address} hello"));
+}
+
+
+#[test]
+fn socket_errors() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let used_address = listener.local_addr().unwrap().to_string();
+
+    let cases = [
+        (Interrupts::Connect, free_address(), ErrorCodes::ConnectionRefused),
+        (Interrupts::Listen, used_address, ErrorCodes::AddressInUse),
+        (Interrupts::Connect, "not an address".to_string(), ErrorCodes::InvalidArgument),
+        // Sockets are limited to the loopback interface
+        (Interrupts::Listen, "0.0.0.0:0".to_string(), ErrorCodes::PermissionDenied),
+        (Interrupts::Bind, "[::]:0".to_string(), ErrorCodes::PermissionDenied),
+        (Interrupts::Connect, "192.0.2.1:80".to_string(), ErrorCodes::PermissionDenied),
+    ];
+
+    for (interrupt, address, error_code) in cases {
+        let mut program = Program::new();
+        program.string(&address).interrupt(interrupt).print8().exit_with_error_code();
+
+        let (result, output) = run(&program, VM::builder());
+        assert_eq!(result, Ok(ExitStatus::Exited(error_code)), "{interrupt:?} {address}");
+        assert_eq!(output, "0");
+    }
+
+    // Datagram sockets have no stream to send to
+    let mut program = Program::new();
+    let buf = program.data(b"data");
+    program.string("127.0.0.1:0").interrupt(Interrupts::Bind)
+        .data_address(buf).push8(4).interrupt(Interrupts::Send).print8()
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::InvalidDescriptor)));
+    assert_eq!(output, "0");
+}
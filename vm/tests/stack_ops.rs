@@ -0,0 +1,78 @@
+//! Tests for the instructions that reorder stack items.
+
+mod common;
+
+use common::{run, run_fault, Program};
+use vm::{ExitStatus, FaultKind, VM};
+use vmlib::{ByteCodes, ErrorCodes, Interrupts, VirtualAddress};
+
+
+/// Push the items 1 to `count`, so that `count` is on top of the stack.
+fn push_items(program: &mut Program, count: u8) -> &mut Program {
+    for item in 1..=count {
+        program.push8(item as u64);
+    }
+    program
+}
+
+
+/// Print the top `count` items of the stack.
+fn print_items(program: &mut Program, count: usize) -> &mut Program {
+    for _ in 0..count {
+        program.print8();
+    }
+    program
+}
+
+
+#[test]
+fn swap_over_rot() {
+    let mut program = Program::new();
+    push_items(&mut program, 2).instruction(ByteCodes::Swap8);
+    print_items(&mut program, 2).print_char(' ');
+    push_items(&mut program, 2).instruction(ByteCodes::Over8);
+    print_items(&mut program, 3).print_char(' ');
+    push_items(&mut program, 3).instruction(ByteCodes::Rotate8);
+    print_items(&mut program, 3).print_char(' ');
+    program.push1(1).push1(2).instruction(ByteCodes::Swap1).print1().print1()
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "12 121 132 12");
+}
+
+
+#[test]
+fn pick_and_roll() {
+    let mut program = Program::new();
+    // Copy the fourth item to the top
+    push_items(&mut program, 4).instruction(ByteCodes::Pick8).operand(&[3]);
+    print_items(&mut program, 5).print_char(' ');
+    // Move the fourth item to the top
+    push_items(&mut program, 4).instruction(ByteCodes::Roll8).operand(&[3]);
+    print_items(&mut program, 4).print_char(' ');
+    // Index 0 is the top item
+    push_items(&mut program, 2).instruction(ByteCodes::Pick8).operand(&[0]);
+    print_items(&mut program, 3).print_char(' ');
+    program.push2(1).push2(2).push2(3).instruction(ByteCodes::Roll2).operand(&[2])
+        .interrupt(Interrupts::Print2).interrupt(Interrupts::Print2).interrupt(Interrupts::Print2)
+        .exit_with_error_code();
+
+    let (result, output) = run(&program, VM::builder());
+    assert_eq!(result, Ok(ExitStatus::Exited(ErrorCodes::NoError)));
+    assert_eq!(output, "14321 1432 221 132");
+}
+
+
+#[test]
+fn pick_below_the_stack() {
+    let mut program = Program::new();
+    push_items(&mut program, 2);
+    let pick = program.position();
+    program.instruction(ByteCodes::Pick8).operand(&[2]);
+
+    let fault = run_fault(&program, VM::builder());
+    assert_eq!(fault.kind, FaultKind::StackUnderflow);
+    assert_eq!(fault.pc, VirtualAddress(pick));
+}
@@ -0,0 +1,37 @@
+//! Constants of the file system and process interrupts.
+
+
+/// Open the file for reading.
+pub const OPEN_READ: u8 = 1 << 0;
+/// Open the file for writing.
+pub const OPEN_WRITE: u8 = 1 << 1;
+/// Open the file for writing at its end.
+pub const OPEN_APPEND: u8 = 1 << 2;
+/// Create the file if it doesn't exist. Requires write or append access.
+pub const OPEN_CREATE: u8 = 1 << 3;
+/// Truncate the file to zero length. Requires write access.
+pub const OPEN_TRUNCATE: u8 = 1 << 4;
+/// Create the file, failing if it already exists. Requires write or append access.
+pub const OPEN_EXCLUSIVE: u8 = 1 << 5;
+
+
+/// Seek relative to the start of the file.
+pub const SEEK_START: u8 = 0;
+/// Seek relative to the current position.
+pub const SEEK_CURRENT: u8 = 1;
+/// Seek relative to the end of the file.
+pub const SEEK_END: u8 = 2;
+
+
+/// Kind reported by the stat interrupt when the path doesn't exist or cannot be queried.
+pub const FILE_KIND_NONE: u8 = 0;
+pub const FILE_KIND_FILE: u8 = 1;
+pub const FILE_KIND_DIRECTORY: u8 = 2;
+/// Symbolic links that cannot be followed and special files.
+pub const FILE_KIND_OTHER: u8 = 3;
+
+
+/// Pipe the standard input of the spawned process to a descriptor the program can write to.
+pub const SPAWN_PIPE_STDIN: u8 = 1 << 0;
+/// Pipe the standard output of the spawned process to a descriptor the program can read from.
+pub const SPAWN_PIPE_STDOUT: u8 = 1 << 1;
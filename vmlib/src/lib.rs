@@ -4,16 +4,32 @@ use std::fmt;
 
 use static_assertions::{const_assert, const_assert_eq};
 
+pub mod metadata;
+pub mod files;
 
-pub const LIBRARY_ENV_VARIABLE: &'static str = "STACKVM_ASM_LIB";
+
+pub const LIBRARY_ENV_VARIABLE: &str = "STACKVM_ASM_LIB";
 
 pub type Address = usize;
 pub const ADDRESS_SIZE: usize = mem::size_of::<Address>();
-pub const INSTRUCTION_SIZE: usize = 1;
+pub const INSTRUCTION_SIZE: usize = mem::size_of::<Opcode>();
 pub const INTERRUPT_SIZE: usize = 1;
 pub const ERROR_CODE_SIZE: usize = mem::size_of::<i32>();
-
-#[derive(Default, Clone, Copy)]
+/// Size of the shift amount operand of shift and rotate instructions.
+/// Shifts by the bit width of the value or more leave 0, or the sign fill for arithmetic shifts. Rotations use the count modulo the width.
+pub const SHIFT_COUNT_SIZE: usize = mem::size_of::<u8>();
+/// Size of the item index operand of `pick` and `roll` instructions.
+pub const STACK_INDEX_SIZE: usize = mem::size_of::<u8>();
+/// Size of single-precision floating point values.
+pub const FLOAT4_SIZE: usize = mem::size_of::<f32>();
+/// Size of double-precision floating point values.
+pub const FLOAT8_SIZE: usize = mem::size_of::<f64>();
+/// Size of the host function number operand of `hostcall` instructions.
+pub const HOST_FUNCTION_ID_SIZE: usize = mem::size_of::<u32>();
+/// Precision operand of the float printing interrupts that prints the shortest representation that reads back as the same value.
+pub const SHORTEST_FLOAT_PRECISION: u8 = u8::MAX;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct VirtualAddress(pub Address);
 
 impl VirtualAddress {
@@ -29,28 +45,51 @@ const_assert_eq!(mem::size_of::<VirtualAddress>(), mem::size_of::<usize>());
 
 pub type ByteCode<'a> = &'a [u8];
 
+/// Raw instruction code, encoded in little-endian order in the byte code.
+pub type Opcode = u16;
+
 
 macro_rules! declare_instructions {
     ($($name:ident $asm_name:ident),+) => {
         
-/// VM instructions. Each instruction is represented by one byte.
+/// VM instructions. Each instruction is represented by a little-endian `Opcode`.
 #[derive(Debug, Clone, Copy)]
-#[repr(u8)]
+#[repr(u16)]
 pub enum ByteCodes {
     $($name),+
 }
 
-impl From<u8> for ByteCodes {
-    /// Convert a byte to an intruction code. An invalid instruction code will result in undefined behavior.
-    fn from(byte: u8) -> Self {
+impl From<Opcode> for ByteCodes {
+    /// Convert a raw code to an intruction code. An invalid instruction code will result in undefined behavior.
+    fn from(code: Opcode) -> Self {
         unsafe {
-            mem::transmute(byte)
+            mem::transmute(code)
         }
     }
 }
 
 impl ByteCodes {
 
+    /// Number of defined instructions. Valid instruction codes are in the range `0..COUNT`.
+    pub const COUNT: usize = [$(stringify!($name)),+].len();
+
+
+    /// Convert a raw code to an instruction code, checking that the code is a valid instruction code.
+    pub fn from_code(code: Opcode) -> Option<Self> {
+        if (code as usize) < Self::COUNT {
+            Some(Self::from(code))
+        } else {
+            None
+        }
+    }
+
+
+    /// Return the byte code representation of the instruction.
+    pub fn to_le_bytes(self) -> [u8; INSTRUCTION_SIZE] {
+        (self as Opcode).to_le_bytes()
+    }
+
+
     pub fn from_string(string: &str) -> Option<Self> {
         match string {
             $(stringify!($asm_name) => Some(Self::$name),)+
@@ -58,6 +97,14 @@ impl ByteCodes {
         }
     }
 
+
+    /// Return the assembly name of the instruction.
+    pub fn asm_name(&self) -> &'static str {
+        match self {
+            $(Self::$name => stringify!($asm_name),)+
+        }
+    }
+
 }
 
     };
@@ -86,6 +133,65 @@ declare_instructions! {
     ModInt4 modi4,
     ModInt8 modi8,
 
+    DivUint1 divu1,
+    DivUint2 divu2,
+    DivUint4 divu4,
+    DivUint8 divu8,
+    ModUint1 modu1,
+    ModUint2 modu2,
+    ModUint4 modu4,
+    ModUint8 modu8,
+
+    CheckedAddInt1 caddi1,
+    CheckedAddInt2 caddi2,
+    CheckedAddInt4 caddi4,
+    CheckedAddInt8 caddi8,
+    CheckedAddUint1 caddu1,
+    CheckedAddUint2 caddu2,
+    CheckedAddUint4 caddu4,
+    CheckedAddUint8 caddu8,
+    CheckedSubInt1 csubi1,
+    CheckedSubInt2 csubi2,
+    CheckedSubInt4 csubi4,
+    CheckedSubInt8 csubi8,
+    CheckedSubUint1 csubu1,
+    CheckedSubUint2 csubu2,
+    CheckedSubUint4 csubu4,
+    CheckedSubUint8 csubu8,
+    CheckedMulInt1 cmuli1,
+    CheckedMulInt2 cmuli2,
+    CheckedMulInt4 cmuli4,
+    CheckedMulInt8 cmuli8,
+    CheckedMulUint1 cmulu1,
+    CheckedMulUint2 cmulu2,
+    CheckedMulUint4 cmulu4,
+    CheckedMulUint8 cmulu8,
+
+    SaturatingAddInt1 saddi1,
+    SaturatingAddInt2 saddi2,
+    SaturatingAddInt4 saddi4,
+    SaturatingAddInt8 saddi8,
+    SaturatingAddUint1 saddu1,
+    SaturatingAddUint2 saddu2,
+    SaturatingAddUint4 saddu4,
+    SaturatingAddUint8 saddu8,
+    SaturatingSubInt1 ssubi1,
+    SaturatingSubInt2 ssubi2,
+    SaturatingSubInt4 ssubi4,
+    SaturatingSubInt8 ssubi8,
+    SaturatingSubUint1 ssubu1,
+    SaturatingSubUint2 ssubu2,
+    SaturatingSubUint4 ssubu4,
+    SaturatingSubUint8 ssubu8,
+    SaturatingMulInt1 smuli1,
+    SaturatingMulInt2 smuli2,
+    SaturatingMulInt4 smuli4,
+    SaturatingMulInt8 smuli8,
+    SaturatingMulUint1 smulu1,
+    SaturatingMulUint2 smulu2,
+    SaturatingMulUint4 smulu4,
+    SaturatingMulUint8 smulu8,
+
     AddFloat4 addf4,
     AddFloat8 addf8,
     SubFloat4 subf4,
@@ -97,6 +203,192 @@ declare_instructions! {
     ModFloat4 modf4,
     ModFloat8 modf8,
 
+    NegFloat4 negf4,
+    NegFloat8 negf8,
+    AbsFloat4 absf4,
+    AbsFloat8 absf8,
+    SqrtFloat4 sqrtf4,
+    SqrtFloat8 sqrtf8,
+    FloorFloat4 floorf4,
+    FloorFloat8 floorf8,
+    CeilFloat4 ceilf4,
+    CeilFloat8 ceilf8,
+    MinFloat4 minf4,
+    MinFloat8 minf8,
+    MaxFloat4 maxf4,
+    MaxFloat8 maxf8,
+    SinFloat4 sinf4,
+    SinFloat8 sinf8,
+    CosFloat4 cosf4,
+    CosFloat8 cosf8,
+    ExpFloat4 expf4,
+    ExpFloat8 expf8,
+    LogFloat4 logf4,
+    LogFloat8 logf8,
+    PowFloat4 powf4,
+    PowFloat8 powf8,
+
+    BitAnd1 and1,
+    BitAnd2 and2,
+    BitAnd4 and4,
+    BitAnd8 and8,
+    BitOr1 or1,
+    BitOr2 or2,
+    BitOr4 or4,
+    BitOr8 or8,
+    BitXor1 xor1,
+    BitXor2 xor2,
+    BitXor4 xor4,
+    BitXor8 xor8,
+    BitNot1 not1,
+    BitNot2 not2,
+    BitNot4 not4,
+    BitNot8 not8,
+
+    ShiftLeft1 shl1,
+    ShiftLeft2 shl2,
+    ShiftLeft4 shl4,
+    ShiftLeft8 shl8,
+    ShiftRight1 shr1,
+    ShiftRight2 shr2,
+    ShiftRight4 shr4,
+    ShiftRight8 shr8,
+    ShiftRightArith1 sar1,
+    ShiftRightArith2 sar2,
+    ShiftRightArith4 sar4,
+    ShiftRightArith8 sar8,
+
+    RotateLeft1 rotl1,
+    RotateLeft2 rotl2,
+    RotateLeft4 rotl4,
+    RotateLeft8 rotl8,
+    RotateRight1 rotr1,
+    RotateRight2 rotr2,
+    RotateRight4 rotr4,
+    RotateRight8 rotr8,
+
+    ByteSwap2 bswap2,
+    ByteSwap4 bswap4,
+    ByteSwap8 bswap8,
+
+    EqualInt1 eqi1,
+    EqualInt2 eqi2,
+    EqualInt4 eqi4,
+    EqualInt8 eqi8,
+    NotEqualInt1 nei1,
+    NotEqualInt2 nei2,
+    NotEqualInt4 nei4,
+    NotEqualInt8 nei8,
+    LessInt1 lti1,
+    LessInt2 lti2,
+    LessInt4 lti4,
+    LessInt8 lti8,
+    LessEqualInt1 lei1,
+    LessEqualInt2 lei2,
+    LessEqualInt4 lei4,
+    LessEqualInt8 lei8,
+    GreaterInt1 gti1,
+    GreaterInt2 gti2,
+    GreaterInt4 gti4,
+    GreaterInt8 gti8,
+    GreaterEqualInt1 gei1,
+    GreaterEqualInt2 gei2,
+    GreaterEqualInt4 gei4,
+    GreaterEqualInt8 gei8,
+
+    LessUint1 ltu1,
+    LessUint2 ltu2,
+    LessUint4 ltu4,
+    LessUint8 ltu8,
+    LessEqualUint1 leu1,
+    LessEqualUint2 leu2,
+    LessEqualUint4 leu4,
+    LessEqualUint8 leu8,
+    GreaterUint1 gtu1,
+    GreaterUint2 gtu2,
+    GreaterUint4 gtu4,
+    GreaterUint8 gtu8,
+    GreaterEqualUint1 geu1,
+    GreaterEqualUint2 geu2,
+    GreaterEqualUint4 geu4,
+    GreaterEqualUint8 geu8,
+
+    EqualFloat4 eqf4,
+    EqualFloat8 eqf8,
+    NotEqualFloat4 nef4,
+    NotEqualFloat8 nef8,
+    LessFloat4 ltf4,
+    LessFloat8 ltf8,
+    LessEqualFloat4 lef4,
+    LessEqualFloat8 lef8,
+    GreaterFloat4 gtf4,
+    GreaterFloat8 gtf8,
+    GreaterEqualFloat4 gef4,
+    GreaterEqualFloat8 gef8,
+
+    Select1 select1,
+    Select2 select2,
+    Select4 select4,
+    Select8 select8,
+
+    SignExtend1To2 sext1to2,
+    SignExtend1To4 sext1to4,
+    SignExtend1To8 sext1to8,
+    SignExtend2To4 sext2to4,
+    SignExtend2To8 sext2to8,
+    SignExtend4To8 sext4to8,
+
+    ZeroExtend1To2 zext1to2,
+    ZeroExtend1To4 zext1to4,
+    ZeroExtend1To8 zext1to8,
+    ZeroExtend2To4 zext2to4,
+    ZeroExtend2To8 zext2to8,
+    ZeroExtend4To8 zext4to8,
+
+    Truncate2To1 trunc2to1,
+    Truncate4To1 trunc4to1,
+    Truncate8To1 trunc8to1,
+    Truncate4To2 trunc4to2,
+    Truncate8To2 trunc8to2,
+    Truncate8To4 trunc8to4,
+
+    Int1ToFloat4 i1tof4,
+    Int1ToFloat8 i1tof8,
+    Int2ToFloat4 i2tof4,
+    Int2ToFloat8 i2tof8,
+    Int4ToFloat4 i4tof4,
+    Int4ToFloat8 i4tof8,
+    Int8ToFloat4 i8tof4,
+    Int8ToFloat8 i8tof8,
+    Uint1ToFloat4 u1tof4,
+    Uint1ToFloat8 u1tof8,
+    Uint2ToFloat4 u2tof4,
+    Uint2ToFloat8 u2tof8,
+    Uint4ToFloat4 u4tof4,
+    Uint4ToFloat8 u4tof8,
+    Uint8ToFloat4 u8tof4,
+    Uint8ToFloat8 u8tof8,
+
+    Float4ToInt1 f4toi1,
+    Float8ToInt1 f8toi1,
+    Float4ToInt2 f4toi2,
+    Float8ToInt2 f8toi2,
+    Float4ToInt4 f4toi4,
+    Float8ToInt4 f8toi4,
+    Float4ToInt8 f4toi8,
+    Float8ToInt8 f8toi8,
+    Float4ToUint1 f4tou1,
+    Float8ToUint1 f8tou1,
+    Float4ToUint2 f4tou2,
+    Float8ToUint2 f8tou2,
+    Float4ToUint4 f4tou4,
+    Float8ToUint4 f8tou4,
+    Float4ToUint8 f4tou8,
+    Float8ToUint8 f8tou8,
+
+    Float4ToFloat8 f4tof8,
+    Float8ToFloat4 f8tof4,
+
     LoadStatic1 loadst1,
     LoadStatic2 loadst2,
     LoadStatic4 loadst4,
@@ -126,6 +418,17 @@ declare_instructions! {
     VirtualConstToReal vctr,
     VirtualToReal vtr,
 
+    LoadData1 loadd1,
+    LoadData2 loadd2,
+    LoadData4 loadd4,
+    LoadData8 loadd8,
+    StoreData1 stored1,
+    StoreData2 stored2,
+    StoreData4 stored4,
+    StoreData8 stored8,
+    VirtualDataConstToReal vdctr,
+    VirtualDataToReal vdtr,
+
     Store1 store1,
     Store2 store2,
     Store4 store4,
@@ -144,13 +447,38 @@ declare_instructions! {
     Duplicate8 dup8,
     DuplicateBytes dupn,
 
+    Swap1 swap1,
+    Swap2 swap2,
+    Swap4 swap4,
+    Swap8 swap8,
+    Over1 over1,
+    Over2 over2,
+    Over4 over4,
+    Over8 over8,
+    Rotate1 rot1,
+    Rotate2 rot2,
+    Rotate4 rot4,
+    Rotate8 rot8,
+    Pick1 pick1,
+    Pick2 pick2,
+    Pick4 pick4,
+    Pick8 pick8,
+    Roll1 roll1,
+    Roll2 roll2,
+    Roll4 roll4,
+    Roll8 roll8,
+
     Malloc malloc,
     Realloc realloc,
     Free free,
+    Calloc calloc,
+    MallocAligned malloca,
 
     Intr intr,
     IntrConst intrconst,
 
+    HostCall hostcall,
+
     ReadError readerr,
     SetErrorConst seterrconst,
     SetError seterr,
@@ -182,6 +510,28 @@ declare_instructions! {
     JumpNoError jnoerr,
 
     Call call,
+    CallIndirect callind,
+    TailCall tailcall,
+    Return ret,
+
+    Enter enter,
+    Leave leave,
+    LoadLocal1 loadlocal1,
+    LoadLocal2 loadlocal2,
+    LoadLocal4 loadlocal4,
+    LoadLocal8 loadlocal8,
+    StoreLocal1 storelocal1,
+    StoreLocal2 storelocal2,
+    StoreLocal4 storelocal4,
+    StoreLocal8 storelocal8,
+    LoadArg1 loadarg1,
+    LoadArg2 loadarg2,
+    LoadArg4 loadarg4,
+    LoadArg8 loadarg8,
+    StoreArg1 storearg1,
+    StoreArg2 storearg2,
+    StoreArg4 storearg4,
+    StoreArg8 storearg8,
 
     Nop nop
 
@@ -190,20 +540,14 @@ declare_instructions! {
 const_assert!(mem::size_of::<ByteCodes>() == INSTRUCTION_SIZE);
 
 
-#[derive(Clone, Copy)]
+macro_rules! declare_interrupts {
+    ($($name:ident),+) => {
+
+/// Built-in interrupts. Each interrupt is identified by a one-byte code.
+#[derive(Debug, Clone, Copy)]
 #[repr(u8)]
 pub enum Interrupts {
-    Print1 = 0,
-    Print2,
-    Print4,
-    Print8,
-    PrintBytes,
-    PrintChar,
-    PrintString,
-    PrintStaticBytes,
-    PrintStaticString,
-    ReadBytes,
-    ReadAll,
+    $($name),+
 }
 
 impl From<u8> for Interrupts {
@@ -215,12 +559,110 @@ impl From<u8> for Interrupts {
     }
 }
 
+impl Interrupts {
+
+    /// Number of defined interrupts. Valid interrupt codes are in the range `0..COUNT`.
+    pub const COUNT: usize = [$(stringify!($name)),+].len();
+
+
+    /// Convert a byte to an interrupt code, checking that the byte is a valid interrupt code.
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        if (byte as usize) < Self::COUNT {
+            Some(Self::from(byte))
+        } else {
+            None
+        }
+    }
+
+}
+
 impl Display for Interrupts {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", *self as u8)
     }
 }
 
+    };
+}
+
+declare_interrupts! {
+    Print1,
+    Print2,
+    Print4,
+    Print8,
+    PrintBytes,
+    PrintChar,
+    PrintString,
+    PrintStaticBytes,
+    PrintStaticString,
+    ReadBytes,
+    ReadAll,
+    Flush,
+    Open,
+    Read,
+    Write,
+    Seek,
+    Close,
+    Stat,
+    Unlink,
+    OpenDir,
+    ReadDir,
+    ArgCount,
+    Arg,
+    GetEnv,
+    MonotonicTime,
+    UnixTime,
+    Sleep,
+    Random,
+    Spawn,
+    Wait,
+    Listen,
+    Accept,
+    Connect,
+    Bind,
+    Send,
+    SendTo,
+    Recv,
+    RecvFrom,
+    LocalPort,
+    PrintInt1,
+    PrintInt2,
+    PrintInt4,
+    PrintInt8,
+    PrintHex1,
+    PrintHex2,
+    PrintHex4,
+    PrintHex8,
+    PrintBinary1,
+    PrintBinary2,
+    PrintBinary4,
+    PrintBinary8,
+    PrintFloat4,
+    PrintFloat8,
+    PrintCodePoint,
+    PrintFormat,
+    FormatUint1,
+    FormatUint2,
+    FormatUint4,
+    FormatUint8,
+    FormatInt1,
+    FormatInt2,
+    FormatInt4,
+    FormatInt8,
+    FormatFloat4,
+    FormatFloat8,
+    ParseUint1,
+    ParseUint2,
+    ParseUint4,
+    ParseUint8,
+    ParseInt1,
+    ParseInt2,
+    ParseInt4,
+    ParseInt8,
+    ParseFloat4,
+    ParseFloat8
+}
+
 const_assert_eq!(mem::size_of::<Interrupts>(), INTERRUPT_SIZE);
 
 
@@ -228,7 +670,7 @@ macro_rules! declare_error_codes {
     ($($name:ident $value:literal),+) => {
 
 /// Identifies a specific internal error
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(i32)]
 pub enum ErrorCodes {
     $($name = $value),+
@@ -253,6 +695,18 @@ impl From<i32> for ErrorCodes {
     }
 }
 
+impl ErrorCodes {
+
+    /// Convert an integer to an error code, checking that the integer is a valid error code.
+    pub fn from_i32(i: i32) -> Option<Self> {
+        match i {
+            $($value => Some(ErrorCodes::$name),)+
+            _ => None
+        }
+    }
+
+}
+
 const_assert_eq!(mem::size_of::<ErrorCodes>(), ERROR_CODE_SIZE);
 
     };
@@ -262,6 +716,21 @@ declare_error_codes! {
     UnexpectedEOF -2,
     GenericError -1,
     NoError 0,
-    EOF 1
+    EOF 1,
+    DivisionByZero 2,
+    Overflow 3,
+    OutOfMemory 4,
+    InvalidPointer 5,
+    DoubleFree 6,
+    FileNotFound 7,
+    PermissionDenied 8,
+    FileExists 9,
+    InvalidDescriptor 10,
+    InvalidArgument 11,
+    IOError 12,
+    VariableNotFound 13,
+    ConnectionRefused 14,
+    ConnectionReset 15,
+    AddressInUse 16
 }
 
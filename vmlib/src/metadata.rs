@@ -0,0 +1,255 @@
+//! Optional program metadata appended to the byte code by the assembler.
+//!
+//! A binary with metadata is laid out as follows:
+//! `[code][metadata sections][metadata offset: u64][METADATA_MAGIC]`
+//!
+//! Each metadata section is encoded as `[tag: u8][payload size: u64][payload]`. Sections with an unknown tag are skipped.
+//! Besides debug information, the metadata carries the initial content of the writable data segment, which is not part of the program space,
+//! the names of the host functions the program calls, and the functions the program exports to the host.
+//! All integers are little-endian. Binaries without the metadata footer are treated as pure code.
+
+use std::fmt;
+use std::mem;
+
+use crate::{Address, VirtualAddress};
+
+
+pub const METADATA_MAGIC: [u8; 8] = *b"SVMMETA\0";
+
+const FOOTER_SIZE: usize = mem::size_of::<u64>() + METADATA_MAGIC.len();
+
+
+#[repr(u8)]
+enum SectionTag {
+    Symbols = 0,
+    DataSegment = 1,
+    HostFunctions = 2,
+    Exports = 3,
+}
+
+
+/// A named location in the program space (label or section).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    pub address: VirtualAddress,
+}
+
+
+#[derive(Debug, Default, Clone)]
+pub struct ProgramMetadata {
+
+    /// Debug symbols, sorted by address.
+    pub symbols: Vec<Symbol>,
+
+    /// Initial content of the writable data segment.
+    pub data: Vec<u8>,
+
+    /// Size of the zero-initialized part of the data segment, located after the initialized data.
+    pub zeroed_data_size: usize,
+
+    /// Names of the host functions declared by the program. The index of a name is the number the program calls the function with.
+    pub host_functions: Vec<String>,
+
+    /// Functions that the host can call by name.
+    pub exports: Vec<Symbol>,
+
+}
+
+
+#[derive(Debug, Clone, Copy)]
+pub struct MalformedMetadata;
+
+impl fmt::Display for MalformedMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "malformed program metadata")
+    }
+}
+
+
+/// Sequential little-endian reader over a byte slice.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+
+    fn read_bytes(&mut self, count: usize) -> Result<&'a [u8], MalformedMetadata> {
+        if count > self.bytes.len() {
+            return Err(MalformedMetadata);
+        }
+        let (bytes, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(bytes)
+    }
+
+
+    fn read_u8(&mut self) -> Result<u8, MalformedMetadata> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+
+    fn read_u64(&mut self) -> Result<u64, MalformedMetadata> {
+        Ok(u64::from_le_bytes(self.read_bytes(mem::size_of::<u64>())?.try_into().unwrap()))
+    }
+
+
+    fn read_usize(&mut self) -> Result<usize, MalformedMetadata> {
+        usize::try_from(self.read_u64()?).map_err(|_| MalformedMetadata)
+    }
+
+
+    fn read_string(&mut self) -> Result<String, MalformedMetadata> {
+        let length = self.read_usize()?;
+        String::from_utf8(self.read_bytes(length)?.to_vec()).map_err(|_| MalformedMetadata)
+    }
+
+}
+
+
+fn write_u64(buf: &mut Vec<u8>, value: u64) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+
+fn write_string(buf: &mut Vec<u8>, string: &str) {
+    write_u64(buf, string.len() as u64);
+    buf.extend_from_slice(string.as_bytes());
+}
+
+
+fn write_section(buf: &mut Vec<u8>, tag: SectionTag, payload: &[u8]) {
+    buf.push(tag as u8);
+    write_u64(buf, payload.len() as u64);
+    buf.extend_from_slice(payload);
+}
+
+
+impl ProgramMetadata {
+
+    /// Append the metadata sections and the metadata footer to the program code.
+    pub fn append_to(&self, code: &mut Vec<u8>) {
+
+        let metadata_offset = code.len();
+
+        if !self.symbols.is_empty() {
+            let mut payload = Vec::new();
+            write_u64(&mut payload, self.symbols.len() as u64);
+            for symbol in &self.symbols {
+                write_u64(&mut payload, symbol.address.0 as u64);
+                write_string(&mut payload, &symbol.name);
+            }
+            write_section(code, SectionTag::Symbols, &payload);
+        }
+
+        if !self.data.is_empty() || self.zeroed_data_size != 0 {
+            let mut payload = Vec::new();
+            write_u64(&mut payload, self.data.len() as u64);
+            payload.extend_from_slice(&self.data);
+            write_u64(&mut payload, self.zeroed_data_size as u64);
+            write_section(code, SectionTag::DataSegment, &payload);
+        }
+
+        if !self.host_functions.is_empty() {
+            let mut payload = Vec::new();
+            write_u64(&mut payload, self.host_functions.len() as u64);
+            for name in &self.host_functions {
+                write_string(&mut payload, name);
+            }
+            write_section(code, SectionTag::HostFunctions, &payload);
+        }
+
+        if !self.exports.is_empty() {
+            let mut payload = Vec::new();
+            write_u64(&mut payload, self.exports.len() as u64);
+            for export in &self.exports {
+                write_u64(&mut payload, export.address.0 as u64);
+                write_string(&mut payload, &export.name);
+            }
+            write_section(code, SectionTag::Exports, &payload);
+        }
+
+        write_u64(code, metadata_offset as u64);
+        code.extend_from_slice(&METADATA_MAGIC);
+    }
+
+
+    /// Split a binary into its program code and its metadata.
+    /// A binary without the metadata footer is entirely program code.
+    pub fn split(binary: &[u8]) -> Result<(&[u8], Self), MalformedMetadata> {
+
+        if binary.len() < FOOTER_SIZE || !binary.ends_with(&METADATA_MAGIC) {
+            return Ok((binary, Self::default()));
+        }
+
+        let footer = &binary[binary.len() - FOOTER_SIZE..];
+        let metadata_offset = Reader { bytes: footer }.read_usize()?;
+        let metadata_end = binary.len() - FOOTER_SIZE;
+
+        if metadata_offset > metadata_end {
+            return Err(MalformedMetadata);
+        }
+
+        let mut metadata = Self::default();
+        let mut reader = Reader { bytes: &binary[metadata_offset..metadata_end] };
+
+        while !reader.bytes.is_empty() {
+
+            let tag = reader.read_u8()?;
+            let size = reader.read_usize()?;
+            let mut section = Reader { bytes: reader.read_bytes(size)? };
+
+            if tag == SectionTag::Symbols as u8 {
+                let count = section.read_usize()?;
+                for _ in 0..count {
+                    let address = VirtualAddress(section.read_usize()? as Address);
+                    let name = section.read_string()?;
+                    metadata.symbols.push(Symbol { name, address });
+                }
+            } else if tag == SectionTag::DataSegment as u8 {
+                let size = section.read_usize()?;
+                metadata.data = section.read_bytes(size)?.to_vec();
+                metadata.zeroed_data_size = section.read_usize()?;
+            } else if tag == SectionTag::HostFunctions as u8 {
+                let count = section.read_usize()?;
+                for _ in 0..count {
+                    metadata.host_functions.push(section.read_string()?);
+                }
+            } else if tag == SectionTag::Exports as u8 {
+                let count = section.read_usize()?;
+                for _ in 0..count {
+                    let address = VirtualAddress(section.read_usize()? as Address);
+                    let name = section.read_string()?;
+                    metadata.exports.push(Symbol { name, address });
+                }
+            }
+            // Skip unknown sections for forward compatibility
+        }
+
+        metadata.symbols.sort_by_key(|symbol| symbol.address);
+
+        Ok((&binary[..metadata_offset], metadata))
+    }
+
+
+    /// Total size of the data segment.
+    pub fn data_segment_size(&self) -> usize {
+        self.data.len() + self.zeroed_data_size
+    }
+
+
+    /// Return the address of the exported function with the given name.
+    pub fn export(&self, name: &str) -> Option<VirtualAddress> {
+        self.exports.iter().find(|export| export.name == name).map(|export| export.address)
+    }
+
+
+    /// Find the closest symbol located at or before the given address.
+    /// Return the symbol and the offset of the address from the symbol.
+    pub fn symbolize(&self, address: VirtualAddress) -> Option<(&Symbol, usize)> {
+        let index = self.symbols.partition_point(|symbol| symbol.address <= address);
+        let symbol = self.symbols.get(index.checked_sub(1)?)?;
+        Some((symbol, address.0 - symbol.address.0))
+    }
+
+}